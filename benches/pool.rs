@@ -0,0 +1,115 @@
+// Concurrent reads through the old single `Mutex<DatabaseConnection>` against the connection
+// pool. Every iteration runs READERS threads that each list QUERIES pages of cards.
+//   cargo bench --bench pool
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use rusqlite::{Connection, params};
+
+use card_collection_manager::card::{CardListOptions, CardSort};
+use card_collection_manager::db::{DatabaseConnection, setup};
+use card_collection_manager::pool::DatabasePool;
+
+const READERS: usize = 8;
+const QUERIES: usize = 10;
+const CARDS: i32 = 2000;
+
+// Database file with one series of CARDS cards
+fn bench_db() -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ccm-bench-{}.db", std::process::id()));
+    remove_db(&path);
+    setup(path.to_str().unwrap()).unwrap();
+
+    let mut conn = Connection::open(&path).unwrap();
+    let tx = conn.transaction().unwrap();
+    tx.execute(
+        "INSERT INTO series (name, release_date, n_cards, prefix) VALUES ('Bench', '2025-09-05', ?1, 'BE')",
+        params![CARDS],
+    )
+    .unwrap();
+    for n in 1..=CARDS {
+        // every card is a printing of its own card identity
+        tx.execute(
+            "INSERT INTO card_identities (name) VALUES (?1)",
+            params![format!("Bench Card {}", n)],
+        )
+        .unwrap();
+        tx.execute(
+            "INSERT INTO cards (identity_id, series_id, number, collection_number, in_collection, rarity_id, card_type_id)
+             VALUES (?1, 1, ?2, ?3, 0, ?4, ?5)",
+            params![
+                tx.last_insert_rowid(),
+                format!("BE-{:04}", n),
+                n,
+                n % 7 + 1,
+                n % 13 + 1
+            ],
+        )
+        .unwrap();
+    }
+    tx.commit().unwrap();
+    path
+}
+
+fn remove_db(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
+
+fn list_page(db: &DatabaseConnection, page: usize) {
+    let options = CardListOptions {
+        limit: Some(50),
+        offset: (page * 50) as i64,
+        sort: CardSort::Name,
+        ..Default::default()
+    };
+    db.list_cards(&options).unwrap();
+}
+
+fn concurrent_reads(c: &mut Criterion) {
+    let path = bench_db();
+    let mut group = c.benchmark_group("concurrent_reads");
+    group.sample_size(20);
+
+    let mutex = Mutex::new(DatabaseConnection::new(path.to_str().unwrap()).unwrap());
+    group.bench_function("mutex", |b| {
+        b.iter(|| {
+            thread::scope(|s| {
+                for _ in 0..READERS {
+                    s.spawn(|| {
+                        for page in 0..QUERIES {
+                            list_page(&mutex.lock().unwrap(), page);
+                        }
+                    });
+                }
+            })
+        })
+    });
+    drop(mutex);
+
+    let pool = DatabasePool::open(path.to_str().unwrap(), READERS).unwrap();
+    group.bench_function("pool", |b| {
+        b.iter(|| {
+            thread::scope(|s| {
+                for _ in 0..READERS {
+                    s.spawn(|| {
+                        for page in 0..QUERIES {
+                            list_page(&pool.read(), page);
+                        }
+                    });
+                }
+            })
+        })
+    });
+    drop(pool);
+
+    group.finish();
+    remove_db(&path);
+}
+
+criterion_group!(benches, concurrent_reads);
+criterion_main!(benches);
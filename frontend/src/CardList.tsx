@@ -1,7 +1,10 @@
 import { useEffect, useState } from "react";
 import {
-  getCards,
+  getCardPage,
+  getRarities,
   type Card,
+  type CardListParams,
+  type CardSort,
   updateCard,
   type CardType,
 } from "./services/cards.ts";
@@ -16,7 +19,7 @@ export function CardList({ seriesId }: { seriesId: number | null }) {
   const [visibleCards, setVisibleCards] = useState<Card[]>([]);
   const [search, setSearch] = useState("");
   const [sortConfig, setSortConfig] = useState<{
-    key: keyof Card;
+    key: CardSort;
     direction: "asc" | "desc";
   } | null>(null);
 
@@ -25,43 +28,39 @@ export function CardList({ seriesId }: { seriesId: number | null }) {
     "all" | "collected" | "uncollected"
   >("all");
   const [selectedRarities, setSelectedRarities] = useState<string[]>([]);
-
-  const fetchCards = async (q?: string) => {
-    const data = await getCards(q);
-    setInitialCards(data);
-    setVisibleCards(data);
+  const [rarities, setRarities] = useState<string[]>([]);
+
+  // filters and sorting are applied by the server
+  const fetchCards = async () => {
+    const params: CardListParams = {
+      name: search || undefined,
+      series_id: seriesId ?? undefined,
+      rarity: selectedRarities.length ? selectedRarities.join(",") : undefined,
+      owned:
+        collectionFilter === "all"
+          ? undefined
+          : collectionFilter === "collected",
+      sort: sortConfig?.key,
+      order: sortConfig?.direction,
+    };
+    const page = await getCardPage(params);
+    setInitialCards(page.cards);
   };
 
-  console.log({ initialCards });
-
   useEffect(() => {
-    fetchCards();
+    // only offer rarities that are used by at least one card
+    getRarities().then((data) =>
+      setRarities(data.filter((r) => r.cards > 0).map((r) => r.name))
+    );
   }, []);
 
   useEffect(() => {
-    setVisibleCards(
-      initialCards.filter((c) => {
-        const matchesSearch = c.name
-          .toLowerCase()
-          .includes(search.toLowerCase());
-        const matchesSeries = !seriesId || c.series.id === seriesId;
-
-        let matchesCollected = true;
-        if (collectionFilter === "collected")
-          matchesCollected = (c.in_collection ?? 0) > 0;
-        else if (collectionFilter === "uncollected")
-          matchesCollected = (c.in_collection ?? 0) === 0;
-
-        const matchesRarity =
-          selectedRarities.length === 0 ||
-          selectedRarities.includes(c.rarity.name || "");
+    fetchCards();
+  }, [search, seriesId, collectionFilter, selectedRarities, sortConfig]);
 
-        return (
-          matchesSearch && matchesSeries && matchesCollected && matchesRarity
-        );
-      })
-    );
-  }, [initialCards, search, seriesId, collectionFilter, selectedRarities]);
+  useEffect(() => {
+    setVisibleCards(initialCards);
+  }, [initialCards]);
 
   const handleIncrement = async (card: Card) => {
     //pass null to increment by one
@@ -84,29 +83,14 @@ export function CardList({ seriesId }: { seriesId: number | null }) {
     setInitialCards(newcards);
   };
 
-  const sortBy = (key: keyof Card) => {
+  const sortBy = (key: CardSort) => {
     let direction: "asc" | "desc" = "asc";
     if (sortConfig && sortConfig.key === key && sortConfig.direction === "asc")
       direction = "desc";
-
-    const newcards = [...initialCards].sort((a, b) => {
-      const aValue = a[key] ?? "";
-      const bValue = b[key] ?? "";
-
-      let comparison = 0;
-      if (typeof aValue === "string" && typeof bValue === "string")
-        comparison = aValue.localeCompare(bValue);
-      else if (typeof aValue === "number" && typeof bValue === "number")
-        comparison = aValue - bValue;
-
-      return direction === "asc" ? comparison : -comparison;
-    });
-
-    setInitialCards(newcards);
     setSortConfig({ key, direction });
   };
 
-  const renderSortIcon = (key: keyof Card) => {
+  const renderSortIcon = (key: CardSort) => {
     if (!sortConfig || sortConfig.key !== key) return null;
     return sortConfig.direction === "asc" ? (
       <ChevronUp size={16} className="inline ml-1" />
@@ -115,15 +99,6 @@ export function CardList({ seriesId }: { seriesId: number | null }) {
     );
   };
 
-  const rarities = Array.from(
-    new Set(
-      initialCards
-        .map((c) => c.rarity.name)
-        .filter(Boolean)
-        .sort()
-    )
-  ) as string[];
-
   const countCollected = () => {
     //count number of collected cards (ignoring copies);
     return visibleCards.reduce((acc, card) => {
@@ -255,9 +230,9 @@ export function CardList({ seriesId }: { seriesId: number | null }) {
             </th>
             <th
               className="border p-2 cursor-pointer"
-              onClick={() => sortBy("in_collection")}
+              onClick={() => sortBy("owned")}
             >
-              In Collection {renderSortIcon("in_collection")}
+              In Collection {renderSortIcon("owned")}
             </th>
             <th
               className="border p-2 cursor-pointer"
@@ -267,9 +242,9 @@ export function CardList({ seriesId }: { seriesId: number | null }) {
             </th>
             <th
               className="border p-2 cursor-pointer"
-              onClick={() => sortBy("cardtype")}
+              onClick={() => sortBy("type")}
             >
-              Card-Type {renderSortIcon("cardtype")}
+              Card-Type {renderSortIcon("type")}
             </th>
             <th className="border p-2">Actions</th>
           </tr>
@@ -1,18 +1,49 @@
 import axios from "axios";
 
 export const api = axios.create({
-  baseURL: "http://localhost:3000/api", // your Axum backend
+  // the dev server talks to the Axum backend, a build is served by the backend itself
+  baseURL: import.meta.env.DEV ? "http://localhost:3000/api" : "/api",
 });
 
 export interface Card {
+  identity_id: number;
   name: string;
   number: string;
+  region: string;
   collection_number: number;
   in_collection: number;
   series: Series;
   rarity: Rarity;
   cardtype: CardType;
   cardtype_display: String;
+  text: string | null;
+  // game stats, left out when unknown and for spells and traps
+  attribute?: "DARK" | "LIGHT" | "EARTH" | "WATER" | "FIRE" | "WIND" | "DIVINE";
+  level?: number;
+  rank?: number;
+  link_rating?: number;
+  monster_type?: string;
+  atk?: number;
+  def?: number;
+  copies: Copy[];
+}
+
+export interface SearchResult extends Card {
+  rank: number;
+  name_highlight: string;
+  snippet: string | null;
+}
+
+export interface Copy {
+  id: number;
+  condition: "Mint" | "NM" | "LP" | "MP" | "HP" | "Damaged";
+  edition: "1st" | "Unlimited" | "Limited";
+  language: string;
+  notes: string | null;
+  purchase_price: number | null;
+  purchase_date: string | null;
+  sale_price: number | null;
+  sale_date: string | null;
 }
 
 export interface Rarity {
@@ -25,6 +56,20 @@ export interface CardType {
   sub:String;  
 }
 
+export interface RarityUsage extends Rarity {
+  cards: number;
+  owned_copies: number;
+}
+
+export interface CardTypeUsage extends CardType {
+  id: number;
+  display: string;
+  // frame and abilities, null for card types outside the bundled definitions
+  taxon: { frame: string; abilities: string[] } | null;
+  cards: number;
+  owned_copies: number;
+}
+
 export interface Series {
   name: string;
   id:number;
@@ -33,12 +78,66 @@ export interface Series {
   release_date:string;
 }
 
+export type CardSort =
+  | "name"
+  | "number"
+  | "series"
+  | "rarity"
+  | "release_date"
+  | "owned"
+  | "type";
+
+// query parameters of GET /cards, filtering, sorting and paging happen on the server
+export interface CardListParams {
+  limit?: number;
+  offset?: number;
+  sort?: CardSort;
+  order?: "asc" | "desc";
+  name?: string;
+  series_id?: number;
+  rarity?: string; // comma separated rarity names
+  type?: string;
+  owned?: boolean; // false for missing cards
+}
+
+export interface CardPage {
+  cards: Card[];
+  total: number; // cards matching the filters, over all pages
+  limit: number | null;
+  offset: number;
+}
+
+export async function getCardPage(params: CardListParams = {}) {
+  const res = await api.get<CardPage>("/cards", { params });
+  return res.data;
+}
+
 export async function getCards(query?: string) {
   if (query) {
     const res = await api.post<Card[]>("/cards", { name: query });
     return res.data;
   }
-  const res = await api.get<Card[]>("/cards");
+  return (await getCardPage()).cards;
+}
+
+// query language, e.g. `rarity:"ultra rare" type:trap owned>0`
+export async function findCards(query: string) {
+  const res = await api.post<Card[]>("/cards", { query });
+  return res.data;
+}
+
+export async function searchCards(q: string, limit?: number) {
+  const res = await api.get<SearchResult[]>("/search", { params: { q, limit } });
+  return res.data;
+}
+
+export async function getRarities() {
+  const res = await api.get<RarityUsage[]>("/rarities");
+  return res.data;
+}
+
+export async function getCardTypes() {
+  const res = await api.get<CardTypeUsage[]>("/card-types");
   return res.data;
 }
 
@@ -0,0 +1,114 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::{Copy, MarketPrice};
+use crate::cardtype::CardType;
+use crate::deck::Deck;
+use crate::identity::CardIdentity;
+use crate::jsoncards::PasscodeJson;
+use crate::rarity::Rarity;
+use crate::series::Series;
+use crate::want::NewWant;
+
+/// Version of the backup document layout, bumped on incompatible changes
+pub const BACKUP_FORMAT_VERSION: i32 = 1;
+
+/// Card as stored in a backup. Series, rarity and card type are referenced by name,
+/// so a backup can be restored into a database with different ids.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupCard {
+    pub number: String,
+    pub name: String,
+    pub series: String,
+    pub collection_number: i32,
+    pub rarity: String,
+    pub cardtype: CardType,
+    #[serde(default)]
+    pub passcode: Option<i64>,
+    #[serde(default)]
+    pub text: Option<String>,
+    /// All copies, including sold ones
+    #[serde(default)]
+    pub copies: Vec<Copy>,
+}
+
+/// Regional printing of a series as stored in a backup, the series referenced by name
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupRegion {
+    pub series: String,
+    pub region: String,
+    pub language: String,
+    #[serde(default)]
+    pub release_date: Option<String>,
+    pub n_cards: i32,
+}
+
+/// Full contents of a collection database
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Backup {
+    pub format_version: i32,
+    pub schema_version: i32,
+    pub created_at: String,
+    pub rarities: Vec<Rarity>,
+    pub card_types: Vec<CardType>,
+    pub series: Vec<Series>,
+    #[serde(default)]
+    pub regions: Vec<BackupRegion>,
+    /// Matched by name on restore, the ids are those of the backed up database
+    #[serde(default)]
+    pub identities: Vec<CardIdentity>,
+    pub cards: Vec<BackupCard>,
+    #[serde(default)]
+    pub prices: Vec<MarketPrice>,
+    #[serde(default)]
+    pub wants: Vec<NewWant>,
+    #[serde(default)]
+    pub decks: Vec<Deck>,
+    #[serde(default)]
+    pub passcodes: Vec<PasscodeJson>,
+}
+
+/// What to do with cards, wants and decks that already exist when restoring
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictPolicy {
+    /// Keep the existing entry, ignore the one in the backup
+    Skip,
+    /// Replace the existing entry (including its copies) with the one in the backup
+    Overwrite,
+    /// Keep the existing entry and add the copies and quantities of the backup to it
+    MergeQuantities,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "skip" => Ok(ConflictPolicy::Skip),
+            "overwrite" => Ok(ConflictPolicy::Overwrite),
+            "merge-quantities" | "merge" => Ok(ConflictPolicy::MergeQuantities),
+            _ => Err(format!(
+                "unknown conflict policy '{}', expected one of skip|overwrite|merge-quantities",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RestoreReport {
+    pub series: i32,
+    pub regions: i32,
+    pub identities: i32,
+    pub cards_inserted: i32,
+    pub cards_skipped: i32,
+    pub cards_overwritten: i32,
+    pub cards_merged: i32,
+    pub copies: i32,
+    pub prices: i32,
+    pub wants: i32,
+    pub decks: i32,
+    pub passcodes: i32,
+}
@@ -1,48 +1,116 @@
-use axum::http::HeaderValue;
-use card_collection_manager::routes::api;
-
-use tower_http::cors::{Any, CorsLayer};
-
-use axum::{Router, serve};
 use card_collection_manager::AppState;
-use card_collection_manager::db::setup;
+use card_collection_manager::config::ServerConfig;
+use card_collection_manager::pool::DatabasePool;
+use card_collection_manager::routes::app;
+
+use axum::serve;
+use tracing_subscriber::EnvFilter;
 
-use std::net::SocketAddr;
-use std::sync::{Arc, Mutex};
+use std::fmt::Display;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use clap::Parser;
 
 #[derive(Parser, Debug)]
 #[command(name = "app", version, about = "Card DB Server")]
 pub struct Args {
-    /// Database file name    
+    /// Database file name
     pub dbname: String,
+
+    /// TOML file with the server settings, overridden by the flags below
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+
+    /// Address to listen on [default: 0.0.0.0]
+    #[arg(long)]
+    pub bind: Option<IpAddr>,
+
+    /// Port to listen on [default: 3000]
+    #[arg(short, long)]
+    pub port: Option<u16>,
+
+    /// Origin allowed to call the API, repeat for several origins or use "*" for any
+    /// [default: http://localhost:5173]
+    #[arg(long = "cors-origin")]
+    pub cors_origins: Vec<String>,
+
+    /// Log filter, e.g. "debug" or "card_collection_manager=debug,tower_http=debug" [default: info]
+    #[arg(long)]
+    pub log_level: Option<String>,
+
+    /// Serve the built frontend (e.g. frontend/dist) next to the API
+    #[arg(long)]
+    pub static_dir: Option<PathBuf>,
+
+    /// Number of read-only database connections, defaults to the number of CPUs
+    #[arg(long)]
+    pub readers: Option<usize>,
+}
+
+impl Args {
+    /// Settings from the config file (or the defaults) with the given flags applied
+    fn config(&self) -> Result<ServerConfig, Box<dyn std::error::Error>> {
+        let mut config = match &self.config {
+            Some(path) => ServerConfig::load(path)?,
+            None => ServerConfig::default(),
+        };
+        if let Some(bind) = self.bind {
+            config.bind = bind;
+        }
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if !self.cors_origins.is_empty() {
+            config.cors_origins = self.cors_origins.clone();
+        }
+        if let Some(log_level) = &self.log_level {
+            config.log_level = log_level.clone();
+        }
+        if self.static_dir.is_some() {
+            config.static_dir = self.static_dir.clone();
+        }
+        if self.readers.is_some() {
+            config.readers = self.readers;
+        }
+        Ok(config)
+    }
+}
+
+fn exit_with(message: impl Display) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1)
 }
 
 #[tokio::main]
 async fn main() {
     let args = Args::parse(); // Parse CLI arguments
+    let config = args.config().unwrap_or_else(|e| exit_with(e));
 
-    let conn = setup(&args.dbname).expect("failed to setup database");
+    let filter = EnvFilter::try_new(&config.log_level)
+        .unwrap_or_else(|e| exit_with(format!("Invalid log level {}: {}", config.log_level, e)));
+    tracing_subscriber::fmt().with_env_filter(filter).init();
 
-    let state = Arc::new(AppState {
-        db: Arc::new(Mutex::new(conn)), // wrap DatabaseConnection in Mutex + Arc
+    let readers = config.readers.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(4, std::num::NonZeroUsize::get)
     });
+    let pool = DatabasePool::open(&args.dbname, readers)
+        .unwrap_or_else(|e| exit_with(format!("Failed to set up database: {}", e)));
+    tracing::info!("Using {} read connections", pool.readers());
 
-    let cors = CorsLayer::new()
-        .allow_origin(HeaderValue::from_static("http://localhost:5173"))
-        .allow_methods(Any)
-        .allow_headers(Any);
-
-    let app = Router::new()
-        .nest("/api", api::routes())
-        .layer(cors)
-        .with_state(state);
-
-    let addr: SocketAddr = "0.0.0.0:3000".parse().unwrap();
-    println!("🚀 Server running at http://{}", addr);
+    let state = Arc::new(AppState {
+        db: Arc::new(pool), // one writer and a set of readers, shared by all requests
+    });
+    let app = app::router(&config, state).unwrap_or_else(|e| exit_with(e));
 
-    // 👇 new Axum 0.8 style
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    let addr = config.addr();
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .unwrap_or_else(|e| exit_with(format!("Could not listen on {}: {}", addr, e)));
+    match &config.static_dir {
+        Some(dir) => tracing::info!("Serving {} at http://{}", dir.display(), addr),
+        None => tracing::info!("Server running at http://{}/api", addr),
+    }
     serve(listener, app).await.unwrap();
 }
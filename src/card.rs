@@ -1,6 +1,9 @@
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-use crate::{cardtype::CardType, rarity::Rarity, series::Series};
+use crate::{cardtype::CardType, identity::CardStats, rarity::Rarity, series::Series};
 #[derive(Debug, Serialize, Deserialize, Clone)]
 
 pub struct DatabaseCard {
@@ -15,11 +18,303 @@ pub struct DatabaseCard {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Card {
+    /// Id of the card identity this is a printing of
+    #[serde(default)]
+    pub identity_id: i32,
     pub name: String,
     pub number: String,
+    /// Region code of the number, e.g. `EN` for LOB-EN001
+    #[serde(default)]
+    pub region: String,
     pub collection_number: i32,
     pub in_collection: i32,
     pub series: Series,
     pub rarity: Rarity,
     pub cardtype: CardType,
+    pub passcode: Option<i64>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(flatten)]
+    pub stats: CardStats,
+    pub copies: Vec<Copy>,
+}
+
+/// Physical state of an owned copy
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub enum Condition {
+    Mint,
+    #[serde(rename = "NM")]
+    NearMint,
+    #[serde(rename = "LP")]
+    LightlyPlayed,
+    #[serde(rename = "MP")]
+    ModeratelyPlayed,
+    #[serde(rename = "HP")]
+    HeavilyPlayed,
+    Damaged,
+}
+
+impl Condition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Condition::Mint => "Mint",
+            Condition::NearMint => "NM",
+            Condition::LightlyPlayed => "LP",
+            Condition::ModeratelyPlayed => "MP",
+            Condition::HeavilyPlayed => "HP",
+            Condition::Damaged => "Damaged",
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['-', '_'], " ").as_str() {
+            "mint" | "m" => Ok(Condition::Mint),
+            "nm" | "near mint" => Ok(Condition::NearMint),
+            "lp" | "lightly played" => Ok(Condition::LightlyPlayed),
+            "mp" | "moderately played" => Ok(Condition::ModeratelyPlayed),
+            "hp" | "heavily played" => Ok(Condition::HeavilyPlayed),
+            "damaged" | "dmg" => Ok(Condition::Damaged),
+            _ => Err(format!(
+                "unknown condition '{}', expected one of Mint|NM|LP|MP|HP|Damaged",
+                s
+            )),
+        }
+    }
+}
+
+/// Print run an owned copy belongs to
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub enum Edition {
+    #[serde(rename = "1st")]
+    First,
+    Unlimited,
+    Limited,
+}
+
+impl Edition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Edition::First => "1st",
+            Edition::Unlimited => "Unlimited",
+            Edition::Limited => "Limited",
+        }
+    }
+}
+
+impl FromStr for Edition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "1st" | "first" | "1st edition" | "first edition" => Ok(Edition::First),
+            "unlimited" | "unl" => Ok(Edition::Unlimited),
+            "limited" | "limited edition" => Ok(Edition::Limited),
+            _ => Err(format!(
+                "unknown edition '{}', expected one of 1st|Unlimited|Limited",
+                s
+            )),
+        }
+    }
+}
+
+// Enums stored as text by their `as_str` name, needs `FromStr<Err = String>`
+macro_rules! sql_as_str {
+    ($t:ty) => {
+        impl std::fmt::Display for $t {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl rusqlite::types::ToSql for $t {
+            fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+                Ok(rusqlite::types::ToSqlOutput::from(self.as_str()))
+            }
+        }
+
+        impl rusqlite::types::FromSql for $t {
+            fn column_result(
+                value: rusqlite::types::ValueRef<'_>,
+            ) -> rusqlite::types::FromSqlResult<Self> {
+                value
+                    .as_str()?
+                    .parse()
+                    .map_err(|e: String| rusqlite::types::FromSqlError::Other(e.into()))
+            }
+        }
+    };
+}
+pub(crate) use sql_as_str;
+
+sql_as_str!(Condition);
+sql_as_str!(Edition);
+
+/// A single physical copy of a card in the collection
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Copy {
+    pub id: i32,
+    pub condition: Condition,
+    pub edition: Edition,
+    pub language: String,
+    pub notes: Option<String>,
+    pub purchase_price: Option<f64>,
+    pub purchase_date: Option<String>,
+    pub sale_price: Option<f64>,
+    pub sale_date: Option<String>,
+}
+
+impl Copy {
+    pub fn display(&self) -> String {
+        match &self.notes {
+            Some(notes) => format!(
+                "{} {} {} ({})",
+                self.condition, self.edition, self.language, notes
+            ),
+            None => format!("{} {} {}", self.condition, self.edition, self.language),
+        }
+    }
+}
+
+/// Attributes used to create copies (`collect`) or to select them (`sell`).
+/// Fields left empty fall back to the defaults when collecting and match anything when selling.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct CopyAttributes {
+    pub condition: Option<Condition>,
+    pub edition: Option<Edition>,
+    pub language: Option<String>,
+    pub notes: Option<String>,
+    /// Purchase price when collecting, sale price when selling
+    pub price: Option<f64>,
+    /// Purchase date when collecting, sale date when selling (%Y-%m-%d, defaults to today)
+    pub date: Option<String>,
+}
+
+/// Market price of a card number in a given condition at a point in time
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MarketPrice {
+    pub number: String,
+    pub rarity: String,
+    pub condition: Condition,
+    pub price: f64,
+    pub date: String,
+}
+
+/// How a collect or sell of several card numbers handles numbers that fail
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum BatchMode {
+    /// Roll back every card when a single one fails
+    #[default]
+    AllOrNothing,
+    /// Apply the cards that succeed and report the ones that fail
+    BestEffort,
+}
+
+impl FromStr for BatchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "all-or-nothing" | "atomic" => Ok(BatchMode::AllOrNothing),
+            "best-effort" => Ok(BatchMode::BestEffort),
+            _ => Err(format!(
+                "unknown mode '{}', expected all-or-nothing or best-effort",
+                s
+            )),
+        }
+    }
+}
+
+/// Outcome of a collect or sell for a single card number
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct CardResult {
+    pub number: String,
+    /// Copies in the collection afterwards, `None` when the card failed
+    pub in_collection: Option<i32>,
+    pub error: Option<String>,
+}
+
+impl CardResult {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// What a card listing is sorted on, ties are broken on the card number
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CardSort {
+    Name,
+    Number,
+    /// Series name
+    Series,
+    /// Rarity in the order rarities were defined, roughly from common to rare
+    Rarity,
+    /// Release date of the series
+    #[default]
+    ReleaseDate,
+    /// Copies in the collection
+    Owned,
+    /// Full card type name, e.g. "Effect Monster"
+    Type,
+}
+
+impl CardSort {
+    // SQL expression over the aliases of the card query
+    pub fn column(&self) -> &'static str {
+        match self {
+            CardSort::Name => "i.name COLLATE NOCASE",
+            CardSort::Number => "c.number",
+            CardSort::Series => "s.name COLLATE NOCASE",
+            CardSort::Rarity => "r.id",
+            CardSort::ReleaseDate => "s.release_date",
+            CardSort::Owned => "c.in_collection",
+            CardSort::Type => "t.subtype || ' ' || t.maintype",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Filters, sorting and paging of a card listing, as query parameters of `GET /api/cards`
+#[derive(Debug, Serialize, Deserialize, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CardListOptions {
+    /// Page size, all cards when missing
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub offset: i64,
+    #[serde(default)]
+    pub sort: CardSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Part of the card name
+    pub name: Option<String>,
+    pub series_id: Option<i32>,
+    /// Rarity names, comma separated
+    pub rarity: Option<String>,
+    /// Main type, sub type or full type name, e.g. `trap`, `effect` or `effect monster`
+    #[serde(rename = "type")]
+    pub cardtype: Option<String>,
+    /// `true` for cards with copies in the collection, `false` for missing cards
+    pub owned: Option<bool>,
+}
+
+/// One page of a card listing with the number of cards matching the filters
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CardPage {
+    pub cards: Vec<Card>,
+    pub total: i64,
+    pub limit: Option<i64>,
+    pub offset: i64,
 }
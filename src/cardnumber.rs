@@ -0,0 +1,167 @@
+// Card numbers and lists/ranges of them as typed on the command line.
+//
+// A card number is `<set code>-<region code><number>`, e.g. `LOB-EN001`, `SDK-E001` or `LOB-001`
+// (no region code). The number keeps its zero-padded width, so formatting gives back the same text.
+//
+// A card number spec is a comma separated list of items, each a single number or a range:
+//   LOB-EN001                  single card
+//   LOB-EN001..EN010           range, the end may be `LOB-EN010`, `EN010` or `010`
+//   LOB-EN001-EN010            legacy range syntax, same as above
+//   LOB-EN001..EN010,EN020     later items without a set code reuse the previous set (and region)
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Largest number of cards a single range may expand to, guards against typos like `LOB-001..100000`
+pub const MAX_RANGE: u32 = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardNumber {
+    pub set_code: String,
+    pub region: Option<String>,
+    pub number: u32,
+    /// Number of digits, including leading zeroes
+    pub width: usize,
+}
+
+impl CardNumber {
+    // Same set and region with another number, keeping the width
+    fn with_number(&self, number: u32) -> CardNumber {
+        CardNumber {
+            number,
+            ..self.clone()
+        }
+    }
+}
+
+impl fmt::Display for CardNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}{:0width$}",
+            self.set_code,
+            self.region.as_deref().unwrap_or(""),
+            self.number,
+            width = self.width
+        )
+    }
+}
+
+impl FromStr for CardNumber {
+    type Err = String;
+
+    /// Parse a single full card number like `LOB-EN001`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (set_code, suffix) = s
+            .split_once('-')
+            .ok_or_else(|| format!("'{}' is not a card number, expected e.g. LOB-EN001", s))?;
+        if set_code.is_empty() || !set_code.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("'{}' has an invalid set code '{}'", s, set_code));
+        }
+        let (region, number, width) =
+            parse_suffix(suffix).map_err(|e| format!("'{}' is not a card number: {}", s, e))?;
+        Ok(CardNumber {
+            set_code: set_code.to_string(),
+            region,
+            number,
+            width,
+        })
+    }
+}
+
+// Split the part after the set code, e.g. "EN001" -> (Some("EN"), 1, 3)
+fn parse_suffix(s: &str) -> Result<(Option<String>, u32, usize), String> {
+    let digits_start = s.find(|c: char| c.is_ascii_digit()).unwrap_or(s.len());
+    let (region, digits) = s.split_at(digits_start);
+    if !region.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("invalid region code '{}'", region));
+    }
+    if digits.is_empty() {
+        return Err(format!("'{}' has no number", s));
+    }
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("'{}' must end in digits only", s));
+    }
+    let number = digits
+        .parse::<u32>()
+        .map_err(|_| format!("number '{}' is too large", digits))?;
+    let region = (!region.is_empty()).then(|| region.to_string());
+    Ok((region, number, digits.len()))
+}
+
+// Parse an item that may leave out the set code (and region) of `base`
+fn parse_relative(s: &str, base: Option<&CardNumber>) -> Result<CardNumber, String> {
+    if s.contains('-') {
+        return s.parse();
+    }
+    let base = base.ok_or_else(|| {
+        format!(
+            "'{}' has no set code and there is no earlier card number to take it from",
+            s
+        )
+    })?;
+    let (region, number, width) = parse_suffix(s).map_err(|e| format!("'{}': {}", s, e))?;
+    Ok(CardNumber {
+        set_code: base.set_code.clone(),
+        region: region.or_else(|| base.region.clone()),
+        number,
+        width,
+    })
+}
+
+/// Parse a card number spec (see the module comment) into single card numbers, in order
+pub fn parse_card_numbers(spec: &str) -> Result<Vec<CardNumber>, String> {
+    let mut numbers = Vec::new();
+    let mut previous: Option<CardNumber> = None;
+
+    for item in spec.split(',') {
+        let item = item.trim();
+        if item.is_empty() {
+            return Err(format!("'{}' contains an empty item", spec));
+        }
+
+        let (start, end) = match item.split_once("..") {
+            Some((start, end)) => (start, Some(end)),
+            // legacy syntax LOB-EN001-EN010
+            None => match item.splitn(3, '-').collect::<Vec<_>>()[..] {
+                [set_code, start, end] => (&item[..set_code.len() + 1 + start.len()], Some(end)),
+                _ => (item, None),
+            },
+        };
+
+        let start = parse_relative(start.trim(), previous.as_ref())?;
+        match end {
+            None => numbers.push(start.clone()),
+            Some(end) => {
+                let end = parse_relative(end.trim(), Some(&start))?;
+                if end.set_code != start.set_code || end.region != start.region {
+                    return Err(format!(
+                        "range '{}' must stay within one set and region",
+                        item
+                    ));
+                }
+                if end.number < start.number {
+                    return Err(format!("range '{}' ends before it starts", item));
+                }
+                if end.number - start.number >= MAX_RANGE {
+                    return Err(format!(
+                        "range '{}' is larger than {} cards",
+                        item, MAX_RANGE
+                    ));
+                }
+                numbers.extend((start.number..=end.number).map(|n| start.with_number(n)));
+            }
+        }
+        previous = Some(start);
+    }
+    Ok(numbers)
+}
+
+/// Expand a card number spec into formatted card numbers
+pub fn expand_card_numbers(spec: &str) -> Result<Vec<String>, String> {
+    Ok(parse_card_numbers(spec)?
+        .iter()
+        .map(CardNumber::to_string)
+        .collect())
+}
@@ -1,5 +1,14 @@
+// Card types are stored as a main and a sub type ("Effect" "Monster"). Categories read from
+// the wiki are mapped onto them through a taxonomy of frames and abilities that is loaded
+// from the bundled `cardtypes.toml`.
+
+use std::error::Error;
+use std::sync::LazyLock;
+
 use serde::{Deserialize, Serialize};
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
 pub struct CardType {
     pub main: String,
     pub sub: String,
@@ -10,3 +19,163 @@ impl CardType {
         format!("{} {}", self.sub, self.main)
     }
 }
+
+/// A card type with the number of cards of that type and the owned copies of those cards
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CardTypeUsage {
+    pub id: i32,
+    #[serde(flatten)]
+    pub cardtype: CardType,
+    pub display: String,
+    /// Frame and abilities, missing for card types outside the taxonomy
+    pub taxon: Option<CardTypeTaxon>,
+    pub cards: i32,
+    pub owned_copies: i32,
+}
+
+/// Frame and abilities of a card type, as read from a category by `CardTypeDefinitions::parse`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct CardTypeTaxon {
+    /// Frame name, e.g. "Synchro" or "Spell"
+    pub frame: String,
+    /// Abilities in definition order, e.g. ["Tuner", "Effect"], empty for the frame default
+    pub abilities: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FrameDefinition {
+    pub name: String,
+    /// Main type the frame is stored as, e.g. "Synchro Monster"
+    pub main: String,
+    /// Lowercase words that identify the frame in a category
+    pub words: Vec<String>,
+    /// Names of the abilities a card of this frame can have
+    pub abilities: Vec<String>,
+    /// Sub type of a category without abilities
+    pub default: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AbilityDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// Card type taxonomy, see the bundled `cardtypes.toml` for the format
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CardTypeDefinitions {
+    /// Words of a category that are neither frame nor ability, e.g. "card"
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    pub frames: Vec<FrameDefinition>,
+    pub abilities: Vec<AbilityDefinition>,
+}
+
+static BUNDLED: LazyLock<CardTypeDefinitions> = LazyLock::new(|| {
+    CardTypeDefinitions::from_toml(include_str!("cardtypes.toml"))
+        .expect("bundled cardtypes.toml is valid")
+});
+
+impl CardTypeDefinitions {
+    /// The definitions shipped with the binary
+    pub fn bundled() -> &'static CardTypeDefinitions {
+        &BUNDLED
+    }
+
+    /// Parse definitions, every ability a frame names must be defined
+    pub fn from_toml(text: &str) -> Result<Self, Box<dyn Error>> {
+        let definitions: CardTypeDefinitions = toml::from_str(text)?;
+        for frame in &definitions.frames {
+            for ability in frame.abilities.iter().chain([&frame.default]) {
+                if !definitions.abilities.iter().any(|a| &a.name == ability) {
+                    return Err(format!(
+                        "frame '{}' names undefined ability '{}'",
+                        frame.name, ability
+                    )
+                    .into());
+                }
+            }
+        }
+        Ok(definitions)
+    }
+
+    fn frame(&self, name: &str) -> Option<&FrameDefinition> {
+        self.frames.iter().find(|f| f.name == name)
+    }
+
+    /// Frame and abilities of a category like "Synchro Tuner Effect Monster" or
+    /// "Quick-Play Spell Card". Words are matched ignoring case and order.
+    pub fn parse(&self, category: &str) -> Result<CardTypeTaxon, String> {
+        let words: Vec<String> = category
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        let frame = self
+            .frames
+            .iter()
+            .find(|f| f.words.iter().any(|w| words.contains(w)))
+            .ok_or_else(|| format!("'{}' names no card frame", category))?;
+
+        let mut abilities = Vec::new();
+        for word in &words {
+            if frame.words.contains(word) || self.ignore.contains(word) {
+                continue;
+            }
+            let ability = self
+                .abilities
+                .iter()
+                .find(|a| {
+                    a.name.eq_ignore_ascii_case(word)
+                        || a.aliases
+                            .iter()
+                            .any(|alias| alias.eq_ignore_ascii_case(word))
+                })
+                .filter(|a| frame.abilities.contains(&a.name))
+                .ok_or_else(|| {
+                    format!(
+                        "unknown word '{}' in '{}', a {} card can be {}",
+                        word,
+                        category,
+                        frame.name,
+                        frame.abilities.join(", ")
+                    )
+                })?;
+            if !abilities.contains(&ability.name) {
+                abilities.push(ability.name.clone());
+            }
+        }
+        // definition order, so that categories listing the same abilities agree
+        abilities.sort_by_key(|name| self.abilities.iter().position(|a| &a.name == name));
+
+        Ok(CardTypeTaxon {
+            frame: frame.name.clone(),
+            abilities,
+        })
+    }
+
+    /// Main and sub type a frame and its abilities are stored as
+    pub fn card_type(&self, taxon: &CardTypeTaxon) -> Option<CardType> {
+        let frame = self.frame(&taxon.frame)?;
+        let sub = if taxon.abilities.is_empty() {
+            frame.default.clone()
+        } else {
+            taxon.abilities.join(" ")
+        };
+        Some(CardType {
+            main: frame.main.clone(),
+            sub,
+        })
+    }
+
+    /// Main and sub type of a category, see `parse`
+    pub fn parse_card_type(&self, category: &str) -> Result<CardType, String> {
+        let taxon = self.parse(category)?;
+        self.card_type(&taxon)
+            .ok_or_else(|| format!("'{}' names no card frame", category))
+    }
+}
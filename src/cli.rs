@@ -1,5 +1,9 @@
 use clap::{Parser, Subcommand};
 
+use crate::backup::ConflictPolicy;
+use crate::card::{BatchMode, Condition, Edition};
+use crate::deck::DeckSection;
+
 #[derive(Parser, Debug)]
 #[command(name = "app", version, about = "Card DB CLI")]
 pub struct Args {
@@ -26,14 +30,19 @@ pub enum Command {
 
         /// name for add rarity or card-type
         name: Option<String>,
+
+        /// Offer to create the card types of add json that are not in the database yet
+        #[arg(long)]
+        create_types: bool,
     },
 
     /// List entities (series, cards)
     List {
-        /// Kind of entity to list [serie | series | cards |rarities|card-types]
+        /// Kind of entity to list [serie | series | regions | slot | identities | cards |rarities|card-types]
         kind: String,
 
-        /// series name filter (for list serie --name)
+        /// series name filter (for list serie --name and list regions --name),
+        /// card number for list slot --name, part of the card name for list identities --name
         #[arg(long)]
         name: Option<String>,
 
@@ -45,38 +54,234 @@ pub enum Command {
         /// Format options:
         /// {name}=card name
         /// {number}=card number
+        /// {region}=region code of the number, e.g. EN or DE
         /// {collection_number}=unique collection id
         /// {rarity}=rarity name
         /// {series}=series name
         /// {cardtype}=card type
         /// {in_collection}=copies in collection
+        /// {attribute}=attribute of a monster, e.g. DARK
+        /// {level}=level, rank or link rating, e.g. "Level 7" or "Rank 4"
+        /// {monster_type}=monster type, e.g. Spellcaster
+        /// {atk}={def}=ATK and DEF, "?" when unknown
+        /// {copies}=condition, edition and language of each copy
         #[arg(long, default_value = "|{series}|{number}|{name}|")]
         formatter: String,
     },
 
     /// Collect a card
     Collect {
-        /// Card ID to collect
+        /// Card IDs or ranges to collect, e.g. LOB-001 or LOB-001-010
         #[arg(long, num_args = 1..)]
         id: Vec<String>,
 
-        /// Set all cards to this number of cards in the collection
+        /// Number of copies to add to the collection (defaults to 1)
         #[arg(long)]
         count: Option<i32>,
+
+        /// Condition of the copies [Mint | NM | LP | MP | HP | Damaged] (defaults to NM)
+        #[arg(long)]
+        condition: Option<Condition>,
+
+        /// Edition of the copies [1st | Unlimited | Limited] (defaults to Unlimited)
+        #[arg(long)]
+        edition: Option<Edition>,
+
+        /// Language of the copies, e.g. EN, DE, FR (defaults to EN)
+        #[arg(long)]
+        language: Option<String>,
+
+        /// Free text notes stored with each copy
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Purchase price paid per copy
+        #[arg(long)]
+        price: Option<f64>,
+
+        /// Purchase date (%Y-%m-%d, defaults to today)
+        #[arg(long)]
+        date: Option<String>,
+
+        /// How to handle card numbers that fail [all-or-nothing | best-effort]
+        #[arg(long, default_value = "all-or-nothing")]
+        mode: BatchMode,
     },
     /// Sell a card
     Sell {
-        /// Card ID to sell
+        /// Card IDs or ranges to sell, e.g. LOB-001 or LOB-001-010
         #[arg(long, num_args = 1..)]
         id: Vec<String>,
 
         #[arg(long, default_value = "1")]
         count: i32,
+
+        /// Only sell copies in this condition
+        #[arg(long)]
+        condition: Option<Condition>,
+
+        /// Only sell copies of this edition
+        #[arg(long)]
+        edition: Option<Edition>,
+
+        /// Only sell copies in this language
+        #[arg(long)]
+        language: Option<String>,
+
+        /// Sale price received per copy
+        #[arg(long)]
+        price: Option<f64>,
+
+        /// Sale date (%Y-%m-%d, defaults to today)
+        #[arg(long)]
+        date: Option<String>,
+
+        /// How to handle card numbers that fail [all-or-nothing | best-effort]
+        #[arg(long, default_value = "all-or-nothing")]
+        mode: BatchMode,
+    },
+
+    /// Record a market price for a card, or show its price history when --price is omitted
+    Price {
+        /// Card ID to price (e.g. LOB-001)
+        #[arg(long)]
+        id: String,
+
+        /// Market price of one copy
+        #[arg(long)]
+        price: Option<f64>,
+
+        /// Condition the price applies to
+        #[arg(long, default_value = "NM")]
+        condition: Condition,
+
+        /// Date of the price (%Y-%m-%d, defaults to today)
+        #[arg(long)]
+        date: Option<String>,
+    },
+
+    /// Report market value, cost basis and profit of the collection per series
+    Value {},
+    /// Report completion of each series: cards owned, copies and duplicates
+    Stats {
+        /// Also show the completion per rarity
+        #[arg(short, long)]
+        rarities: bool,
+    },
+    /// Manage the want list [add | remove | list | missing]
+    Want {
+        /// Action to perform [add | remove | list | missing]
+        action: String,
+
+        /// Card ID (required for add and remove)
+        #[arg(long)]
+        id: Option<String>,
+
+        /// Number of copies wanted
+        #[arg(long, default_value = "1")]
+        quantity: i32,
+
+        /// Maximum price you are willing to pay per copy
+        #[arg(long)]
+        max_price: Option<f64>,
+
+        /// Priority, higher is more important
+        #[arg(long, default_value = "0")]
+        priority: i32,
+
+        /// Only report missing cards of this series (for want missing)
+        #[arg(long)]
+        series: Option<String>,
+    },
+    /// Build decks from card names [create | delete | add | remove | show | list]
+    Deck {
+        /// Action to perform [create | delete | add | remove | show | list]
+        action: String,
+
+        /// Deck name (required for all actions except list)
+        name: Option<String>,
+
+        /// Card name (required for add and remove)
+        #[arg(long)]
+        card: Option<String>,
+
+        /// Deck section [main | extra | side]
+        #[arg(long, default_value = "main")]
+        section: DeckSection,
+
+        /// Number of copies to add or remove
+        #[arg(long, default_value = "1")]
+        quantity: i32,
+    },
+    /// Import data from a file [ydk | passcodes | csv | html]
+    Import {
+        /// Kind of file to import [ydk | passcodes | csv | html]
+        kind: String,
+
+        /// File to read
+        #[arg(short, long)]
+        file: String,
+
+        /// Deck name for import ydk (defaults to the file name)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Only report what import csv would do, without changing the database
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Update existing cards (matched by number) on import csv instead of skipping them
+        #[arg(long)]
+        upsert: bool,
+
+        /// Region tab of the card list for import html, e.g. "English (eu)" or "French",
+        /// or "all" for every tab (defaults to the tab open when the page loads)
+        #[arg(long)]
+        region: Option<String>,
+
+        /// Write the series json of import html to this file instead of adding the series
+        #[arg(long)]
+        output: Option<String>,
+
+        /// Offer to create the card types of import csv or html that are not in the database yet
+        #[arg(long)]
+        create_types: bool,
+    },
+
+    /// Export data to a file [ydk | csv]
+    Export {
+        /// Kind of file to export [ydk | csv]
+        kind: String,
+
+        /// File to write
+        #[arg(short, long)]
+        file: String,
+
+        /// Deck to export (exports all owned cards when omitted)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Write every table of the database to a JSON backup file
+    Backup {
+        /// File to write
+        #[arg(short, long)]
+        file: String,
+    },
+    /// Restore a JSON backup into this database
+    Restore {
+        /// File to read
+        #[arg(short, long)]
+        file: String,
+
+        /// What to do with existing cards, wants and decks [skip | overwrite | merge-quantities]
+        #[arg(long, default_value = "skip")]
+        policy: ConflictPolicy,
     },
     Find {
         /// Kind of entity to list [serie | cards]
         kind: String,
 
+        /// Words to search for in card names, numbers, series and card text (prefixes match too)
         query: Option<String>,
 
         //hides card already in collection (defaults to false)
@@ -87,12 +292,19 @@ pub enum Command {
         /// Format options:
         /// {name}=card name
         /// {number}=card number
+        /// {region}=region code of the number, e.g. EN or DE
         /// {collection_number}=unique collection id
         /// {rarity}=rarity name
         /// {series}=series name
         /// {cardtype}=card type
         /// {in_collection}=copies in collection
-        #[arg(long, default_value = "|{series}|{number}|{name}|")]
+        /// {attribute}=attribute of a monster, e.g. DARK
+        /// {level}=level, rank or link rating, e.g. "Level 7" or "Rank 4"
+        /// {monster_type}=monster type, e.g. Spellcaster
+        /// {atk}={def}=ATK and DEF, "?" when unknown
+        /// {copies}=condition, edition and language of each copy
+        /// {highlight}=card name with the matched words in bold on a terminal
+        #[arg(long, default_value = "|{series}|{number}|{highlight}|")]
         formatter: String,
     },
 }
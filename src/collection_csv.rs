@@ -0,0 +1,127 @@
+// CSV import and export of the whole collection.
+//
+// Column layout (with header row):
+//
+//   series,number,name,rarity,type,owned
+//
+// series: series name, created when it does not exist yet
+// number: card number, e.g. LOB-EN001 (unique)
+// name:   card name
+// rarity: rarity name, must exist (see `add rarity`)
+// type:   card type as shown by `{cardtype}`, e.g. "Effect Monster" or "Continuous Trap Card"
+// owned:  number of copies in the collection
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::card::Card;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CsvRow {
+    pub series: String,
+    pub number: String,
+    pub name: String,
+    pub rarity: String,
+    #[serde(rename = "type")]
+    pub card_type: String,
+    pub owned: i32,
+}
+
+impl From<&Card> for CsvRow {
+    fn from(card: &Card) -> Self {
+        CsvRow {
+            series: card.series.name.clone(),
+            number: card.number.clone(),
+            name: card.name.clone(),
+            rarity: card.rarity.name.clone(),
+            card_type: card.cardtype.display(),
+            owned: card.in_collection,
+        }
+    }
+}
+
+/// A row that could not be imported. `line` is the line number in the file.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CsvRowError {
+    pub line: u64,
+    pub number: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CsvImportOptions {
+    /// Validate and report without changing the database
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Update cards whose number already exists instead of reporting them as errors
+    #[serde(default)]
+    pub upsert: bool,
+    /// Create the card types of valid categories that are not in the database instead of
+    /// reporting their rows as errors
+    #[serde(default)]
+    pub create_types: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct CsvImportReport {
+    pub dry_run: bool,
+    pub inserted: i32,
+    pub updated: i32,
+    pub unchanged: i32,
+    /// Card types created for the import, as shown by `{cardtype}`
+    pub created_card_types: Vec<String>,
+    pub errors: Vec<CsvRowError>,
+}
+
+/// Line number and parsed row of a csv record
+pub type CsvLine = (u64, Result<CsvRow, CsvRowError>);
+
+/// Write cards in the documented column layout
+pub fn write_csv<W: Write>(writer: W, cards: &[Card]) -> Result<(), csv::Error> {
+    let mut wtr = csv::Writer::from_writer(writer);
+    for card in cards {
+        wtr.serialize(CsvRow::from(card))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Read all rows with their line numbers, keeping malformed rows as errors
+/// so they can be reported together with the rows rejected by the database
+pub fn read_csv<R: Read>(reader: R) -> Result<Vec<CsvLine>, csv::Error> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let headers = rdr.headers()?.clone();
+    let number_column = headers.iter().position(|h| h == "number");
+
+    let rows = rdr
+        .records()
+        .map(|record| {
+            let record = record.map_err(|e| CsvRowError {
+                line: e.position().map(|p| p.line()).unwrap_or(0),
+                number: None,
+                message: e.to_string(),
+            });
+            match record {
+                Ok(record) => {
+                    let line = record.position().map(|p| p.line()).unwrap_or(0);
+                    let row =
+                        record
+                            .deserialize::<CsvRow>(Some(&headers))
+                            .map_err(|e| CsvRowError {
+                                line,
+                                number: number_column.and_then(|i| record.get(i)).map(String::from),
+                                message: e.to_string(),
+                            });
+                    (line, row)
+                }
+                Err(e) => (e.line, Err(e)),
+            }
+        })
+        .collect();
+    Ok(rows)
+}
@@ -0,0 +1,65 @@
+// Settings of the API server. They are read from an optional TOML file, e.g.
+//   bind = "127.0.0.1"
+//   port = 8080
+//   cors_origins = ["http://localhost:5173"]
+//   log_level = "debug"
+//   static_dir = "frontend/dist"
+// and single settings can be overridden by the flags of the server binary.
+
+use std::error::Error;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Address to listen on
+    pub bind: IpAddr,
+    pub port: u16,
+    /// Origins allowed to call the API from a browser, `"*"` allows any origin
+    pub cors_origins: Vec<String>,
+    /// Log filter like `info` or `card_collection_manager=debug,tower_http=debug`
+    pub log_level: String,
+    /// Directory with the built frontend (`frontend/dist`), served next to the API
+    pub static_dir: Option<PathBuf>,
+    /// Number of read-only database connections, the number of CPUs when missing
+    pub readers: Option<usize>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            bind: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 3000,
+            cors_origins: vec!["http://localhost:5173".to_string()],
+            log_level: "info".to_string(),
+            static_dir: None,
+            readers: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Parse a config file, settings missing from it keep their default
+    pub fn from_toml(text: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read config {}: {}", path.display(), e))?;
+        ServerConfig::from_toml(&text)
+            .map_err(|e| format!("Invalid config {}: {}", path.display(), e).into())
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind, self.port)
+    }
+
+    /// Whether any origin may call the API
+    pub fn allows_any_origin(&self) -> bool {
+        self.cors_origins.iter().any(|origin| origin == "*")
+    }
+}
@@ -1,47 +1,61 @@
+use std::collections::HashMap;
 use std::error::Error;
+use std::sync::LazyLock;
+use std::time::Duration;
 
-use chrono::NaiveDate;
-use rusqlite::{Connection, Result, params};
+use chrono::{Local, NaiveDate};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Result, params, params_from_iter};
 
-use crate::card::{Card, DatabaseCard};
-use crate::cardtype::CardType;
+use crate::backup::{
+    BACKUP_FORMAT_VERSION, Backup, BackupCard, BackupRegion, ConflictPolicy, RestoreReport,
+};
+use crate::card::{
+    BatchMode, Card, CardListOptions, CardPage, CardResult, Condition, Copy, CopyAttributes,
+    DatabaseCard, Edition, MarketPrice, SortOrder,
+};
+use crate::cardnumber::expand_card_numbers;
+use crate::cardtype::{CardType, CardTypeDefinitions, CardTypeUsage};
+use crate::collection_csv::{CsvImportOptions, CsvImportReport, CsvLine, CsvRow, CsvRowError};
 use crate::dberror::DbError;
+use crate::deck::{
+    Deck, DeckCardRequest, DeckCheck, DeckCommitment, DeckEntry, DeckEntryCheck, DeckSection,
+};
+use crate::identity::{CardIdentity, CardIdentityPrintings, CardStats, Printing};
+use crate::jsoncards::{PasscodeJson, SeriesJson};
+use crate::migrations::{MIGRATIONS, SCHEMA_VERSION};
 
-use crate::rarity::Rarity;
-use crate::series::Series;
+use crate::query::Query;
+use crate::rarity::{Rarity, RarityUsage};
+use crate::search::{Highlight, MATCH_CLOSE, MATCH_OPEN, SearchHit, fts_query};
+use crate::series::{Series, SeriesRegion, SeriesRegionSummary};
+use crate::stats::{
+    CollectionValue, CompletionStats, RarityCompletion, SeriesCompletion, SeriesValue, percentage,
+};
+use crate::want::{NewWant, Want};
+use crate::ydk::{Ydk, YdkReport};
 
-use rusqlite::OptionalExtension; // <- import this
+// How long a connection waits for a lock held by another connection before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub struct DatabaseConnection {
     conn: Connection,
 }
 
-// Helper function to parse a card range like "LOB-001-010"
-fn parse_card_range(card_id: &str) -> Option<(&str, Option<&str>, i32, i32)> {
-    // Expect format: PREFIX-START-END
-    // START and END can be numeric or series+number (like EN001)
-    // Returns: (prefix, optional series prefix, start_number, end_number)
-
-    let parts: Vec<&str> = card_id.split('-').collect();
-    if parts.len() != 3 {
-        return None;
+// Expand card numbers and specs (e.g. "LOB-EN001..EN010,EN020") into single card numbers.
+// An id that is not a valid spec but has no list or range syntax is passed on as is,
+// so unusual card numbers can still be looked up exactly.
+fn expand_card_ids(ids: &[String]) -> Vec<(String, Result<(), DbError>)> {
+    let mut numbers = Vec::new();
+    for id in ids {
+        match expand_card_numbers(id) {
+            Ok(expanded) => numbers.extend(expanded.into_iter().map(|n| (n, Ok(())))),
+            Err(_) if !id.contains(',') && !id.contains("..") && id.matches('-').count() < 2 => {
+                numbers.push((id.clone(), Ok(())))
+            }
+            Err(e) => numbers.push((id.clone(), Err(DbError::InvalidOperation(e)))),
+        }
     }
-
-    let prefix = parts[0];
-    let start_part = parts[1];
-    let end_part = parts[2];
-
-    // Extract series prefix and numeric value from start
-    let start_series_end = start_part.find(|c: char| c.is_digit(10))?;
-    let series_prefix = if start_series_end > 0 {
-        Some(&start_part[..start_series_end])
-    } else {
-        None
-    };
-    let start_num = start_part[start_series_end..].parse::<i32>().ok()?;
-    let end_num = end_part[start_series_end..].parse::<i32>().ok()?; // assume same series prefix for end
-
-    Some((prefix, series_prefix, start_num, end_num))
+    numbers
 }
 
 pub fn get_series_and_number(s: &str) -> (String, i32) {
@@ -65,60 +79,204 @@ pub fn get_series_and_number(s: &str) -> (String, i32) {
     (abbr, number)
 }
 
+enum CsvRowOutcome {
+    Inserted,
+    Updated,
+    Unchanged,
+}
+
+// Validate a %Y-%m-%d date, defaulting to today
+fn parse_date(date: Option<&str>) -> Result<String, DbError> {
+    match date {
+        Some(d) => NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map(|d| d.to_string())
+            .map_err(|_| {
+                DbError::InvalidOperation(format!("Invalid date '{}', expected YYYY-MM-DD", d))
+            }),
+        None => Ok(Local::now().date_naive().to_string()),
+    }
+}
+
+// Dates come as "March 8, 2002" from the series JSON and as 2002-03-08 from the API
+fn parse_release_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%B %d, %Y")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .ok()
+}
+
+// Columns read by `card_from_row`, by position.
+// A row of `cards` is a printing, name, passcode, text and stats come from its identity.
+const CARD_COLUMNS: &str = "
+        i.name, c.series_id, c.number, c.collection_number, c.in_collection,
+        c.rarity_id, c.card_type_id, r.name, t.maintype, t.subtype,
+        s.name, s.prefix, s.release_date, s.n_cards, i.passcode, i.text, c.region,
+        c.identity_id, i.attribute, i.level, i.rank, i.link_rating, i.monster_type,
+        i.atk, i.def";
+
+const CARD_TABLES: &str = "cards c
+        JOIN card_identities i ON c.identity_id = i.id
+        JOIN rarity r ON c.rarity_id = r.id
+        JOIN card_type t ON c.card_type_id = t.id
+        JOIN series s ON c.series_id = s.id";
+
+// Callers append their own WHERE clause
+static CARD_SELECT: LazyLock<String> =
+    LazyLock::new(|| format!("SELECT {} FROM {}", CARD_COLUMNS, CARD_TABLES));
+
+fn card_from_row(row: &rusqlite::Row) -> Result<Card> {
+    let rarity = Rarity {
+        id: row.get(5)?,
+        name: row.get(7)?,
+    };
+
+    let series = Series {
+        id: row.get(1)?,
+        name: row.get(10)?,
+        prefix: row.get(11)?,
+        release_date: row.get(12)?,
+        n_cards: row.get(13)?,
+    };
+    Ok(Card {
+        identity_id: row.get(17)?,
+        name: row.get(0)?,
+        number: row.get(2)?,
+        region: row.get(16)?,
+        collection_number: row.get(3)?,
+        in_collection: row.get(4)?,
+        rarity,
+        cardtype: CardType {
+            main: row.get(8)?,
+            sub: row.get(9)?,
+        },
+        series,
+        passcode: row.get(14)?,
+        text: row.get(15)?,
+        stats: stats_from_row(row, 18)?,
+        copies: Vec::new(),
+    })
+}
+
+// Columns attribute, level, rank, link_rating, monster_type, atk, def of card_identities
+// starting at column `first`
+fn stats_from_row(r: &rusqlite::Row, first: usize) -> Result<CardStats> {
+    Ok(CardStats {
+        attribute: r.get(first)?,
+        level: r.get(first + 1)?,
+        rank: r.get(first + 2)?,
+        link_rating: r.get(first + 3)?,
+        monster_type: r.get(first + 4)?,
+        atk: r.get(first + 5)?,
+        def: r.get(first + 6)?,
+    })
+}
+
+// Columns id, series_id, region, language, release_date, n_cards of series_regions
+fn series_region_from_row(r: &rusqlite::Row) -> Result<SeriesRegion> {
+    Ok(SeriesRegion {
+        id: r.get(0)?,
+        series_id: r.get(1)?,
+        region: r.get(2)?,
+        language: r.get(3)?,
+        release_date: r.get(4)?,
+        n_cards: r.get(5)?,
+    })
+}
+
+// Columns id, name, passcode, text followed by the stats of card_identities
+fn identity_from_row(r: &rusqlite::Row) -> Result<CardIdentity> {
+    Ok(CardIdentity {
+        id: r.get(0)?,
+        name: r.get(1)?,
+        passcode: r.get(2)?,
+        text: r.get(3)?,
+        stats: stats_from_row(r, 4)?,
+    })
+}
+
+const WANT_SELECT: &str = "SELECT
+        c.number, i.name, s.name, r.name, w.quantity, w.max_price, w.priority, c.in_collection
+        FROM wants w
+        JOIN cards c ON w.card_id = c.id
+        JOIN card_identities i ON c.identity_id = i.id
+        JOIN rarity r ON c.rarity_id = r.id
+        JOIN series s ON c.series_id = s.id";
+
+fn want_from_row(row: &rusqlite::Row) -> Result<Want> {
+    Ok(Want {
+        number: row.get(0)?,
+        name: row.get(1)?,
+        series: row.get(2)?,
+        rarity: row.get(3)?,
+        quantity: row.get(4)?,
+        max_price: row.get(5)?,
+        priority: row.get(6)?,
+        in_collection: row.get(7)?,
+    })
+}
+
 impl DatabaseConnection {
     /// Open (or create) a database file
     pub fn new(db_path: &str) -> Result<Self> {
         let conn = Connection::open(db_path)?;
+        // the cascades of the schema need foreign keys, which only the bundled SQLite enables by default
+        conn.pragma_update(None, "foreign_keys", true)?;
         Ok(Self { conn })
     }
 
-    /// Create required tables
-    pub fn create_tables(&self) -> Result<()> {
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS rarity (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL UNIQUE
-            )",
-            [],
+    /// Open an existing database file for reading only, used for the read connections of the server
+    pub fn open_read_only(db_path: &str) -> Result<Self> {
+        let conn = Connection::open_with_flags(
+            db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_URI
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX,
         )?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        Ok(Self { conn })
+    }
 
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS card_type (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                maintype TEXT NOT NULL,  
-                subtype TEXT,
-                UNIQUE (maintype, subtype) -- unique constraint for the two columns together
-            )",
-            [],
-        )?;
+    /// Switch a database file to write-ahead logging, so readers and the writer do not block
+    /// each other. In-memory databases stay in their journal mode, returned as `false`.
+    pub fn enable_wal(&self) -> Result<bool> {
+        self.conn.busy_timeout(BUSY_TIMEOUT)?;
+        let mode: String =
+            self.conn
+                .pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+        Ok(mode.eq_ignore_ascii_case("wal"))
+    }
 
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS series (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        name TEXT NOT NULL UNIQUE,
-        release_date DATE NOT NULL,
-        prefix TEXT,
-        n_cards INTEGER NOT NULL DEFAULT 0
-        )",
-            [],
-        )?;
+    /// Path of the database file, `None` for in-memory databases
+    pub fn path(&self) -> Option<&str> {
+        self.conn.path().filter(|path| !path.is_empty())
+    }
 
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS cards (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                series_id INTEGER NOT NULL,
-                collection_number INTEGER NOT NULL,
-                number TEXT NOT NULL UNIQUE,
-                in_collection INTEGER NOT NULL DEFAULT 0,
-                rarity_id INTEGER NOT NULL,
-                card_type_id INTEGER NOT NULL,
-                FOREIGN KEY (rarity_id) REFERENCES rarity(id)
-                FOREIGN KEY (series_id) REFERENCES series(id)
-                FOREIGN KEY (card_type_id) REFERENCES card_type(id)
-            )",
-            [],
-        )?;
+    /// Current schema version of the database (`PRAGMA user_version`)
+    pub fn schema_version(&self) -> Result<i32> {
+        self.conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+    }
+
+    /// Bring the schema up to date with this binary
+    pub fn migrate(&self) -> Result<(), DbError> {
+        self.migrate_to(SCHEMA_VERSION)
+    }
+
+    /// Apply pending migrations up to and including version `target`.
+    /// Every step runs in its own transaction together with the version bump,
+    /// so a failing step leaves the database at the previous version.
+    pub fn migrate_to(&self, target: i32) -> Result<(), DbError> {
+        let current = self.schema_version()?;
+        if current > SCHEMA_VERSION {
+            return Err(DbError::UnsupportedSchemaVersion(current));
+        }
+
+        for version in current..target.min(SCHEMA_VERSION) {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute_batch(MIGRATIONS[version as usize])?;
+            tx.pragma_update(None, "user_version", version + 1)?;
+            tx.commit()?;
+        }
         Ok(())
     }
 
@@ -140,8 +298,8 @@ impl DatabaseConnection {
     }
 
     pub fn insert_series(&self, series: &Series) -> Result<i32> {
-        let release_date = NaiveDate::parse_from_str(&series.release_date, "%B %d, %Y")
-            .unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
+        let release_date = parse_release_date(&series.release_date)
+            .unwrap_or_else(|| NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
 
         self.conn.execute(
             "INSERT OR IGNORE INTO series (name, release_date, n_cards,prefix)
@@ -160,135 +318,639 @@ impl DatabaseConnection {
         Ok(id)
     }
 
-    /// Insert card entry
+    /// Id of the card identity with this name (ignoring case), created when it is new
+    pub fn insert_identity(&self, name: &str) -> Result<i32> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO card_identities (name) VALUES (?1)",
+            [name],
+        )?;
+        self.conn.query_row(
+            "SELECT id FROM card_identities WHERE name = ?1",
+            [name],
+            |row| row.get(0),
+        )
+    }
+
+    /// Run `op` in one transaction, committed only when it succeeds, so a request made of
+    /// several writes is applied completely or not at all
+    pub fn transaction<T>(
+        &self,
+        op: impl FnOnce(&Self) -> Result<T, DbError>,
+    ) -> Result<T, DbError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let value = op(self)?;
+        tx.commit()?;
+        Ok(value)
+    }
+
+    // Delete a card identity that has no printings left
+    fn delete_orphan_identity(&self, id: i32) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM card_identities
+             WHERE id = ?1 AND NOT EXISTS (SELECT 1 FROM cards WHERE identity_id = ?1)",
+            [id],
+        )?;
+        Ok(())
+    }
+
+    // Run `op` on a savepoint that is rolled back when it fails.
+    // Savepoints nest, so this also works inside the transaction of an import.
+    fn with_savepoint<T>(&self, op: impl FnOnce() -> Result<T, DbError>) -> Result<T, DbError> {
+        self.conn.execute_batch("SAVEPOINT card_write")?;
+        match op() {
+            Ok(value) => {
+                self.conn.execute_batch("RELEASE card_write")?;
+                Ok(value)
+            }
+            Err(e) => {
+                self.conn
+                    .execute_batch("ROLLBACK TO card_write; RELEASE card_write")?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Insert card entry, a printing of the card identity with its name
     pub fn insert_card(&self, card: &DatabaseCard) -> Result<i32, DbError> {
+        self.with_savepoint(|| self.insert_card_row(card))
+    }
+
+    fn insert_card_row(&self, card: &DatabaseCard) -> Result<i32, DbError> {
         // Get the series name safely
         let series = self.get_series_by_id(card.series_id)?;
+        let identity_id = self.insert_identity(&card.name)?;
 
+        // in_collection is derived from the copies table, so insert with zero copies first
         match self.conn.execute(
-            "INSERT INTO cards (name, series_id, number, collection_number, in_collection, rarity_id,card_type_id)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            "INSERT INTO cards (identity_id, series_id, number, collection_number, in_collection, rarity_id,card_type_id)
+             VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6)",
             params![
-                card.name,
+                identity_id,
                 card.series_id,
                 card.number,
                 card.collection_number,
-                card.in_collection,
                 card.rarity_id,
                 card.card_type_id
             ],
         ) {
-            Ok(_) => Ok(self.conn.last_insert_rowid() as i32),
+            Ok(_) => {
+                let id = self.conn.last_insert_rowid() as i32;
+                if card.in_collection > 0 {
+                    self.add_copies(&card.number, card.in_collection, &CopyAttributes::default())?;
+                }
+                Ok(id)
+            }
             Err(rusqlite::Error::SqliteFailure(e, _)) if e.extended_code == 2067 => {
             println!("Warning: Card '{}' already exists in series '{}'.", card.number, series.name);
+            self.delete_orphan_identity(identity_id)?;
             Ok(0) // indicate nothing was inserted
         }
             Err(e) => Err(DbError::SqliteError(e)),
         }
     }
 
-    pub fn collect_card(&self, card_id: &str, count: Option<i32>) -> Result<i32, DbError> {
-        // Check if the card_id contains a range (e.g., "LOB-001-010")
-        if let Some((prefix, series_prefix, start, end)) = parse_card_range(card_id) {
-            // Update all cards in the range
-            let mut total_updated = 0;
-            for num in start..=end {
-                let card_number = format!(
-                    "{}-{}{:03}",
-                    prefix,
-                    series_prefix.unwrap_or(""), // use empty string if None
-                    num
-                );
-                let new_count: i32 = if let Some(c) = count {
-                    println!("Collecting {} copies of '{}'", card_number, c);
-                    self.conn
-                        .query_row(
-                            "UPDATE cards
-                            SET in_collection = in_collection+?1
-                            WHERE number = ?2
-                            RETURNING in_collection",
-                            params![c, card_number],
-                            |row| row.get(0),
-                        )
-                        .map_err(DbError::from)?
-                } else {
+    // Add `count` copies of a single card number, returns the new number of copies
+    fn add_copies(&self, number: &str, count: i32, copy: &CopyAttributes) -> Result<i32, DbError> {
+        if count < 0 {
+            return Err(DbError::InvalidOperation(format!(
+                "Could not collect card '{}': count must not be negative, use sell instead.",
+                number
+            )));
+        }
+
+        let card_id: i32 = self
+            .conn
+            .query_row(
+                "SELECT id FROM cards WHERE number = ?1",
+                params![number],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| DbError::UnknownCard(number.into()))?;
+
+        let condition = copy.condition.clone().unwrap_or(Condition::NearMint);
+        let edition = copy.edition.clone().unwrap_or(Edition::Unlimited);
+        let language = copy.language.as_deref().unwrap_or("EN");
+        let purchase_date = parse_date(copy.date.as_deref())?;
+
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT INTO copies (card_id, condition, edition, language, notes, purchase_price, purchase_date)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        for _ in 0..count {
+            stmt.execute(params![
+                card_id,
+                condition,
+                edition,
+                language,
+                copy.notes,
+                copy.price,
+                purchase_date
+            ])?;
+        }
+
+        let new_count: i32 = self.conn.query_row(
+            "SELECT in_collection FROM cards WHERE id = ?1",
+            params![card_id],
+            |row| row.get(0),
+        )?;
+        Ok(new_count)
+    }
+
+    // Mark `count` owned copies matching `copy` of a single card number as sold, newest
+    // copies first. Sold copies are kept for profit reporting. Returns the new number of copies.
+    fn sell_copies(&self, number: &str, count: i32, copy: &CopyAttributes) -> Result<i32, DbError> {
+        const MATCHING_COPIES: &str = "SELECT cp.id FROM copies cp
+            JOIN cards c ON c.id = cp.card_id
+            WHERE c.number = ?1
+              AND cp.sale_date IS NULL
+              AND (?2 IS NULL OR cp.condition = ?2)
+              AND (?3 IS NULL OR cp.edition = ?3)
+              AND (?4 IS NULL OR cp.language = ?4 COLLATE NOCASE)";
+        let filter = params![number, copy.condition, copy.edition, copy.language];
+        let sale_date = parse_date(copy.date.as_deref())?;
+
+        self.conn
+            .query_row(
+                "SELECT 1 FROM cards WHERE number = ?1",
+                [number],
+                |_| Ok(()),
+            )
+            .optional()?
+            .ok_or_else(|| DbError::UnknownCard(number.into()))?;
+
+        let available: i32 = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM ({})", MATCHING_COPIES),
+            filter,
+            |row| row.get(0),
+        )?;
+        if available < count {
+            return Err(DbError::InvalidOperation(format!(
+                "Could not sell card '{}': Number of copies in collection cannot become negative.",
+                number
+            )));
+        }
+
+        self.conn.execute(
+            &format!(
+                "UPDATE copies SET sale_price = ?6, sale_date = ?7
+                 WHERE id IN ({} ORDER BY cp.id DESC LIMIT ?5)",
+                MATCHING_COPIES
+            ),
+            params![
+                number,
+                copy.condition,
+                copy.edition,
+                copy.language,
+                count,
+                copy.price,
+                sale_date
+            ],
+        )?;
+
+        let new_count: i32 = self.conn.query_row(
+            "SELECT in_collection FROM cards WHERE number = ?1",
+            params![number],
+            |row| row.get(0),
+        )?;
+        Ok(new_count)
+    }
+
+    /// Add copies of a card number or range (e.g. "LOB-001-010"), returns the summed number of copies.
+    /// A range is applied all-or-nothing.
+    pub fn collect_card(
+        &self,
+        card_id: &str,
+        count: Option<i32>,
+        copy: &CopyAttributes,
+    ) -> Result<i32, DbError> {
+        let results =
+            self.collect_cards(&[card_id.to_string()], count, copy, BatchMode::AllOrNothing)?;
+        Ok(results.iter().filter_map(|r| r.in_collection).sum())
+    }
+
+    /// Sell copies of a card number or range, returns the summed number of remaining copies.
+    /// A range is applied all-or-nothing.
+    pub fn sell_card(
+        &self,
+        card_id: &str,
+        count: i32,
+        copy: &CopyAttributes,
+    ) -> Result<i32, DbError> {
+        let results =
+            self.sell_cards(&[card_id.to_string()], count, copy, BatchMode::AllOrNothing)?;
+        Ok(results.iter().filter_map(|r| r.in_collection).sum())
+    }
+
+    /// Add `count` copies (defaults to 1) of every card number and range in `ids` in a single transaction
+    pub fn collect_cards(
+        &self,
+        ids: &[String],
+        count: Option<i32>,
+        copy: &CopyAttributes,
+        mode: BatchMode,
+    ) -> Result<Vec<CardResult>, DbError> {
+        let count = count.unwrap_or(1); //default to add one copy
+        self.for_each_card(ids, mode, |number| self.add_copies(number, count, copy))
+    }
+
+    /// Sell `count` copies of every card number and range in `ids` in a single transaction
+    pub fn sell_cards(
+        &self,
+        ids: &[String],
+        count: i32,
+        copy: &CopyAttributes,
+        mode: BatchMode,
+    ) -> Result<Vec<CardResult>, DbError> {
+        self.for_each_card(ids, mode, |number| self.sell_copies(number, count, copy))
+    }
+
+    // Apply `op` to every card number of `ids` inside one transaction, each number on its own savepoint.
+    // With `AllOrNothing` the first failure rolls everything back and is returned as the error.
+    fn for_each_card<F>(
+        &self,
+        ids: &[String],
+        mode: BatchMode,
+        op: F,
+    ) -> Result<Vec<CardResult>, DbError>
+    where
+        F: Fn(&str) -> Result<i32, DbError>,
+    {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut results = Vec::new();
+        for (number, parsed) in expand_card_ids(ids) {
+            self.conn.execute_batch("SAVEPOINT card_op")?;
+            match parsed.and_then(|_| op(&number)) {
+                Ok(in_collection) => {
+                    self.conn.execute_batch("RELEASE card_op")?;
+                    results.push(CardResult {
+                        number,
+                        in_collection: Some(in_collection),
+                        error: None,
+                    });
+                }
+                Err(e) if mode == BatchMode::BestEffort => {
                     self.conn
-                        .query_row(
-                            "UPDATE cards
-                        SET in_collection = in_collection + 1
-                        WHERE number = ?1
-                        RETURNING in_collection",
-                            params![card_number],
-                            |row| row.get(0),
-                        )
-                        .map_err(DbError::from)?
-                };
-                total_updated += new_count;
+                        .execute_batch("ROLLBACK TO card_op; RELEASE card_op")?;
+                    results.push(CardResult {
+                        number,
+                        in_collection: None,
+                        error: Some(e.to_string()),
+                    });
+                }
+                Err(e) => return Err(e), // dropping tx rolls back all cards
             }
-            return Ok(total_updated);
         }
+        tx.commit()?;
+        Ok(results)
+    }
 
-        let final_count = count.unwrap_or_else(|| 1); //default to increment by one
+    /// Update the catalogue fields of a card (everything except its copies).
+    /// A new name makes it a printing of the identity with that name. The identity itself is
+    /// renamed when the name only differs in case, or when the card is its only printing and
+    /// no other identity has the name; an identity left without printings is deleted.
+    pub fn update_card(&self, number: &str, card: &DatabaseCard) -> Result<(), DbError> {
+        self.check_card_references(card)?;
+        self.get_database_card(number)?;
+        self.with_savepoint(|| {
+            let old_identity: i32 = self.conn.query_row(
+                "SELECT identity_id FROM cards WHERE number = ?1",
+                [number],
+                |r| r.get(0),
+            )?;
+            let identity_id = self.card_identity_for_name(old_identity, &card.name)?;
+            let updated = self.conn.execute(
+                "UPDATE cards SET identity_id = ?2, series_id = ?3, number = ?4, collection_number = ?5,
+                    rarity_id = ?6, card_type_id = ?7
+                 WHERE number = ?1",
+                params![
+                    number,
+                    identity_id,
+                    card.series_id,
+                    card.number,
+                    card.collection_number,
+                    card.rarity_id,
+                    card.card_type_id
+                ],
+            );
+            match updated {
+                Ok(0) => Err(DbError::UnknownCard(number.into())),
+                Ok(_) => Ok(self.delete_orphan_identity(old_identity)?),
+                Err(rusqlite::Error::SqliteFailure(e, _)) if e.extended_code == 2067 => {
+                    Err(DbError::UniqueConstraintViolation(format!(
+                        "card '{}' already exists",
+                        card.number
+                    )))
+                }
+                Err(e) => Err(DbError::SqliteError(e)),
+            }
+        })
+    }
 
-        //no count specified, add 1 to existing collection
-        let new_count: i32 = self
+    // Identity a printing of `current` gets when renamed to `name`, see `update_card`
+    fn card_identity_for_name(&self, current: i32, name: &str) -> Result<i32, DbError> {
+        let existing: Option<i32> = self
             .conn
             .query_row(
-                "UPDATE cards
-                    SET in_collection = in_collection + ?1
-                    WHERE number = ?2
-                    RETURNING in_collection",
-                params![final_count, card_id],
-                |row| row.get(0),
+                "SELECT id FROM card_identities WHERE name = ?1",
+                [name],
+                |r| r.get(0),
             )
-            .map_err(DbError::from)?; // convert rusqlite::Error to DbError if needed
-        Ok(new_count)
+            .optional()?;
+        let other_printings: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 1 FROM cards WHERE identity_id = ?1",
+            [current],
+            |r| r.get(0),
+        )?;
+        match existing {
+            Some(id) if id != current => Ok(id),
+            None if other_printings => Ok(self.insert_identity(name)?),
+            _ => {
+                self.conn.execute(
+                    "UPDATE card_identities SET name = ?2 WHERE id = ?1",
+                    params![current, name],
+                )?;
+                Ok(current)
+            }
+        }
     }
 
-    pub fn sell_card(&self, card_id: &str, count: i32) -> Result<i32, DbError> {
-        // Helper closure to update a single card
-        let sell_single = |conn: &rusqlite::Connection, number: &str| -> Result<i32, DbError> {
-            let new_count: Option<i32> = conn
+    // Series, rarity and card type of a card must exist
+    fn check_card_references(&self, card: &DatabaseCard) -> Result<(), DbError> {
+        let exists = |table: &str, id: i32| -> Result<bool, DbError> {
+            Ok(self
+                .conn
                 .query_row(
-                    "UPDATE cards
-                 SET in_collection = in_collection - ?2
-                 WHERE number = ?1 AND in_collection -?2>=0
-                 RETURNING in_collection",
-                    params![number, count],
-                    |row| row.get(0),
+                    &format!("SELECT 1 FROM {} WHERE id = ?1", table),
+                    [id],
+                    |_| Ok(()),
                 )
-                .optional() // returns None if no rows updated
-                .map_err(DbError::from)?;
+                .optional()?
+                .is_some())
+        };
+        if !exists("series", card.series_id)? {
+            return Err(DbError::UnknownSeries(card.series_id.to_string()));
+        }
+        if !exists("rarity", card.rarity_id)? {
+            return Err(DbError::UnknownRarity(card.rarity_id.to_string()));
+        }
+        if !exists("card_type", card.card_type_id)? {
+            return Err(DbError::UnknownCardType(card.card_type_id.to_string()));
+        }
+        Ok(())
+    }
+
+    // Card identities matching a WHERE clause over `i` with one parameter, with their printings
+    fn query_identities(
+        &self,
+        where_clause: &str,
+        param: rusqlite::types::Value,
+    ) -> Result<Vec<CardIdentityPrintings>, DbError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT i.id, i.name, i.passcode, i.text, i.attribute, i.level, i.rank,
+                i.link_rating, i.monster_type, i.atk, i.def
+             FROM card_identities i WHERE {}
+             ORDER BY i.name",
+            where_clause
+        ))?;
+        let identities = stmt
+            .query_map([&param], identity_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT c.identity_id, c.number, c.region, c.series_id, s.name, s.release_date,
+                r.name, c.in_collection
+             FROM cards c
+             JOIN card_identities i ON c.identity_id = i.id
+             JOIN series s ON c.series_id = s.id
+             JOIN rarity r ON c.rarity_id = r.id
+             WHERE {}
+             ORDER BY s.release_date, c.number",
+            where_clause
+        ))?;
+        let mut printings: HashMap<i32, Vec<Printing>> = HashMap::new();
+        for row in stmt.query_map([&param], |r| {
+            Ok((
+                r.get::<_, i32>(0)?,
+                Printing {
+                    number: r.get(1)?,
+                    region: r.get(2)?,
+                    series_id: r.get(3)?,
+                    series: r.get(4)?,
+                    release_date: r.get(5)?,
+                    rarity: r.get(6)?,
+                    in_collection: r.get(7)?,
+                },
+            ))
+        })? {
+            let (identity_id, printing) = row?;
+            printings.entry(identity_id).or_default().push(printing);
+        }
+
+        Ok(identities
+            .into_iter()
+            .map(|identity| {
+                let printings = printings.remove(&identity.id).unwrap_or_default();
+                CardIdentityPrintings {
+                    owned_copies: printings.iter().map(|p| p.in_collection).sum(),
+                    identity,
+                    printings,
+                }
+            })
+            .collect())
+    }
+
+    /// Card identities whose name contains `name`, each with its printings oldest first.
+    /// `owned` keeps only identities with (`true`) or without (`false`) owned copies.
+    pub fn get_identities(
+        &self,
+        name: Option<&str>,
+        owned: Option<bool>,
+    ) -> Result<Vec<CardIdentityPrintings>, DbError> {
+        let pattern = format!("%{}%", name.unwrap_or(""));
+        let mut identities = self.query_identities("i.name LIKE ?1", pattern.into())?;
+        if let Some(owned) = owned {
+            identities.retain(|i| owned == (i.owned_copies > 0));
+        }
+        Ok(identities)
+    }
 
-            match new_count {
-                Some(count) => Ok(count),
-                None => Err(DbError::InvalidOperation(format!(
-                    "Could not sell card '{}': Number of copies in collection cannot become negative.",
-                    number
-                ))),
+    /// A card identity by id with its printings
+    pub fn get_identity(&self, id: i32) -> Result<CardIdentityPrintings, DbError> {
+        self.query_identities("i.id = ?1", id.into())?
+            .pop()
+            .ok_or_else(|| DbError::UnknownCard(format!("identity {}", id)))
+    }
+
+    /// A single card by number, with its copies
+    pub fn get_card(&self, number: &str) -> Result<Card, DbError> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("{} WHERE c.number = ?1", *CARD_SELECT))?;
+        let mut card = match stmt.query_row([number], card_from_row) {
+            Ok(card) => card,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(DbError::UnknownCard(number.into()));
             }
+            Err(e) => return Err(DbError::SqliteError(e)),
         };
+        card.copies = self.get_copies(number)?;
+        Ok(card)
+    }
+
+    /// The catalogue fields of a card as stored, with the ids of its series, rarity and card type
+    pub fn get_database_card(&self, number: &str) -> Result<DatabaseCard, DbError> {
+        match self.conn.query_row(
+            "SELECT i.name, c.series_id, c.number, c.collection_number, c.in_collection,
+                c.rarity_id, c.card_type_id
+             FROM cards c JOIN card_identities i ON c.identity_id = i.id
+             WHERE c.number = ?1",
+            [number],
+            |r| {
+                Ok(DatabaseCard {
+                    name: r.get(0)?,
+                    series_id: r.get(1)?,
+                    number: r.get(2)?,
+                    collection_number: r.get(3)?,
+                    in_collection: r.get(4)?,
+                    rarity_id: r.get(5)?,
+                    card_type_id: r.get(6)?,
+                })
+            },
+        ) {
+            Ok(card) => Ok(card),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Err(DbError::UnknownCard(number.into())),
+            Err(e) => Err(DbError::SqliteError(e)),
+        }
+    }
+
+    /// Add a new card without copies, unlike `insert_card` an existing number is an error
+    pub fn create_card(&self, card: &DatabaseCard) -> Result<Card, DbError> {
+        if card.number.trim().is_empty() || card.name.trim().is_empty() {
+            return Err(DbError::InvalidOperation(
+                "A card needs a number and a name".into(),
+            ));
+        }
+        self.check_card_references(card)?;
+        if self.get_card(&card.number).is_ok() {
+            return Err(DbError::UniqueConstraintViolation(format!(
+                "card '{}' already exists",
+                card.number
+            )));
+        }
+        self.insert_card(&DatabaseCard {
+            in_collection: 0,
+            ..card.clone()
+        })?;
+        self.get_card(&card.number)
+    }
 
-        // Range case (e.g., "LOB-001-010")
-        if let Some((prefix, series_prefix, start, end)) = parse_card_range(card_id) {
-            let mut total_updated = 0;
+    /// Delete a card together with its wants and price history.
+    /// Cards with copies (owned or sold) are kept, remove those first.
+    pub fn delete_card(&self, number: &str) -> Result<(), DbError> {
+        let copies: i32 = match self.conn.query_row(
+            "SELECT COUNT(cp.id) FROM cards c LEFT JOIN copies cp ON cp.card_id = c.id
+             WHERE c.number = ?1 GROUP BY c.id",
+            [number],
+            |r| r.get(0),
+        ) {
+            Ok(n) => n,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(DbError::UnknownCard(number.into()));
+            }
+            Err(e) => return Err(DbError::SqliteError(e)),
+        };
+        if copies > 0 {
+            return Err(DbError::InUse(format!(
+                "card '{}' has {} copies",
+                number, copies
+            )));
+        }
+        // its want is deleted by the foreign key, prices have none to cards and are deleted here
+        let tx = self.conn.unchecked_transaction()?;
+        let identity_id: i32 = tx.query_row(
+            "SELECT identity_id FROM cards WHERE number = ?1",
+            [number],
+            |r| r.get(0),
+        )?;
+        tx.execute("DELETE FROM price WHERE number = ?1", [number])?;
+        tx.execute("DELETE FROM cards WHERE number = ?1", [number])?;
+        self.delete_orphan_identity(identity_id)?;
+        tx.commit()?;
+        Ok(())
+    }
 
-            for num in start..=end {
-                let card_number = format!(
-                    "{}-{}{:03}",
-                    prefix,
-                    series_prefix.unwrap_or(""), // use empty string if None
-                    num
-                );
-                println!("Selling '{}'", card_number);
-                total_updated += sell_single(&self.conn, &card_number)?;
+    /// Set the number of owned copies, adding default copies or deleting the newest ones.
+    /// Meant for corrections and imports; use `sell_card` to record a sale.
+    pub fn set_owned_count(&self, number: &str, owned: i32) -> Result<i32, DbError> {
+        if owned < 0 {
+            return Err(DbError::InvalidOperation(format!(
+                "Number of copies of '{}' cannot be negative",
+                number
+            )));
+        }
+        let current: i32 = match self.conn.query_row(
+            "SELECT in_collection FROM cards WHERE number = ?1",
+            [number],
+            |r| r.get(0),
+        ) {
+            Ok(n) => n,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(DbError::UnknownCard(number.into()));
             }
+            Err(e) => return Err(DbError::SqliteError(e)),
+        };
 
-            return Ok(total_updated);
+        if owned > current {
+            return self.add_copies(number, owned - current, &CopyAttributes::default());
         }
+        self.conn.execute(
+            "DELETE FROM copies WHERE id IN (
+                SELECT cp.id FROM copies cp
+                JOIN cards c ON c.id = cp.card_id
+                WHERE c.number = ?1 AND cp.sale_date IS NULL
+                ORDER BY cp.id DESC
+                LIMIT ?2
+            )",
+            params![number, current - owned],
+        )?;
+        Ok(owned)
+    }
 
-        // Single card case
-        sell_single(&self.conn, card_id)
+    /// Copies in the collection of a single card number, oldest first
+    pub fn get_copies(&self, number: &str) -> Result<Vec<Copy>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT cp.id, cp.condition, cp.edition, cp.language, cp.notes,
+                    cp.purchase_price, cp.purchase_date, cp.sale_price, cp.sale_date
+             FROM copies cp
+             JOIN cards c ON c.id = cp.card_id
+             WHERE c.number = ?1 AND cp.sale_date IS NULL
+             ORDER BY cp.id",
+        )?;
+        let copies = stmt
+            .query_map([number], |row| {
+                Ok(Copy {
+                    id: row.get(0)?,
+                    condition: row.get(1)?,
+                    edition: row.get(2)?,
+                    language: row.get(3)?,
+                    notes: row.get(4)?,
+                    purchase_price: row.get(5)?,
+                    purchase_date: row.get(6)?,
+                    sale_price: row.get(7)?,
+                    sale_date: row.get(8)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(copies)
+    }
+
+    // Fill in the copies of cards loaded through `card_from_row`
+    fn attach_copies(&self, cards: &mut [Card]) -> Result<()> {
+        for card in cards.iter_mut().filter(|c| c.in_collection > 0) {
+            card.copies = self.get_copies(&card.number)?;
+        }
+        Ok(())
     }
 
     /// Query cards with rarity name joined
@@ -298,173 +960,2081 @@ impl DatabaseConnection {
             None => "%".to_string(), // matches everything
         };
 
-        let mut stmt = self.conn.prepare(
-            "SELECT 
-                    c.name, c.series_id, c.number, c.collection_number, c.in_collection, 
-                    c.rarity_id, c.card_type_id, r.name, t.maintype, t.subtype,
-                    s.name, s.prefix, s.release_date, s.n_cards
-                    FROM cards c
-                    JOIN rarity r ON c.rarity_id = r.id
-                    JOIN card_type t ON c.card_type_id = t.id
-                    JOIN series s ON c.series_id = s.id
-                    where c.name LIKE ?1
-                    COLLATE NOCASE
-                    ",
-        )?;
-
-        let card_iter = stmt.query_map([pattern], |row| {
-            let rarity = Rarity {
-                id: row.get(5)?,
-                name: row.get(7)?,
-            };
+        let mut stmt = self.conn.prepare(&format!(
+            "{} where i.name LIKE ?1 COLLATE NOCASE",
+            *CARD_SELECT
+        ))?;
 
-            let series = Series {
-                id: row.get(1)?,
-                name: row.get(10)?,
-                prefix: row.get(11)?,
-                release_date: row.get(12)?,
-                n_cards: row.get(13)?,
-            };
-            let card = Card {
-                name: row.get(0)?,
-                number: row.get(2)?,
-                collection_number: row.get(3)?,
-                in_collection: row.get(4)?,
-                rarity: rarity,
-                cardtype: CardType {
-                    main: row.get(8)?,
-                    sub: row.get(9)?,
-                },
-                series: series,
-            };
-            Ok(card)
-        })?;
+        let card_iter = stmt.query_map([pattern], card_from_row)?;
+
+        let mut cards: Vec<Card> = card_iter.filter_map(Result::ok).collect();
+        self.attach_copies(&mut cards)?;
+        Ok(cards)
+    }
+
+    /// Filtered, sorted page of cards with the total number of matching cards
+    pub fn list_cards(&self, options: &CardListOptions) -> Result<CardPage, DbError> {
+        if options.limit.is_some_and(|l| l < 1) || options.offset < 0 {
+            return Err(DbError::InvalidOperation(
+                "limit must be at least 1 and offset must not be negative".into(),
+            ));
+        }
 
-        let cards: Vec<Card> = card_iter.filter_map(Result::ok).collect();
+        let mut params: Vec<rusqlite::types::Value> = Vec::new();
+        let mut clauses = Vec::new();
+        if let Some(name) = options.name.as_deref().filter(|n| !n.is_empty()) {
+            params.push(format!("%{}%", name).into());
+            clauses.push(format!("i.name LIKE ?{} COLLATE NOCASE", params.len()));
+        }
+        if let Some(series_id) = options.series_id {
+            params.push(series_id.into());
+            clauses.push(format!("c.series_id = ?{}", params.len()));
+        }
+        if let Some(rarities) = options.rarity.as_deref() {
+            let mut names = Vec::new();
+            for rarity in rarities.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+                params.push(rarity.to_string().into());
+                names.push(format!("?{}", params.len()));
+            }
+            if !names.is_empty() {
+                clauses.push(format!("r.name COLLATE NOCASE IN ({})", names.join(", ")));
+            }
+        }
+        if let Some(cardtype) = options.cardtype.as_deref().filter(|t| !t.is_empty()) {
+            params.push(cardtype.to_string().into());
+            let n = params.len();
+            clauses.push(format!(
+                "(t.maintype = ?{n} COLLATE NOCASE OR t.subtype = ?{n} COLLATE NOCASE
+                  OR t.subtype || ' ' || t.maintype = ?{n} COLLATE NOCASE)"
+            ));
+        }
+        match options.owned {
+            Some(true) => clauses.push("c.in_collection > 0".into()),
+            Some(false) => clauses.push("c.in_collection = 0".into()),
+            None => {}
+        }
+        let where_clause = if clauses.is_empty() {
+            "1".to_string()
+        } else {
+            clauses.join(" AND ")
+        };
+
+        let total: i64 = self.conn.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM ({} WHERE {})",
+                *CARD_SELECT, where_clause
+            ),
+            params_from_iter(params.iter()),
+            |r| r.get(0),
+        )?;
+
+        let order = match options.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+        params.push(options.limit.unwrap_or(-1).into()); // -1 is no limit in SQLite
+        params.push(options.offset.into());
+        let mut stmt = self.conn.prepare(&format!(
+            "{} WHERE {} ORDER BY {} {}, c.number {} LIMIT ?{} OFFSET ?{}",
+            *CARD_SELECT,
+            where_clause,
+            options.sort.column(),
+            order,
+            order,
+            params.len() - 1,
+            params.len()
+        ))?;
+        let mut cards = stmt
+            .query_map(params_from_iter(params.iter()), card_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+        self.attach_copies(&mut cards)?;
+
+        Ok(CardPage {
+            cards,
+            total,
+            limit: options.limit,
+            offset: options.offset,
+        })
+    }
+
+    /// Cards matching a parsed query (see `query.rs`), ordered by series release date and number
+    pub fn find_cards(&self, query: &Query) -> Result<Vec<Card>, DbError> {
+        let (where_clause, params) = query.to_sql();
+        let mut stmt = self.conn.prepare(&format!(
+            "{} WHERE {} ORDER BY s.release_date, c.number",
+            *CARD_SELECT, where_clause
+        ))?;
+        let mut cards = stmt
+            .query_map(params_from_iter(params), card_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+        self.attach_copies(&mut cards)?;
         Ok(cards)
     }
 
+    /// Full-text search over card name, number, series name and text, best matches first.
+    /// Every word of `query` is matched as a prefix, see `search::fts_query`.
+    pub fn search_cards(
+        &self,
+        query: &str,
+        limit: Option<i64>,
+        highlight: Highlight,
+    ) -> Result<Vec<SearchHit>, DbError> {
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        // matches in the name weigh most, then the number, series and text. The search columns
+        // are read by name, `bm25_rank` as the card columns already have the rank of Xyz monsters.
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {},
+                bm25(cards_fts, 10.0, 5.0, 2.0, 1.0) AS bm25_rank,
+                highlight(cards_fts, 0, ?2, ?3) AS name_highlight,
+                snippet(cards_fts, 3, ?2, ?3, '...', 16) AS snippet
+             FROM {}
+             JOIN cards_fts f ON f.rowid = c.id
+             WHERE cards_fts MATCH ?1
+             ORDER BY bm25_rank
+             LIMIT ?4",
+            CARD_COLUMNS, CARD_TABLES
+        ))?;
+        let mut hits = stmt
+            .query_map(
+                params![fts_query, MATCH_OPEN, MATCH_CLOSE, limit.unwrap_or(-1)],
+                |row| {
+                    Ok(SearchHit {
+                        card: card_from_row(row)?,
+                        rank: row.get("bm25_rank")?,
+                        name_highlight: row.get("name_highlight")?,
+                        snippet: row.get("snippet")?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>>>()?;
+        for hit in hits.iter_mut() {
+            if hit.card.in_collection > 0 {
+                hit.card.copies = self.get_copies(&hit.card.number)?;
+            }
+            // only keep snippets of text that actually matched
+            hit.snippet = hit
+                .snippet
+                .take()
+                .filter(|s| s.contains(MATCH_OPEN))
+                .map(|s| highlight.apply(&s));
+            hit.name_highlight = highlight.apply(&hit.name_highlight);
+        }
+        Ok(hits)
+    }
+
     /// Query cards with rarity name joined
     pub fn get_cards_by_seriesname(&self, series_name: &str) -> Result<Vec<Card>, DbError> {
-        let sql = "SELECT 
-                                c.name, c.series_id, c.number, c.collection_number, c.in_collection, 
-                                c.rarity_id, c.card_type_id, r.name, t.maintype, t.subtype,
-                                s.name, s.prefix, s.release_date, s.n_cards
-                                FROM cards c
-                                JOIN rarity r ON c.rarity_id = r.id
-                                JOIN card_type t ON c.card_type_id = t.id
-                                JOIN series s ON c.series_id = s.id
-                                where s.name = ?1
-                                COLLATE NOCASE";
-        let mut stmt = self.conn.prepare(sql)?;
-        // println!("Executing SQL:\n{}\nWith param: '{}'", sql, series_name);
-
-        // println!("'{:?}'", stmt);
-        let card_iter = stmt
-            .query_map([series_name], |row| {
-                let rarity = Rarity {
-                    id: row.get(5)?,
-                    name: row.get(7)?,
-                };
+        let sql = format!("{} where s.name = ?1 COLLATE NOCASE", *CARD_SELECT);
+        let mut stmt = self.conn.prepare(&sql)?;
 
-                let series = Series {
-                    id: row.get(1)?,
-                    name: row.get(10)?,
-                    prefix: row.get(11)?,
-                    n_cards: row.get(13)?,
-                    release_date: row.get(12)?,
-                };
-                let card = Card {
-                    name: row.get(0)?,
-                    number: row.get(2)?,
-                    collection_number: row.get(3)?,
-                    in_collection: row.get(4)?,
-                    rarity: rarity,
-                    cardtype: CardType {
-                        main: row.get(8)?,
-                        sub: row.get(9)?,
-                    },
-                    series: series,
-                };
-                Ok(card)
-            })
+        let card_iter = stmt
+            .query_map([series_name], card_from_row)
             .map_err(DbError::SqliteError)?;
 
-        let results: Vec<_> = card_iter.filter_map(Result::ok).collect();
+        let mut results: Vec<_> = card_iter.filter_map(Result::ok).collect();
 
         if results.is_empty() {
             Err(DbError::UnknownSeries(series_name.to_string()))
         } else {
+            self.attach_copies(&mut results)?;
             Ok(results)
         }
     }
 
-    pub fn get_rarity_id(&self, rarity_name: &str) -> Result<i32, DbError> {
-        let mut stmt = self.conn.prepare("SELECT id FROM rarity WHERE name = ?1")?;
-        match stmt.query_row([rarity_name], |r| r.get(0)) {
-            Ok(id) => Ok(id),
-            Err(rusqlite::Error::QueryReturnedNoRows) => {
-                Err(DbError::UnknownRarity(rarity_name.into()))
-            }
-            Err(e) => Err(DbError::SqliteError(e)),
+    /// Record the market price of a card number in a given condition
+    pub fn insert_price(
+        &self,
+        number: &str,
+        condition: &Condition,
+        price: f64,
+        date: Option<&str>,
+    ) -> Result<(), DbError> {
+        let recorded_at = parse_date(date)?;
+        let inserted = self.conn.execute(
+            "INSERT INTO price (number, rarity_id, condition, price, recorded_at)
+             SELECT number, rarity_id, ?2, ?3, ?4 FROM cards WHERE number = ?1",
+            params![number, condition, price, recorded_at],
+        )?;
+        if inserted == 0 {
+            return Err(DbError::InvalidOperation(format!(
+                "Could not record price: unknown card '{}'",
+                number
+            )));
         }
+        Ok(())
     }
 
-    pub fn get_series_by_id(&self, id: i32) -> Result<Series, DbError> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT id, name, release_date, n_cards,prefix FROM series WHERE id = ?1")?;
-
-        match stmt.query_row([id], |r| {
-            Ok(Series {
-                id: r.get(0)?,
-                name: r.get(1)?,
-                release_date: r.get(2)?,
-                n_cards: r.get(3)?,
-                prefix: r.get(4)?,
-            })
-        }) {
-            Ok(series) => Ok(series),
-            Err(rusqlite::Error::QueryReturnedNoRows) => {
-                Err(DbError::UnknownSeries(id.to_string()))
-            }
-            Err(e) => Err(DbError::SqliteError(e)),
-        }
+    /// Market price history of a card number, newest first
+    pub fn get_prices(&self, number: &str) -> Result<Vec<MarketPrice>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT p.number, r.name, p.condition, p.price, p.recorded_at
+             FROM price p
+             JOIN rarity r ON p.rarity_id = r.id
+             WHERE p.number = ?1
+             ORDER BY p.recorded_at DESC, p.id DESC",
+        )?;
+        let prices = stmt
+            .query_map([number], |row| {
+                Ok(MarketPrice {
+                    number: row.get(0)?,
+                    rarity: row.get(1)?,
+                    condition: row.get(2)?,
+                    price: row.get(3)?,
+                    date: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(prices)
     }
 
-    pub fn get_card_type_id(&self, name: &str) -> Result<i32, DbError> {
-        //expect names like "Continuous Spell Card" or "Effect Fusion Monster"
-        // take first word as subtype
-        // take all the rest as main type:
-        // "Continuous Spell Card" -> maintype: Spell Card, subtype: Continuous
-        // "Effect Fusion Monster" -> maintype: Fusion Monster, subtype: Effect
+    /// Market value, cost basis and profit of the collection per series.
+    /// Copies are valued at the latest market price for their number, rarity and condition.
+    pub fn get_collection_value(&self) -> Result<CollectionValue, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.name,
+                SUM(v.owned), SUM(v.owned AND v.market_price IS NULL), SUM(NOT v.owned),
+                SUM(CASE WHEN v.owned THEN COALESCE(v.market_price, 0) ELSE 0 END),
+                SUM(CASE WHEN v.owned THEN COALESCE(v.purchase_price, 0) ELSE 0 END),
+                SUM(CASE WHEN v.owned THEN 0
+                    ELSE COALESCE(v.sale_price, 0) - COALESCE(v.purchase_price, 0) END)
+             FROM (
+                SELECT c.series_id, cp.sale_date IS NULL AS owned,
+                    cp.purchase_price, cp.sale_price,
+                    (SELECT p.price FROM price p
+                     WHERE p.number = c.number AND p.rarity_id = c.rarity_id
+                       AND p.condition = cp.condition
+                     ORDER BY p.recorded_at DESC, p.id DESC LIMIT 1) AS market_price
+                FROM copies cp
+                JOIN cards c ON c.id = cp.card_id
+             ) v
+             JOIN series s ON s.id = v.series_id
+             GROUP BY s.id
+             ORDER BY s.release_date",
+        )?;
 
-        let (subtype, maintype);
+        let series = stmt
+            .query_map([], |row| {
+                let market_value: f64 = row.get(5)?;
+                let cost_basis: f64 = row.get(6)?;
+                Ok(SeriesValue {
+                    series_id: Some(row.get(0)?),
+                    series: row.get(1)?,
+                    owned_copies: row.get(2)?,
+                    unpriced_copies: row.get(3)?,
+                    sold_copies: row.get(4)?,
+                    market_value,
+                    cost_basis,
+                    unrealized_profit: market_value - cost_basis,
+                    realized_profit: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
 
-        if name == "Fusion Monster" {
-            subtype = "Normal";
-            maintype = "Fusion Monster";
-        } else if name == "Ritual Monster" {
-            subtype = "Normal";
-            maintype = "Ritual Monster";
+        Ok(CollectionValue::from_series(series))
+    }
+
+    /// Completion of every series: cards defined and owned, copies and duplicates, per rarity
+    pub fn get_completion_stats(&self) -> Result<CompletionStats, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.name, COALESCE(s.prefix, ''), s.n_cards,
+                COUNT(slot.copies),
+                COUNT(CASE WHEN slot.copies > 0 THEN 1 END),
+                COALESCE(SUM(slot.copies), 0),
+                COALESCE(SUM(MAX(slot.copies - 1, 0)), 0)
+             FROM series s
+             LEFT JOIN (SELECT series_id, SUM(in_collection) AS copies FROM cards
+                        GROUP BY series_id, collection_number) slot ON slot.series_id = s.id
+             GROUP BY s.id
+             ORDER BY s.release_date, s.id",
+        )?;
+        let mut series = stmt
+            .query_map([], |row| {
+                Ok(SeriesCompletion {
+                    series_id: Some(row.get(0)?),
+                    series: row.get(1)?,
+                    prefix: row.get(2)?,
+                    n_cards: row.get(3)?,
+                    cards_defined: row.get(4)?,
+                    owned_cards: row.get(5)?,
+                    total_copies: row.get(6)?,
+                    duplicates: row.get(7)?,
+                    ..Default::default()
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT c.series_id, r.name, COUNT(DISTINCT c.collection_number),
+                COUNT(DISTINCT CASE WHEN c.in_collection > 0 THEN c.collection_number END)
+             FROM cards c
+             JOIN rarity r ON r.id = c.rarity_id
+             GROUP BY c.series_id, r.id
+             ORDER BY c.series_id, r.id",
+        )?;
+        let mut rarities: HashMap<i32, Vec<RarityCompletion>> = HashMap::new();
+        let rows = stmt.query_map([], |row| {
+            let cards = row.get(2)?;
+            let owned_cards = row.get(3)?;
+            Ok((
+                row.get::<_, i32>(0)?,
+                RarityCompletion {
+                    rarity: row.get(1)?,
+                    cards,
+                    owned_cards,
+                    completion: percentage(owned_cards, cards),
+                },
+            ))
+        })?;
+        for row in rows {
+            let (series_id, rarity) = row?;
+            rarities.entry(series_id).or_default().push(rarity);
+        }
+
+        for s in &mut series {
+            s.completion = percentage(s.owned_cards, s.target());
+            s.rarities = s
+                .series_id
+                .and_then(|id| rarities.remove(&id))
+                .unwrap_or_default();
+        }
+        Ok(CompletionStats::from_series(series))
+    }
+
+    /// Add a card number to the want list, or update the existing want
+    pub fn upsert_want(&self, want: &NewWant) -> Result<Want, DbError> {
+        if want.quantity < 1 {
+            return Err(DbError::InvalidOperation(format!(
+                "Wanted quantity of '{}' must be at least 1",
+                want.number
+            )));
+        }
+        let inserted = self.conn.execute(
+            "INSERT INTO wants (card_id, quantity, max_price, priority)
+             SELECT id, ?2, ?3, ?4 FROM cards WHERE number = ?1
+             ON CONFLICT (card_id) DO UPDATE SET
+                quantity = excluded.quantity,
+                max_price = excluded.max_price,
+                priority = excluded.priority",
+            params![want.number, want.quantity, want.max_price, want.priority],
+        )?;
+        if inserted == 0 {
+            return Err(DbError::UnknownCard(want.number.clone()));
+        }
+        self.get_want(&want.number)
+    }
+
+    /// Remove a card number from the want list
+    pub fn delete_want(&self, number: &str) -> Result<(), DbError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM wants WHERE card_id IN (SELECT id FROM cards WHERE number = ?1)",
+            params![number],
+        )?;
+        if deleted == 0 {
+            return Err(DbError::UnknownCard(number.to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn get_want(&self, number: &str) -> Result<Want, DbError> {
+        let sql = format!("{} WHERE c.number = ?1", WANT_SELECT);
+        match self.conn.query_row(&sql, [number], want_from_row) {
+            Ok(want) => Ok(want),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Err(DbError::UnknownCard(number.into())),
+            Err(e) => Err(DbError::SqliteError(e)),
+        }
+    }
+
+    /// All wants, ordered by priority (highest first)
+    pub fn get_wants(&self) -> Result<Vec<Want>, DbError> {
+        let sql = format!("{} ORDER BY w.priority DESC, c.number", WANT_SELECT);
+        let mut stmt = self.conn.prepare(&sql)?;
+        let wants = stmt
+            .query_map([], want_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(wants)
+    }
+
+    /// Wants not yet covered by the collection, grouped by series (optionally one series only)
+    pub fn get_missing_wants(&self, series_name: Option<&str>) -> Result<Vec<Want>, DbError> {
+        let sql = format!(
+            "{} WHERE c.in_collection < w.quantity
+               AND (?1 IS NULL OR s.name = ?1 COLLATE NOCASE)
+             ORDER BY s.release_date, s.name, w.priority DESC, c.number",
+            WANT_SELECT
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let wants = stmt
+            .query_map([series_name], want_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(wants)
+    }
+
+    /// Create an empty deck, returns its id
+    pub fn create_deck(&self, name: &str) -> Result<i32, DbError> {
+        match self
+            .conn
+            .execute("INSERT INTO decks (name) VALUES (?1)", params![name])
+        {
+            Ok(_) => Ok(self.conn.last_insert_rowid() as i32),
+            Err(rusqlite::Error::SqliteFailure(e, _)) if e.extended_code == 2067 => Err(
+                DbError::UniqueConstraintViolation(format!("deck '{}' already exists", name)),
+            ),
+            Err(e) => Err(DbError::SqliteError(e)),
+        }
+    }
+
+    pub fn delete_deck(&self, name: &str) -> Result<(), DbError> {
+        let deck_id = self.get_deck_id(name)?;
+        // its entries are deleted by the foreign key
+        self.conn
+            .execute("DELETE FROM decks WHERE id = ?1", params![deck_id])?;
+        Ok(())
+    }
+
+    fn get_deck_id(&self, name: &str) -> Result<i32, DbError> {
+        match self.conn.query_row(
+            "SELECT id FROM decks WHERE name = ?1 COLLATE NOCASE",
+            [name],
+            |r| r.get(0),
+        ) {
+            Ok(id) => Ok(id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Err(DbError::UnknownDeck(name.into())),
+            Err(e) => Err(DbError::SqliteError(e)),
+        }
+    }
+
+    /// Add copies of a card name to a deck section.
+    /// Names are matched case-insensitively against the card identities and stored with their spelling.
+    pub fn add_deck_card(&self, deck: &str, request: &DeckCardRequest) -> Result<(), DbError> {
+        if request.quantity < 1 {
+            return Err(DbError::InvalidOperation(format!(
+                "Quantity of '{}' must be at least 1",
+                request.card_name
+            )));
+        }
+        let deck_id = self.get_deck_id(deck)?;
+        let card_name: String = self
+            .conn
+            .query_row(
+                "SELECT name FROM card_identities WHERE name = ?1",
+                [&request.card_name],
+                |r| r.get(0),
+            )
+            .optional()?
+            .unwrap_or_else(|| request.card_name.clone());
+
+        self.conn.execute(
+            "INSERT INTO deck_cards (deck_id, card_name, section, quantity)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (deck_id, card_name, section)
+             DO UPDATE SET quantity = quantity + excluded.quantity",
+            params![deck_id, card_name, request.section, request.quantity],
+        )?;
+        Ok(())
+    }
+
+    /// Remove copies of a card name from a deck section, dropping the entry when none are left
+    pub fn remove_deck_card(&self, deck: &str, request: &DeckCardRequest) -> Result<(), DbError> {
+        let deck_id = self.get_deck_id(deck)?;
+        let updated = self.conn.execute(
+            "UPDATE deck_cards SET quantity = quantity - ?4
+             WHERE deck_id = ?1 AND card_name = ?2 COLLATE NOCASE AND section = ?3",
+            params![
+                deck_id,
+                request.card_name,
+                request.section,
+                request.quantity
+            ],
+        )?;
+        if updated == 0 {
+            return Err(DbError::InvalidOperation(format!(
+                "'{}' is not in the {} deck of '{}'",
+                request.card_name, request.section, deck
+            )));
+        }
+        self.conn.execute(
+            "DELETE FROM deck_cards WHERE deck_id = ?1 AND quantity <= 0",
+            params![deck_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_deck(&self, name: &str) -> Result<Deck, DbError> {
+        let deck_id = self.get_deck_id(name)?;
+        let deck_name: String =
+            self.conn
+                .query_row("SELECT name FROM decks WHERE id = ?1", [deck_id], |r| {
+                    r.get(0)
+                })?;
+        let mut stmt = self.conn.prepare(
+            "SELECT card_name, section, quantity FROM deck_cards
+             WHERE deck_id = ?1
+             ORDER BY CASE section WHEN 'main' THEN 0 WHEN 'extra' THEN 1 ELSE 2 END, id",
+        )?;
+        let entries = stmt
+            .query_map([deck_id], |row| {
+                Ok(DeckEntry {
+                    card_name: row.get(0)?,
+                    section: row.get(1)?,
+                    quantity: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Deck {
+            id: deck_id,
+            name: deck_name,
+            entries,
+        })
+    }
+
+    pub fn get_decks(&self) -> Result<Vec<Deck>, DbError> {
+        let mut stmt = self.conn.prepare("SELECT name FROM decks ORDER BY name")?;
+        let names = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>>>()?;
+        names.iter().map(|name| self.get_deck(name)).collect()
+    }
+
+    /// Resolve every entry of a deck against the owned copies of any printing with the same name.
+    /// Copies are assigned to the entries in deck order; copies used by other decks count as committed.
+    pub fn check_deck(&self, name: &str) -> Result<DeckCheck, DbError> {
+        let deck = self.get_deck(name)?;
+
+        let mut owned_stmt = self.conn.prepare(
+            "SELECT c.number, c.in_collection FROM cards c
+             JOIN card_identities i ON c.identity_id = i.id
+             WHERE i.name = ?1 AND c.in_collection > 0
+             ORDER BY c.number",
+        )?;
+        let mut other_stmt = self.conn.prepare(
+            "SELECT d.name, SUM(dc.quantity) FROM deck_cards dc
+             JOIN decks d ON d.id = dc.deck_id
+             WHERE dc.card_name = ?1 COLLATE NOCASE AND dc.deck_id != ?2
+             GROUP BY d.id
+             ORDER BY d.name",
+        )?;
+
+        // remaining owned and uncommitted copies per (lowercase) name
+        let mut remaining: HashMap<String, (i32, i32)> = HashMap::new();
+        let mut entries = Vec::new();
+        for entry in deck.entries {
+            let owned_printings = owned_stmt
+                .query_map([&entry.card_name], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?))
+                })?
+                .collect::<Result<Vec<_>>>()?;
+            let other_decks = other_stmt
+                .query_map(params![entry.card_name, deck.id], |row| {
+                    Ok(DeckCommitment {
+                        deck: row.get(0)?,
+                        quantity: row.get(1)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>>>()?;
+
+            let owned: i32 = owned_printings.iter().map(|(_, n)| n).sum();
+            let committed_elsewhere: i32 = other_decks.iter().map(|d| d.quantity).sum();
+            let (owned_left, free_left) = remaining
+                .entry(entry.card_name.to_lowercase())
+                .or_insert((owned, (owned - committed_elsewhere).max(0)));
+
+            let assigned = entry.quantity.min(*owned_left);
+            let free = assigned.min(*free_left);
+            *owned_left -= assigned;
+            *free_left -= free;
+
+            entries.push(DeckEntryCheck {
+                missing: entry.quantity - assigned,
+                committed: assigned - free,
+                owned,
+                printings: owned_printings.into_iter().map(|(n, _)| n).collect(),
+                other_decks,
+                entry,
+            });
+        }
+
+        Ok(DeckCheck {
+            deck: deck.name,
+            entries,
+        })
+    }
+
+    /// Insert a series with its cards as read from a series json file or a set page.
+    /// Returns the id of the series and the number of cards inserted; cards that
+    /// already exist are skipped. A series that exists by name gets the cards as another
+    /// region, whose release date and size are taken from the json.
+    pub fn add_series_json(&self, series_json: SeriesJson) -> Result<(i32, i32), DbError> {
+        let release_date = parse_release_date(&series_json.release_date).map(|d| d.to_string());
+        let numbers: Vec<String> = series_json
+            .cards
+            .iter()
+            .map(|c| c.card_number.clone())
+            .collect();
+        let series = Series {
+            id: None,
+            name: series_json.name.clone(),
+            release_date: series_json.release_date,
+            n_cards: series_json.ncards,
+            prefix: Some(series_json.prefix.unwrap_or(String::from(""))),
+        };
+
+        let series_id = self.insert_series(&series)?;
+        let mut cnt = 0;
+        for c in series_json.cards {
+            let (_, collection_number) = get_series_and_number(&c.card_number);
+            let card = DatabaseCard {
+                name: c.name.clone(),
+                number: c.card_number,
+                collection_number,
+                rarity_id: self.get_rarity_id(&c.rarity)?, // directly i32
+                series_id,
+                in_collection: 0,
+                card_type_id: self.get_card_type_id(&c.category)?,
+            };
+            let inserted_id = self.insert_card(&card)?;
+            if inserted_id != 0 {
+                cnt += 1;
+                if let Some(text) = &c.text {
+                    self.set_card_text(&card.number, text)?;
+                }
+                if let Some(passcode) = c.passcode {
+                    self.set_card_passcode(&card.number, passcode)?;
+                }
+                if !c.stats.is_empty() {
+                    self.set_card_stats(&card.number, &c.stats)?;
+                }
+            }
+        }
+
+        // the regions these cards are in, dated and sized by this json
+        let mut regions: Vec<String> = Vec::new();
+        for number in &numbers {
+            let region: Option<String> = self
+                .conn
+                .query_row(
+                    "SELECT region FROM cards WHERE number = ?1 AND series_id = ?2",
+                    params![number, series_id],
+                    |r| r.get(0),
+                )
+                .optional()?;
+            if let Some(region) = region.filter(|r| !regions.contains(r)) {
+                regions.push(region);
+            }
+        }
+        for region in &regions {
+            self.conn.execute(
+                "UPDATE series_regions SET release_date = COALESCE(?3, release_date), n_cards = ?4
+                 WHERE series_id = ?1 AND region = ?2",
+                params![series_id, region, release_date, series_json.ncards],
+            )?;
+        }
+        Ok((series_id, cnt))
+    }
+
+    /// Replace or extend the passcode -> name mapping and fill in missing passcodes of card identities by name.
+    /// Returns the number of mapping entries written.
+    pub fn load_passcodes(&self, entries: &[PasscodeJson]) -> Result<usize, DbError> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt =
+                tx.prepare("INSERT OR REPLACE INTO passcodes (passcode, name) VALUES (?1, ?2)")?;
+            for e in entries {
+                stmt.execute(params![e.passcode, e.name])?;
+            }
+        }
+        tx.execute(
+            "UPDATE card_identities SET passcode =
+                (SELECT MIN(p.passcode) FROM passcodes p WHERE p.name = card_identities.name)
+             WHERE passcode IS NULL",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(entries.len())
+    }
+
+    /// Set the passcode of the card identity a card number is a printing of
+    pub fn set_card_passcode(&self, number: &str, passcode: i64) -> Result<(), DbError> {
+        let updated = self.conn.execute(
+            "UPDATE card_identities SET passcode = ?2
+             WHERE id = (SELECT identity_id FROM cards WHERE number = ?1)",
+            params![number, passcode],
+        )?;
+        if updated == 0 {
+            return Err(DbError::UnknownCard(number.into()));
+        }
+        Ok(())
+    }
+
+    /// Set the text of the card identity a card number is a printing of
+    pub fn set_card_text(&self, number: &str, text: &str) -> Result<(), DbError> {
+        let updated = self.conn.execute(
+            "UPDATE card_identities SET text = ?2
+             WHERE id = (SELECT identity_id FROM cards WHERE number = ?1)",
+            params![number, text],
+        )?;
+        if updated == 0 {
+            return Err(DbError::UnknownCard(number.into()));
+        }
+        Ok(())
+    }
+
+    /// Set the stats given in `stats` on the card identity a card number is a printing of,
+    /// stats left empty keep their value
+    pub fn set_card_stats(&self, number: &str, stats: &CardStats) -> Result<(), DbError> {
+        stats.check().map_err(DbError::InvalidOperation)?;
+        let updated = self.conn.execute(
+            "UPDATE card_identities SET
+                attribute = COALESCE(?2, attribute), level = COALESCE(?3, level),
+                rank = COALESCE(?4, rank), link_rating = COALESCE(?5, link_rating),
+                monster_type = COALESCE(?6, monster_type), atk = COALESCE(?7, atk),
+                def = COALESCE(?8, def)
+             WHERE id = (SELECT identity_id FROM cards WHERE number = ?1)",
+            params![
+                number,
+                stats.attribute,
+                stats.level,
+                stats.rank,
+                stats.link_rating,
+                stats.monster_type,
+                stats.atk,
+                stats.def
+            ],
+        )?;
+        if updated == 0 {
+            return Err(DbError::UnknownCard(number.into()));
+        }
+        Ok(())
+    }
+
+    /// Card name of a passcode, from the mapping table or else from the card identities
+    pub fn get_name_by_passcode(&self, passcode: i64) -> Result<Option<String>, DbError> {
+        let name = self
+            .conn
+            .query_row(
+                "SELECT name FROM passcodes WHERE passcode = ?1
+                 UNION ALL
+                 SELECT name FROM card_identities WHERE passcode = ?1
+                 LIMIT 1",
+                [passcode],
+                |r| r.get(0),
+            )
+            .optional()?;
+        Ok(name)
+    }
+
+    /// Passcode of a card name, from its identity or else from the mapping table
+    pub fn get_passcode_by_name(&self, name: &str) -> Result<Option<i64>, DbError> {
+        let passcode = self
+            .conn
+            .query_row(
+                "SELECT passcode FROM card_identities WHERE name = ?1 AND passcode IS NOT NULL
+                 UNION ALL
+                 SELECT MIN(passcode) FROM passcodes WHERE name = ?1 COLLATE NOCASE
+                 LIMIT 1",
+                [name],
+                |r| r.get::<_, Option<i64>>(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(passcode)
+    }
+
+    /// Create a deck from a parsed `.ydk` file. Unknown passcodes are reported, not fatal.
+    pub fn import_ydk(&self, deck: &str, ydk: &Ydk) -> Result<YdkReport, DbError> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.create_deck(deck)?;
+
+        let mut report = YdkReport::default();
+        for section in [DeckSection::Main, DeckSection::Extra, DeckSection::Side] {
+            for passcode in ydk.section(&section) {
+                match self.get_name_by_passcode(*passcode)? {
+                    Some(card_name) => {
+                        self.add_deck_card(
+                            deck,
+                            &DeckCardRequest {
+                                card_name,
+                                section: section.clone(),
+                                quantity: 1,
+                            },
+                        )?;
+                        report.resolved += 1;
+                    }
+                    None => report
+                        .unresolved
+                        .push((section.clone(), passcode.to_string())),
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(report)
+    }
+
+    /// Translate a deck into `.ydk` passcodes. Card names without a known passcode are reported.
+    pub fn export_ydk(&self, deck: &str) -> Result<(Ydk, YdkReport), DbError> {
+        let deck = self.get_deck(deck)?;
+        let mut ydk = Ydk::default();
+        let mut report = YdkReport::default();
+        for entry in deck.entries {
+            match self.get_passcode_by_name(&entry.card_name)? {
+                Some(passcode) => {
+                    for _ in 0..entry.quantity {
+                        ydk.section_mut(&entry.section).push(passcode);
+                    }
+                    report.resolved += entry.quantity;
+                }
+                None => report.unresolved.push((entry.section, entry.card_name)),
+            }
+        }
+        Ok((ydk, report))
+    }
+
+    /// All owned copies as the main deck of a `.ydk` file, e.g. for loading the collection into a deck editor
+    pub fn export_collection_ydk(&self) -> Result<(Ydk, YdkReport), DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT i.name, SUM(c.in_collection) FROM cards c
+             JOIN card_identities i ON c.identity_id = i.id
+             WHERE c.in_collection > 0
+             GROUP BY i.id
+             ORDER BY i.name",
+        )?;
+        let owned = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut ydk = Ydk::default();
+        let mut report = YdkReport::default();
+        for (name, count) in owned {
+            match self.get_passcode_by_name(&name)? {
+                Some(passcode) => {
+                    ydk.main
+                        .extend(std::iter::repeat_n(passcode, count as usize));
+                    report.resolved += count;
+                }
+                None => report.unresolved.push((DeckSection::Main, name)),
+            }
+        }
+        Ok((ydk, report))
+    }
+
+    pub fn get_series_id_by_name(&self, name: &str) -> Result<i32, DbError> {
+        match self.conn.query_row(
+            "SELECT id FROM series WHERE name = ?1 COLLATE NOCASE",
+            [name],
+            |r| r.get(0),
+        ) {
+            Ok(id) => Ok(id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Err(DbError::UnknownSeries(name.into())),
+            Err(e) => Err(DbError::SqliteError(e)),
+        }
+    }
+
+    /// Import rows read by `collection_csv::read_csv`.
+    /// Each row is applied on its own savepoint, so a failing row is reported and the rest still imported.
+    /// With `dry_run` all changes are rolled back at the end.
+    pub fn import_csv_rows(
+        &self,
+        rows: Vec<CsvLine>,
+        options: &CsvImportOptions,
+    ) -> Result<CsvImportReport, DbError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut report = CsvImportReport {
+            dry_run: options.dry_run,
+            ..Default::default()
+        };
+
+        // the flag is the confirmation the cli asks for, a dry run reports the types it would create
+        if options.create_types {
+            let categories = rows
+                .iter()
+                .filter_map(|(_, row)| row.as_ref().ok())
+                .map(|row| row.card_type.as_str());
+            for cardtype in self.missing_card_types(categories)? {
+                self.create_card_type(&cardtype)?;
+                report.created_card_types.push(cardtype.display());
+            }
+        }
+
+        for (line, row) in rows {
+            let row = match row {
+                Ok(row) => row,
+                Err(e) => {
+                    report.errors.push(e);
+                    continue;
+                }
+            };
+
+            self.conn.execute_batch("SAVEPOINT csv_row")?;
+            match self.import_csv_row(&row, options.upsert) {
+                Ok(outcome) => {
+                    self.conn.execute_batch("RELEASE csv_row")?;
+                    match outcome {
+                        CsvRowOutcome::Inserted => report.inserted += 1,
+                        CsvRowOutcome::Updated => report.updated += 1,
+                        CsvRowOutcome::Unchanged => report.unchanged += 1,
+                    }
+                }
+                Err(e) => {
+                    self.conn
+                        .execute_batch("ROLLBACK TO csv_row; RELEASE csv_row")?;
+                    report.errors.push(CsvRowError {
+                        line,
+                        number: Some(row.number.clone()),
+                        message: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        if options.dry_run {
+            tx.rollback()?;
         } else {
-            let mut parts = name.splitn(2, ' '); // split into at most 2 parts
-            subtype = parts.next().unwrap_or("");
-            maintype = parts.next().unwrap_or("");
+            tx.commit()?;
+        }
+        Ok(report)
+    }
+
+    fn import_csv_row(&self, row: &CsvRow, upsert: bool) -> Result<CsvRowOutcome, DbError> {
+        let rarity_id = self.get_rarity_id(&row.rarity)?;
+        let card_type_id = self.get_card_type_id(&row.card_type)?;
+        let series_id = match self.get_series_id_by_name(&row.series) {
+            Ok(id) => id,
+            Err(DbError::UnknownSeries(_)) => {
+                let (prefix, _) = get_series_and_number(&row.number);
+                self.insert_series(&Series {
+                    id: None,
+                    name: row.series.clone(),
+                    release_date: String::new(),
+                    n_cards: 0,
+                    prefix: Some(prefix),
+                })?
+            }
+            Err(e) => return Err(e),
+        };
+        let (_, collection_number) = get_series_and_number(&row.number);
+        let card = DatabaseCard {
+            name: row.name.clone(),
+            series_id,
+            number: row.number.clone(),
+            collection_number,
+            in_collection: row.owned,
+            rarity_id,
+            card_type_id,
+        };
+        if row.owned < 0 {
+            return Err(DbError::InvalidOperation(format!(
+                "Number of copies of '{}' cannot be negative",
+                row.number
+            )));
         }
+
+        let existing = self
+            .conn
+            .query_row(
+                "SELECT i.name, c.series_id, c.rarity_id, c.card_type_id, c.in_collection
+                 FROM cards c JOIN card_identities i ON c.identity_id = i.id
+                 WHERE c.number = ?1",
+                [&row.number],
+                |r| {
+                    Ok((
+                        r.get::<_, String>(0)?,
+                        r.get::<_, i32>(1)?,
+                        r.get::<_, i32>(2)?,
+                        r.get::<_, i32>(3)?,
+                        r.get::<_, i32>(4)?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        match existing {
+            None => {
+                self.insert_card(&card)?;
+                Ok(CsvRowOutcome::Inserted)
+            }
+            Some(_) if !upsert => Err(DbError::UniqueConstraintViolation(format!(
+                "card '{}' already exists (import with upsert to update it)",
+                row.number
+            ))),
+            Some(current)
+                if current
+                    == (
+                        card.name.clone(),
+                        series_id,
+                        rarity_id,
+                        card_type_id,
+                        row.owned,
+                    ) =>
+            {
+                Ok(CsvRowOutcome::Unchanged)
+            }
+            Some(_) => {
+                self.update_card(&row.number, &card)?;
+                self.set_owned_count(&row.number, row.owned)?;
+                Ok(CsvRowOutcome::Updated)
+            }
+        }
+    }
+
+    /// Every table of the database as a single versioned document
+    pub fn create_backup(&self) -> Result<Backup, DbError> {
         let mut stmt = self
             .conn
-            .prepare("SELECT id FROM card_type WHERE maintype = ?1 and subtype = ?2")?;
-        match stmt.query_one([maintype, subtype], |r| Ok(r.get(0)?)) {
+            .prepare("SELECT id, name FROM rarity ORDER BY id")?;
+        let rarities = stmt
+            .query_map([], |row| {
+                Ok(Rarity {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT maintype, subtype FROM card_type ORDER BY id")?;
+        let card_types = stmt
+            .query_map([], |row| {
+                Ok(CardType {
+                    main: row.get(0)?,
+                    sub: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT s.name, sr.region, sr.language, sr.release_date, sr.n_cards
+             FROM series_regions sr
+             JOIN series s ON s.id = sr.series_id
+             ORDER BY sr.id",
+        )?;
+        let regions = stmt
+            .query_map([], |row| {
+                Ok(BackupRegion {
+                    series: row.get(0)?,
+                    region: row.get(1)?,
+                    language: row.get(2)?,
+                    release_date: row.get(3)?,
+                    n_cards: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, passcode, text, attribute, level, rank, link_rating, monster_type,
+                atk, def
+             FROM card_identities ORDER BY id",
+        )?;
+        let identities = stmt
+            .query_map([], identity_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = self
+            .conn
+            .prepare(&format!("{} ORDER BY c.id", *CARD_SELECT))?;
+        let mut cards = Vec::new();
+        for card in stmt.query_map([], card_from_row)? {
+            let card = card?;
+            cards.push(BackupCard {
+                copies: self.get_all_copies(&card.number)?,
+                number: card.number,
+                name: card.name,
+                series: card.series.name,
+                collection_number: card.collection_number,
+                rarity: card.rarity.name,
+                cardtype: card.cardtype,
+                passcode: card.passcode,
+                text: card.text,
+            });
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT p.number, r.name, p.condition, p.price, p.recorded_at
+             FROM price p
+             JOIN rarity r ON p.rarity_id = r.id
+             ORDER BY p.id",
+        )?;
+        let prices = stmt
+            .query_map([], |row| {
+                Ok(MarketPrice {
+                    number: row.get(0)?,
+                    rarity: row.get(1)?,
+                    condition: row.get(2)?,
+                    price: row.get(3)?,
+                    date: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        let wants = self
+            .get_wants()?
+            .into_iter()
+            .map(|w| NewWant {
+                number: w.number,
+                quantity: w.quantity,
+                max_price: w.max_price,
+                priority: w.priority,
+            })
+            .collect();
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT passcode, name FROM passcodes ORDER BY passcode")?;
+        let passcodes = stmt
+            .query_map([], |row| {
+                Ok(PasscodeJson {
+                    passcode: row.get(0)?,
+                    name: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Backup {
+            format_version: BACKUP_FORMAT_VERSION,
+            schema_version: self.schema_version()?,
+            created_at: Local::now().to_rfc3339(),
+            rarities,
+            card_types,
+            series: self.get_unique_series()?,
+            regions,
+            identities,
+            cards,
+            prices,
+            wants,
+            decks: self.get_decks()?,
+            passcodes,
+        })
+    }
+
+    /// All copies of a single card number including sold ones, oldest first
+    pub fn get_all_copies(&self, number: &str) -> Result<Vec<Copy>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT cp.id, cp.condition, cp.edition, cp.language, cp.notes,
+                    cp.purchase_price, cp.purchase_date, cp.sale_price, cp.sale_date
+             FROM copies cp
+             JOIN cards c ON c.id = cp.card_id
+             WHERE c.number = ?1
+             ORDER BY cp.id",
+        )?;
+        let copies = stmt
+            .query_map([number], |row| {
+                Ok(Copy {
+                    id: row.get(0)?,
+                    condition: row.get(1)?,
+                    edition: row.get(2)?,
+                    language: row.get(3)?,
+                    notes: row.get(4)?,
+                    purchase_price: row.get(5)?,
+                    purchase_date: row.get(6)?,
+                    sale_price: row.get(7)?,
+                    sale_date: row.get(8)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(copies)
+    }
+
+    /// Restore a backup made by `create_backup` in a single transaction.
+    /// Rarities, card types and series are matched by name and created when missing;
+    /// `policy` decides what happens to cards, wants, decks and passcodes that already exist.
+    pub fn restore_backup(
+        &self,
+        backup: &Backup,
+        policy: &ConflictPolicy,
+    ) -> Result<RestoreReport, DbError> {
+        if backup.format_version > BACKUP_FORMAT_VERSION {
+            return Err(DbError::InvalidOperation(format!(
+                "Backup format version {} is newer than the supported version {}",
+                backup.format_version, BACKUP_FORMAT_VERSION
+            )));
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut report = RestoreReport::default();
+
+        for rarity in &backup.rarities {
+            self.insert_rarity(&rarity.name)?;
+        }
+        for card_type in &backup.card_types {
+            self.insert_card_type(&card_type.main, &card_type.sub)?;
+        }
+        for series in &backup.series {
+            report.series += self.restore_series(series, policy)? as i32;
+        }
+        // before the cards, whose triggers would add their regions with the series date
+        for region in &backup.regions {
+            report.regions += self.restore_series_region(region, policy)? as i32;
+        }
+        for identity in &backup.identities {
+            report.identities += self.restore_identity(identity, policy)? as i32;
+        }
+        for card in &backup.cards {
+            self.restore_card(card, policy, &mut report)?;
+        }
+
+        {
+            let mut stmt = self.conn.prepare(
+                "INSERT INTO price (number, rarity_id, condition, price, recorded_at)
+                 SELECT number, rarity_id, ?2, ?3, ?4 FROM cards c
+                 WHERE number = ?1 AND NOT EXISTS (
+                    SELECT 1 FROM price p
+                    WHERE p.number = c.number AND p.condition = ?2
+                      AND p.price = ?3 AND p.recorded_at = ?4)",
+            )?;
+            for price in &backup.prices {
+                report.prices += stmt.execute(params![
+                    price.number,
+                    price.condition,
+                    price.price,
+                    price.date
+                ])? as i32;
+            }
+        }
+
+        for want in &backup.wants {
+            let existing = self
+                .conn
+                .query_row(
+                    "SELECT w.quantity FROM wants w JOIN cards c ON c.id = w.card_id
+                     WHERE c.number = ?1",
+                    [&want.number],
+                    |r| r.get::<_, i32>(0),
+                )
+                .optional()?;
+            let want = match (existing, policy) {
+                (Some(_), ConflictPolicy::Skip) => continue,
+                (Some(quantity), ConflictPolicy::MergeQuantities) => NewWant {
+                    quantity: quantity + want.quantity,
+                    ..want.clone()
+                },
+                _ => want.clone(),
+            };
+            self.upsert_want(&want)?;
+            report.wants += 1;
+        }
+
+        for deck in &backup.decks {
+            let deck_id = match (self.get_deck_id(&deck.name), policy) {
+                (Ok(_), ConflictPolicy::Skip) => continue,
+                (Ok(id), ConflictPolicy::Overwrite) => {
+                    self.conn
+                        .execute("DELETE FROM deck_cards WHERE deck_id = ?1", [id])?;
+                    id
+                }
+                (Ok(id), ConflictPolicy::MergeQuantities) => id,
+                (Err(DbError::UnknownDeck(_)), _) => self.create_deck(&deck.name)?,
+                (Err(e), _) => return Err(e),
+            };
+            let mut stmt = self.conn.prepare_cached(
+                "INSERT INTO deck_cards (deck_id, card_name, section, quantity)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (deck_id, card_name, section)
+                 DO UPDATE SET quantity = quantity + excluded.quantity",
+            )?;
+            for entry in &deck.entries {
+                stmt.execute(params![
+                    deck_id,
+                    entry.card_name,
+                    entry.section,
+                    entry.quantity
+                ])?;
+            }
+            report.decks += 1;
+        }
+
+        {
+            let sql = match policy {
+                ConflictPolicy::Overwrite => {
+                    "INSERT OR REPLACE INTO passcodes (passcode, name) VALUES (?1, ?2)"
+                }
+                _ => "INSERT OR IGNORE INTO passcodes (passcode, name) VALUES (?1, ?2)",
+            };
+            let mut stmt = self.conn.prepare(sql)?;
+            for entry in &backup.passcodes {
+                report.passcodes += stmt.execute(params![entry.passcode, entry.name])? as i32;
+            }
+        }
+
+        tx.commit()?;
+        Ok(report)
+    }
+
+    // Insert a series from a backup, or update it with `Overwrite`. Returns whether it was written.
+    fn restore_series(&self, series: &Series, policy: &ConflictPolicy) -> Result<bool, DbError> {
+        let written = match policy {
+            ConflictPolicy::Overwrite => self.conn.execute(
+                "INSERT INTO series (name, release_date, n_cards, prefix)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (name) DO UPDATE SET
+                    release_date = excluded.release_date,
+                    n_cards = excluded.n_cards,
+                    prefix = excluded.prefix",
+                params![
+                    series.name,
+                    series.release_date,
+                    series.n_cards,
+                    series.prefix
+                ],
+            )?,
+            _ => self.conn.execute(
+                "INSERT OR IGNORE INTO series (name, release_date, n_cards, prefix)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    series.name,
+                    series.release_date,
+                    series.n_cards,
+                    series.prefix
+                ],
+            )?,
+        };
+        Ok(written > 0)
+    }
+
+    // Insert a regional printing from a backup, or update it with `Overwrite`
+    fn restore_series_region(
+        &self,
+        region: &BackupRegion,
+        policy: &ConflictPolicy,
+    ) -> Result<bool, DbError> {
+        let series_id = self.get_series_id_by_name(&region.series)?;
+        let sql = match policy {
+            ConflictPolicy::Overwrite => {
+                "INSERT INTO series_regions (series_id, region, language, release_date, n_cards)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (series_id, region) DO UPDATE SET
+                    language = excluded.language,
+                    release_date = excluded.release_date,
+                    n_cards = excluded.n_cards"
+            }
+            _ => {
+                "INSERT OR IGNORE INTO series_regions
+                    (series_id, region, language, release_date, n_cards)
+                 VALUES (?1, ?2, ?3, ?4, ?5)"
+            }
+        };
+        let written = self.conn.execute(
+            sql,
+            params![
+                series_id,
+                region.region,
+                region.language,
+                region.release_date,
+                region.n_cards
+            ],
+        )?;
+        Ok(written > 0)
+    }
+
+    // Insert a card identity from a backup by name. `Overwrite` replaces the fields of an
+    // existing one, `MergeQuantities` fills in its missing fields.
+    fn restore_identity(
+        &self,
+        identity: &CardIdentity,
+        policy: &ConflictPolicy,
+    ) -> Result<bool, DbError> {
+        let on_conflict = match policy {
+            ConflictPolicy::Skip => "DO NOTHING",
+            ConflictPolicy::Overwrite => {
+                "DO UPDATE SET passcode = excluded.passcode, text = excluded.text,
+                    attribute = excluded.attribute, level = excluded.level,
+                    rank = excluded.rank, link_rating = excluded.link_rating,
+                    monster_type = excluded.monster_type, atk = excluded.atk, def = excluded.def"
+            }
+            ConflictPolicy::MergeQuantities => {
+                "DO UPDATE SET passcode = COALESCE(passcode, excluded.passcode),
+                    text = COALESCE(text, excluded.text),
+                    attribute = COALESCE(attribute, excluded.attribute),
+                    level = COALESCE(level, excluded.level),
+                    rank = COALESCE(rank, excluded.rank),
+                    link_rating = COALESCE(link_rating, excluded.link_rating),
+                    monster_type = COALESCE(monster_type, excluded.monster_type),
+                    atk = COALESCE(atk, excluded.atk), def = COALESCE(def, excluded.def)"
+            }
+        };
+        let written = self.conn.execute(
+            &format!(
+                "INSERT INTO card_identities (name, passcode, text, attribute, level, rank,
+                    link_rating, monster_type, atk, def)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                 ON CONFLICT (name) {}",
+                on_conflict
+            ),
+            params![
+                identity.name,
+                identity.passcode,
+                identity.text,
+                identity.stats.attribute,
+                identity.stats.level,
+                identity.stats.rank,
+                identity.stats.link_rating,
+                identity.stats.monster_type,
+                identity.stats.atk,
+                identity.stats.def
+            ],
+        )?;
+        Ok(written > 0)
+    }
+
+    fn restore_card(
+        &self,
+        card: &BackupCard,
+        policy: &ConflictPolicy,
+        report: &mut RestoreReport,
+    ) -> Result<(), DbError> {
+        let series_id = self.get_series_id_by_name(&card.series)?;
+        let rarity_id = self.get_rarity_id(&card.rarity)?;
+        let card_type_id = self
+            .find_card_type(&card.cardtype)?
+            .ok_or_else(|| DbError::UnknownCardType(card.cardtype.display()))?;
+
+        let existing: Option<i32> = self
+            .conn
+            .query_row(
+                "SELECT id FROM cards WHERE number = ?1",
+                [&card.number],
+                |r| r.get(0),
+            )
+            .optional()?;
+
+        let card_id = match (existing, policy) {
+            (Some(_), ConflictPolicy::Skip) => {
+                report.cards_skipped += 1;
+                return Ok(());
+            }
+            (Some(id), ConflictPolicy::Overwrite) => {
+                let identity_id = self.insert_identity(&card.name)?;
+                self.conn.execute(
+                    "UPDATE cards SET identity_id = ?2, series_id = ?3, collection_number = ?4,
+                        rarity_id = ?5, card_type_id = ?6
+                     WHERE id = ?1",
+                    params![
+                        id,
+                        identity_id,
+                        series_id,
+                        card.collection_number,
+                        rarity_id,
+                        card_type_id
+                    ],
+                )?;
+                self.conn.execute(
+                    "UPDATE card_identities SET passcode = ?2, text = ?3 WHERE id = ?1",
+                    params![identity_id, card.passcode, card.text],
+                )?;
+                self.conn
+                    .execute("DELETE FROM copies WHERE card_id = ?1", [id])?;
+                report.cards_overwritten += 1;
+                id
+            }
+            (Some(id), ConflictPolicy::MergeQuantities) => {
+                self.conn.execute(
+                    "UPDATE card_identities
+                     SET passcode = COALESCE(passcode, ?2), text = COALESCE(text, ?3)
+                     WHERE id = (SELECT identity_id FROM cards WHERE id = ?1)",
+                    params![id, card.passcode, card.text],
+                )?;
+                report.cards_merged += 1;
+                id
+            }
+            (None, _) => {
+                let identity_id = self.insert_identity(&card.name)?;
+                self.conn.execute(
+                    "INSERT INTO cards (identity_id, series_id, number, collection_number,
+                        in_collection, rarity_id, card_type_id)
+                     VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6)",
+                    params![
+                        identity_id,
+                        series_id,
+                        card.number,
+                        card.collection_number,
+                        rarity_id,
+                        card_type_id
+                    ],
+                )?;
+                let card_id = self.conn.last_insert_rowid() as i32;
+                // another printing may have brought the identity already
+                self.conn.execute(
+                    "UPDATE card_identities
+                     SET passcode = COALESCE(passcode, ?2), text = COALESCE(text, ?3)
+                     WHERE id = ?1",
+                    params![identity_id, card.passcode, card.text],
+                )?;
+                report.cards_inserted += 1;
+                card_id
+            }
+        };
+
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT INTO copies (card_id, condition, edition, language, notes,
+                purchase_price, purchase_date, sale_price, sale_date)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        for copy in &card.copies {
+            stmt.execute(params![
+                card_id,
+                copy.condition,
+                copy.edition,
+                copy.language,
+                copy.notes,
+                copy.purchase_price,
+                copy.purchase_date,
+                copy.sale_price,
+                copy.sale_date
+            ])?;
+            report.copies += 1;
+        }
+        Ok(())
+    }
+
+    /// All rarities with the number of cards and owned copies using them
+    pub fn get_rarities(&self) -> Result<Vec<RarityUsage>, DbError> {
+        self.query_rarities("1", &[])
+    }
+
+    // Rarities matching a WHERE clause over `r`, with their usage
+    fn query_rarities(
+        &self,
+        where_clause: &str,
+        params: &[rusqlite::types::Value],
+    ) -> Result<Vec<RarityUsage>, DbError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT r.id, r.name, COUNT(c.id), COALESCE(SUM(c.in_collection), 0)
+             FROM rarity r
+             LEFT JOIN cards c ON c.rarity_id = r.id
+             WHERE {}
+             GROUP BY r.id
+             ORDER BY r.id",
+            where_clause
+        ))?;
+        let rarities = stmt
+            .query_map(params_from_iter(params), |row| {
+                Ok(RarityUsage {
+                    rarity: Rarity {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                    },
+                    cards: row.get(2)?,
+                    owned_copies: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(rarities)
+    }
+
+    /// All card types with the number of cards and owned copies using them
+    pub fn get_card_types(&self) -> Result<Vec<CardTypeUsage>, DbError> {
+        self.query_card_types("1", &[])
+    }
+
+    // Card types matching a WHERE clause over `t`, with their usage
+    fn query_card_types(
+        &self,
+        where_clause: &str,
+        params: &[rusqlite::types::Value],
+    ) -> Result<Vec<CardTypeUsage>, DbError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT t.id, t.maintype, t.subtype, COUNT(c.id), COALESCE(SUM(c.in_collection), 0)
+             FROM card_type t
+             LEFT JOIN cards c ON c.card_type_id = t.id
+             WHERE {}
+             GROUP BY t.id
+             ORDER BY t.maintype, t.subtype",
+            where_clause
+        ))?;
+        let card_types = stmt
+            .query_map(params_from_iter(params), |row| {
+                let cardtype = CardType {
+                    main: row.get(1)?,
+                    sub: row.get(2)?,
+                };
+                Ok(CardTypeUsage {
+                    id: row.get(0)?,
+                    display: cardtype.display(),
+                    taxon: CardTypeDefinitions::bundled()
+                        .parse(&cardtype.display())
+                        .ok(),
+                    cardtype,
+                    cards: row.get(3)?,
+                    owned_copies: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(card_types)
+    }
+
+    /// A single rarity with its usage
+    pub fn get_rarity(&self, id: i32) -> Result<RarityUsage, DbError> {
+        self.query_rarities("r.id = ?1", &[id.into()])?
+            .pop()
+            .ok_or_else(|| DbError::UnknownRarity(id.to_string()))
+    }
+
+    /// Add a rarity, its name must be new
+    pub fn create_rarity(&self, name: &str) -> Result<RarityUsage, DbError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(DbError::InvalidOperation(
+                "Rarity name must not be empty".into(),
+            ));
+        }
+        if self.get_rarity_id(name).is_ok() {
+            return Err(DbError::UniqueConstraintViolation(format!(
+                "rarity '{}' already exists",
+                name
+            )));
+        }
+        self.insert_rarity(name)?;
+        self.get_rarity(self.get_rarity_id(name)?)
+    }
+
+    pub fn rename_rarity(&self, id: i32, name: &str) -> Result<RarityUsage, DbError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(DbError::InvalidOperation(
+                "Rarity name must not be empty".into(),
+            ));
+        }
+        self.get_rarity(id)?;
+        match self.get_rarity_id(name) {
+            Ok(existing) if existing != id => {
+                return Err(DbError::UniqueConstraintViolation(format!(
+                    "rarity '{}' already exists",
+                    name
+                )));
+            }
+            _ => {}
+        }
+        self.conn.execute(
+            "UPDATE rarity SET name = ?2 WHERE id = ?1",
+            params![id, name],
+        )?;
+        self.get_rarity(id)
+    }
+
+    /// Delete a rarity that no card uses
+    pub fn delete_rarity(&self, id: i32) -> Result<(), DbError> {
+        let rarity = self.get_rarity(id)?;
+        if rarity.cards > 0 {
+            return Err(DbError::InUse(format!(
+                "rarity '{}' is used by {} cards",
+                rarity.rarity.name, rarity.cards
+            )));
+        }
+        self.conn
+            .execute("DELETE FROM price WHERE rarity_id = ?1", [id])?;
+        self.conn
+            .execute("DELETE FROM rarity WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// A single card type with its usage
+    pub fn get_card_type(&self, id: i32) -> Result<CardTypeUsage, DbError> {
+        self.query_card_types("t.id = ?1", &[id.into()])?
+            .pop()
+            .ok_or_else(|| DbError::UnknownCardType(id.to_string()))
+    }
+
+    // Id of the card type with exactly these main and sub types
+    fn find_card_type(&self, cardtype: &CardType) -> Result<Option<i32>, DbError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT id FROM card_type WHERE maintype = ?1 AND subtype = ?2",
+                params![cardtype.main, cardtype.sub],
+                |r| r.get(0),
+            )
+            .optional()?)
+    }
+
+    fn check_card_type(&self, cardtype: &CardType, id: Option<i32>) -> Result<(), DbError> {
+        if cardtype.main.trim().is_empty() || cardtype.sub.trim().is_empty() {
+            return Err(DbError::InvalidOperation(
+                "Card type needs a main and a sub type".into(),
+            ));
+        }
+        match self.find_card_type(cardtype)? {
+            Some(existing) if Some(existing) != id => Err(DbError::UniqueConstraintViolation(
+                format!("card type '{}' already exists", cardtype.display()),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Add a card type, the combination of main and sub type must be new
+    pub fn create_card_type(&self, cardtype: &CardType) -> Result<CardTypeUsage, DbError> {
+        self.check_card_type(cardtype, None)?;
+        self.insert_card_type(&cardtype.main, &cardtype.sub)?;
+        let id = self.find_card_type(cardtype)?.unwrap_or_default();
+        self.get_card_type(id)
+    }
+
+    pub fn update_card_type(&self, id: i32, cardtype: &CardType) -> Result<CardTypeUsage, DbError> {
+        self.get_card_type(id)?;
+        self.check_card_type(cardtype, Some(id))?;
+        self.conn.execute(
+            "UPDATE card_type SET maintype = ?2, subtype = ?3 WHERE id = ?1",
+            params![id, cardtype.main, cardtype.sub],
+        )?;
+        self.get_card_type(id)
+    }
+
+    /// Delete a card type that no card uses
+    pub fn delete_card_type(&self, id: i32) -> Result<(), DbError> {
+        let cardtype = self.get_card_type(id)?;
+        if cardtype.cards > 0 {
+            return Err(DbError::InUse(format!(
+                "card type '{}' is used by {} cards",
+                cardtype.display, cardtype.cards
+            )));
+        }
+        self.conn
+            .execute("DELETE FROM card_type WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn get_rarity_id(&self, rarity_name: &str) -> Result<i32, DbError> {
+        let mut stmt = self.conn.prepare("SELECT id FROM rarity WHERE name = ?1")?;
+        match stmt.query_row([rarity_name], |r| r.get(0)) {
             Ok(id) => Ok(id),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Err(DbError::UnknownCardType(format!(
-                "{},{}",
-                maintype, subtype
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Err(DbError::UnknownRarity(rarity_name.into()))
+            }
+            Err(e) => Err(DbError::SqliteError(e)),
+        }
+    }
+
+    pub fn get_series_by_id(&self, id: i32) -> Result<Series, DbError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name, release_date, n_cards,prefix FROM series WHERE id = ?1")?;
+
+        match stmt.query_row([id], |r| {
+            Ok(Series {
+                id: r.get(0)?,
+                name: r.get(1)?,
+                release_date: r.get(2)?,
+                n_cards: r.get(3)?,
+                prefix: r.get(4)?,
+            })
+        }) {
+            Ok(series) => Ok(series),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Err(DbError::UnknownSeries(id.to_string()))
+            }
+            Err(e) => Err(DbError::SqliteError(e)),
+        }
+    }
+
+    fn check_series(&self, series: &Series, id: Option<i32>) -> Result<(), DbError> {
+        if series.name.trim().is_empty() {
+            return Err(DbError::InvalidOperation(
+                "Series name must not be empty".into(),
+            ));
+        }
+        if series.n_cards < 0 {
+            return Err(DbError::InvalidOperation(format!(
+                "Series '{}' cannot have a negative number of cards",
+                series.name
+            )));
+        }
+        parse_date(Some(&series.release_date))?;
+        match self.get_series_id_by_name(&series.name) {
+            Ok(existing) if Some(existing) != id => Err(DbError::UniqueConstraintViolation(
+                format!("series '{}' already exists", series.name),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Add a series with a YYYY-MM-DD release date, its name must be new
+    pub fn create_series(&self, series: &Series) -> Result<Series, DbError> {
+        self.check_series(series, None)?;
+        let id = self.insert_series(series)?;
+        self.get_series_by_id(id)
+    }
+
+    pub fn update_series(&self, id: i32, series: &Series) -> Result<Series, DbError> {
+        self.get_series_by_id(id)?;
+        self.check_series(series, Some(id))?;
+        self.conn.execute(
+            "UPDATE series SET name = ?2, release_date = ?3, n_cards = ?4, prefix = ?5
+             WHERE id = ?1",
+            params![
+                id,
+                series.name,
+                series.release_date,
+                series.n_cards,
+                series.prefix
+            ],
+        )?;
+        self.get_series_by_id(id)
+    }
+
+    /// Delete a series without cards
+    pub fn delete_series(&self, id: i32) -> Result<(), DbError> {
+        let series = self.get_series_by_id(id)?;
+        let cards: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM cards WHERE series_id = ?1",
+            [id],
+            |r| r.get(0),
+        )?;
+        if cards > 0 {
+            return Err(DbError::InUse(format!(
+                "series '{}' has {} cards",
+                series.name, cards
+            )));
+        }
+        // its regions are deleted by the foreign key
+        self.conn
+            .execute("DELETE FROM series WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// The regional printings of a series with the cards defined and owned in each,
+    /// oldest release first
+    pub fn get_series_regions(&self, series_id: i32) -> Result<Vec<SeriesRegionSummary>, DbError> {
+        self.get_series_by_id(series_id)?;
+        let mut stmt = self.conn.prepare(
+            "SELECT sr.id, sr.series_id, sr.region, sr.language, sr.release_date, sr.n_cards,
+                (SELECT COUNT(*) FROM cards c
+                 WHERE c.series_id = sr.series_id AND c.region = sr.region),
+                (SELECT COUNT(*) FROM cards c
+                 WHERE c.series_id = sr.series_id AND c.region = sr.region AND c.in_collection > 0),
+                (SELECT GROUP_CONCAT(name, char(10)) FROM (
+                    SELECT DISTINCT r.name FROM cards c JOIN rarity r ON r.id = c.rarity_id
+                    WHERE c.series_id = sr.series_id AND c.region = sr.region ORDER BY r.id))
+             FROM series_regions sr
+             WHERE sr.series_id = ?1
+             ORDER BY sr.release_date IS NULL, sr.release_date, sr.region",
+        )?;
+        let regions = stmt
+            .query_map([series_id], |r| {
+                let rarities: Option<String> = r.get(8)?;
+                Ok(SeriesRegionSummary {
+                    region: series_region_from_row(r)?,
+                    cards: r.get(6)?,
+                    owned_cards: r.get(7)?,
+                    rarities: rarities
+                        .map(|names| names.split('\n').map(str::to_string).collect())
+                        .unwrap_or_default(),
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(regions)
+    }
+
+    pub fn get_series_region(&self, id: i32) -> Result<SeriesRegion, DbError> {
+        match self.conn.query_row(
+            "SELECT id, series_id, region, language, release_date, n_cards
+             FROM series_regions WHERE id = ?1",
+            [id],
+            series_region_from_row,
+        ) {
+            Ok(region) => Ok(region),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Err(DbError::UnknownRegion(id.to_string()))
+            }
+            Err(e) => Err(DbError::SqliteError(e)),
+        }
+    }
+
+    /// A regional printing by series name and region code, e.g. ("Legend of Blue Eyes White Dragon", "DE")
+    pub fn get_series_region_by_code(
+        &self,
+        series_name: &str,
+        region: &str,
+    ) -> Result<SeriesRegion, DbError> {
+        let series_id = self.get_series_id_by_name(series_name)?;
+        match self.conn.query_row(
+            "SELECT id, series_id, region, language, release_date, n_cards
+             FROM series_regions WHERE series_id = ?1 AND region = ?2",
+            params![series_id, region.to_uppercase()],
+            series_region_from_row,
+        ) {
+            Ok(region) => Ok(region),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Err(DbError::UnknownRegion(format!(
+                "{} in series '{}'",
+                region, series_name
             ))),
             Err(e) => Err(DbError::SqliteError(e)),
         }
     }
 
+    // Validate a regional printing and fill in its language from the region code when empty
+    fn check_series_region(
+        &self,
+        region: &SeriesRegion,
+        id: Option<i32>,
+    ) -> Result<SeriesRegion, DbError> {
+        let series = self.get_series_by_id(region.series_id)?;
+        let code = region.region.trim().to_uppercase();
+        if !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(DbError::InvalidOperation(format!(
+                "Region code '{}' must only have letters",
+                region.region
+            )));
+        }
+        if region.n_cards < 0 {
+            return Err(DbError::InvalidOperation(format!(
+                "Region '{}' of series '{}' cannot have a negative number of cards",
+                code, series.name
+            )));
+        }
+        let release_date = match region.release_date.as_deref() {
+            Some(date) => Some(parse_date(Some(date))?),
+            None => None,
+        };
+        let language = match region.language.trim() {
+            "" => self
+                .conn
+                .query_row(
+                    "SELECT language FROM region_languages WHERE region = ?1",
+                    [&code],
+                    |r| r.get(0),
+                )
+                .optional()?
+                .unwrap_or_else(|| code.clone()),
+            language => language.to_uppercase(),
+        };
+        if language.is_empty() {
+            return Err(DbError::InvalidOperation(format!(
+                "Region '{}' of series '{}' needs a language",
+                code, series.name
+            )));
+        }
+        let existing: Option<i32> = self
+            .conn
+            .query_row(
+                "SELECT id FROM series_regions WHERE series_id = ?1 AND region = ?2",
+                params![region.series_id, code],
+                |r| r.get(0),
+            )
+            .optional()?;
+        if existing.is_some() && existing != id {
+            return Err(DbError::UniqueConstraintViolation(format!(
+                "region '{}' of series '{}' already exists",
+                code, series.name
+            )));
+        }
+        Ok(SeriesRegion {
+            id,
+            series_id: region.series_id,
+            region: code,
+            language,
+            release_date,
+            n_cards: region.n_cards,
+        })
+    }
+
+    /// Add a regional printing to a series, an empty language is looked up from the region code
+    pub fn create_series_region(&self, region: &SeriesRegion) -> Result<SeriesRegion, DbError> {
+        let region = self.check_series_region(region, None)?;
+        self.conn.execute(
+            "INSERT INTO series_regions (series_id, region, language, release_date, n_cards)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                region.series_id,
+                region.region,
+                region.language,
+                region.release_date,
+                region.n_cards
+            ],
+        )?;
+        self.get_series_region(self.conn.last_insert_rowid() as i32)
+    }
+
+    /// Change language, release date and size of a regional printing. The series and region
+    /// code are fixed, they follow from the numbers of its cards.
+    pub fn update_series_region(
+        &self,
+        id: i32,
+        region: &SeriesRegion,
+    ) -> Result<SeriesRegion, DbError> {
+        let current = self.get_series_region(id)?;
+        if current.series_id != region.series_id
+            || !current.region.eq_ignore_ascii_case(region.region.trim())
+        {
+            return Err(DbError::InvalidOperation(format!(
+                "Region '{}' cannot move to another series or region code",
+                current.region
+            )));
+        }
+        let region = self.check_series_region(region, Some(id))?;
+        self.conn.execute(
+            "UPDATE series_regions SET language = ?2, release_date = ?3, n_cards = ?4
+             WHERE id = ?1",
+            params![id, region.language, region.release_date, region.n_cards],
+        )?;
+        self.get_series_region(id)
+    }
+
+    /// Delete a regional printing without cards
+    pub fn delete_series_region(&self, id: i32) -> Result<(), DbError> {
+        let region = self.get_series_region(id)?;
+        let cards: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM cards WHERE series_id = ?1 AND region = ?2",
+            params![region.series_id, region.region],
+            |r| r.get(0),
+        )?;
+        if cards > 0 {
+            return Err(DbError::InUse(format!(
+                "region '{}' has {} cards",
+                region.region, cards
+            )));
+        }
+        self.conn
+            .execute("DELETE FROM series_regions WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// All printings of the slot a card number is in: the cards of its series with the same
+    /// collection number, e.g. LOB-EN001, LOB-DE001 and LOB-001, ordered by region code
+    pub fn get_slot_cards(&self, number: &str) -> Result<Vec<Card>, DbError> {
+        let card = self.get_database_card(number)?;
+        let mut stmt = self.conn.prepare(&format!(
+            "{} WHERE c.series_id = ?1 AND c.collection_number = ?2 ORDER BY c.region, c.number",
+            *CARD_SELECT
+        ))?;
+        let mut cards = stmt
+            .query_map(
+                params![card.series_id, card.collection_number],
+                card_from_row,
+            )?
+            .collect::<Result<Vec<_>>>()?;
+        self.attach_copies(&mut cards)?;
+        Ok(cards)
+    }
+
+    /// Id of the card type of a wiki category like "Synchro Tuner Effect Monster",
+    /// read through the bundled card type definitions
+    pub fn get_card_type_id(&self, category: &str) -> Result<i32, DbError> {
+        let cardtype = CardTypeDefinitions::bundled()
+            .parse_card_type(category)
+            .map_err(DbError::UnknownCardType)?;
+        self.find_card_type(&cardtype)?
+            .ok_or_else(|| DbError::UnknownCardType(cardtype.display()))
+    }
+
+    /// Card types of the given categories that are not in the database yet, each once.
+    /// Categories outside the card type definitions are left out, adding their cards fails.
+    pub fn missing_card_types<'a>(
+        &self,
+        categories: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<CardType>, DbError> {
+        let mut missing: Vec<CardType> = Vec::new();
+        for category in categories {
+            let Ok(cardtype) = CardTypeDefinitions::bundled().parse_card_type(category) else {
+                continue;
+            };
+            if self.find_card_type(&cardtype)?.is_none() && !missing.contains(&cardtype) {
+                missing.push(cardtype);
+            }
+        }
+        Ok(missing)
+    }
+
     pub fn get_unique_series(&self) -> Result<Vec<Series>, DbError> {
         let mut stmt = self
             .conn
@@ -486,7 +3056,7 @@ impl DatabaseConnection {
 
 pub fn setup(dbname: &str) -> Result<DatabaseConnection, Box<dyn Error>> {
     let db = DatabaseConnection::new(dbname)?;
-    db.create_tables()?;
+    db.migrate()?;
 
     // Insert rarities
     db.insert_rarity("Common")?;
@@ -497,20 +3067,28 @@ pub fn setup(dbname: &str) -> Result<DatabaseConnection, Box<dyn Error>> {
     db.insert_rarity("Starlight Rare")?;
     db.insert_rarity("Quarter Century Rare")?;
 
-    // Insert card types
+    // Insert card types, as stored for the common wiki categories
     db.insert_card_type("Spell Card", "Normal")?;
     db.insert_card_type("Spell Card", "Equip")?;
     db.insert_card_type("Spell Card", "Field")?;
     db.insert_card_type("Spell Card", "Quick-Play")?;
     db.insert_card_type("Monster", "Normal")?;
-    db.insert_card_type("Monster", "Flip")?;
+    db.insert_card_type("Monster", "Flip Effect")?;
     db.insert_card_type("Monster", "Effect")?;
-    db.insert_card_type("Monster", "Union")?;
+    db.insert_card_type("Monster", "Union Effect")?;
     db.insert_card_type("Fusion Monster", "Normal")?;
     db.insert_card_type("Fusion Monster", "Effect")?;
     db.insert_card_type("Trap Card", "Normal")?;
     db.insert_card_type("Trap Card", "Continuous")?;
     db.insert_card_type("Trap Card", "Counter")?;
+    db.insert_card_type("Spell Card", "Continuous")?;
+    db.insert_card_type("Spell Card", "Ritual")?;
+    db.insert_card_type("Monster", "Gemini Effect")?;
+    db.insert_card_type("Monster", "Spirit Effect")?;
+    db.insert_card_type("Monster", "Toon Effect")?;
+    db.insert_card_type("Monster", "Tuner Effect")?;
+    db.insert_card_type("Ritual Monster", "Normal")?;
+    db.insert_card_type("Ritual Monster", "Effect")?;
 
     Ok(db)
 }
@@ -1,12 +1,20 @@
 use std::fmt;
 
+use crate::migrations::SCHEMA_VERSION;
+
 #[derive(Debug)]
 pub enum DbError {
     UnknownRarity(String),
     UnknownSeries(String),
     UnknownCardType(String),
+    UnknownCard(String),
+    UnknownDeck(String),
+    UnknownRegion(String),
     UniqueConstraintViolation(String),
+    /// Deleting a row that other rows still refer to
+    InUse(String),
     InvalidOperation(String),
+    UnsupportedSchemaVersion(i32),
 
     SqliteError(rusqlite::Error),
 }
@@ -19,8 +27,17 @@ impl fmt::Display for DbError {
             DbError::UnknownCardType(name) => {
                 write!(f, "Encountered undefined card type: {}", name)
             }
+            DbError::UnknownCard(name) => write!(f, "Encountered undefined card: {}", name),
+            DbError::UnknownDeck(name) => write!(f, "Encountered undefined deck: {}", name),
+            DbError::UnknownRegion(name) => write!(f, "Encountered undefined region: {}", name),
             DbError::InvalidOperation(name) => write!(f, "Invalid DB operation: {}", name),
             DbError::UniqueConstraintViolation(name) => write!(f, "Adding card failure: {}", name),
+            DbError::InUse(name) => write!(f, "Still in use: {}", name),
+            DbError::UnsupportedSchemaVersion(version) => write!(
+                f,
+                "Database schema version {} is newer than the supported version {}, please upgrade card-collection-manager",
+                version, SCHEMA_VERSION
+            ),
 
             DbError::SqliteError(e) => write!(f, "SQLite error: {}", e),
         }
@@ -0,0 +1,116 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::card::sql_as_str;
+
+/// Part of a deck an entry belongs to
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DeckSection {
+    Main,
+    Extra,
+    Side,
+}
+
+impl DeckSection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeckSection::Main => "main",
+            DeckSection::Extra => "extra",
+            DeckSection::Side => "side",
+        }
+    }
+}
+
+impl FromStr for DeckSection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "main" => Ok(DeckSection::Main),
+            "extra" => Ok(DeckSection::Extra),
+            "side" => Ok(DeckSection::Side),
+            _ => Err(format!(
+                "unknown deck section '{}', expected one of main|extra|side",
+                s
+            )),
+        }
+    }
+}
+
+sql_as_str!(DeckSection);
+
+/// `quantity` copies of a card name in one section of a deck
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DeckEntry {
+    pub card_name: String,
+    pub section: DeckSection,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Deck {
+    pub id: i32,
+    pub name: String,
+    pub entries: Vec<DeckEntry>,
+}
+
+/// Copies of a card name used by another deck
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DeckCommitment {
+    pub deck: String,
+    pub quantity: i32,
+}
+
+/// Ownership of a single deck entry
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DeckEntryCheck {
+    #[serde(flatten)]
+    pub entry: DeckEntry,
+    /// Owned copies of this name over all printings
+    pub owned: i32,
+    /// Card numbers of the owned printings
+    pub printings: Vec<String>,
+    /// Other decks using this card name
+    pub other_decks: Vec<DeckCommitment>,
+    /// Copies that are not in the collection at all
+    pub missing: i32,
+    /// Copies that are owned, but already used by other decks
+    pub committed: i32,
+}
+
+/// Result of checking a deck against the collection
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DeckCheck {
+    pub deck: String,
+    pub entries: Vec<DeckEntryCheck>,
+}
+
+impl DeckCheck {
+    /// True when every entry is owned and not used by another deck
+    pub fn is_complete(&self) -> bool {
+        self.entries
+            .iter()
+            .all(|e| e.missing == 0 && e.committed == 0)
+    }
+}
+
+/// Request to add copies of a card name to a deck, or to remove them
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DeckCardRequest {
+    pub card_name: String,
+    #[serde(default = "default_section")]
+    pub section: DeckSection,
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+}
+
+fn default_section() -> DeckSection {
+    DeckSection::Main
+}
+
+fn default_quantity() -> i32 {
+    1
+}
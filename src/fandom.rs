@@ -0,0 +1,294 @@
+// Reading of set pages saved from the Yu-Gi-Oh! fandom wiki, e.g.
+// https://yugioh.fandom.com/wiki/Legend_of_Blue_Eyes_White_Dragon
+//
+// The page has an infobox with the set name and a "Release dates" section, and a tab per
+// region ("English (na)", "French", ...) each holding a `table.card-list`. This does what
+// `cardlists/get_series.js` does in the browser, without the browser.
+
+use scraper::{ElementRef, Html, Node, Selector};
+
+use crate::identity::CardStats;
+use crate::jsoncards::{CardJson, SeriesJson};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetPage {
+    pub name: String,
+    pub release_dates: Vec<ReleaseDate>,
+    pub card_lists: Vec<CardList>,
+}
+
+/// A release date from the infobox, `region` being its label like "North American"
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReleaseDate {
+    pub region: String,
+    pub date: String,
+}
+
+/// The cards of one region tab, `current` for the tab that is open when the page loads
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardList {
+    pub region: String,
+    pub current: bool,
+    pub cards: Vec<CardJson>,
+}
+
+fn selector(css: &str) -> Selector {
+    Selector::parse(css).expect("valid selector")
+}
+
+/// Text of an element the way a browser renders it: runs of whitespace collapse to one
+/// space and `<br>` starts a new line
+fn inner_text(element: ElementRef) -> String {
+    let mut text = String::new();
+    for node in element.descendants() {
+        match node.value() {
+            Node::Text(t) => {
+                text.extend(t.chars().map(|c| if c.is_whitespace() { ' ' } else { c }))
+            }
+            Node::Element(e) if e.name() == "br" => text.push('\n'),
+            _ => {}
+        }
+    }
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Rarity as listed in the card table, short prints count as Common and
+/// only the first of several rarities is kept
+pub fn filter_rarity(text: &str) -> String {
+    if text.contains("Short Print") {
+        return "Common".to_string();
+    }
+    text.lines().next().unwrap_or("").trim().to_string()
+}
+
+// Capitalize each word: "effect monster" -> "Effect Monster"
+fn capitalize(text: &str) -> String {
+    text.split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Footnote markers like "[1]" after a date
+fn strip_footnotes(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        match rest[start..].find(']') {
+            Some(end) => {
+                out.push_str(&rest[..start]);
+                rest = &rest[start + end + 1..];
+            }
+            None => break,
+        }
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+// Column positions of number, name, rarity and category, found by the table header.
+// Tables without a header have them in this order.
+fn columns(table: ElementRef) -> [usize; 4] {
+    let headers: Vec<String> = table
+        .select(&selector("tr"))
+        .find(|row| row.select(&selector("th")).next().is_some())
+        .map(|row| {
+            row.select(&selector("th"))
+                .map(|th| inner_text(th).to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+    let find = |matches: fn(&str) -> bool| headers.iter().position(|h| matches(h));
+    [
+        find(|h| h.contains("number")).unwrap_or(0),
+        // the "English name" when there also is a "Japanese name"
+        find(|h| h == "name" || h.starts_with("english"))
+            .or(find(|h| h.contains("name")))
+            .unwrap_or(1),
+        find(|h| h.contains("rarity")).unwrap_or(2),
+        find(|h| h.contains("category") || h.contains("type")).unwrap_or(3),
+    ]
+}
+
+fn parse_table(table: ElementRef) -> Vec<CardJson> {
+    let [number, name, rarity, category] = columns(table);
+    let td = selector("td");
+    table
+        .select(&selector("tr"))
+        .filter_map(|row| {
+            let cells: Vec<String> = row.select(&td).map(inner_text).collect();
+            if cells.is_empty() {
+                return None; // header row
+            }
+            let cell = |i: usize| cells.get(i).map(String::as_str).unwrap_or("");
+            Some(CardJson {
+                card_number: cell(number).trim().to_string(),
+                name: cell(name).replace('"', "").trim().to_string(),
+                rarity: filter_rarity(cell(rarity)),
+                category: capitalize(cell(category).trim()),
+                passcode: None,
+                text: None,
+                stats: CardStats::default(),
+            })
+        })
+        .filter(|card| !card.card_number.is_empty())
+        .collect()
+}
+
+impl SetPage {
+    /// Parse a saved set page
+    pub fn parse(html: &str) -> Result<Self, String> {
+        let document = Html::parse_document(html);
+
+        let name = document
+            .select(&selector("aside.portable-infobox h2.pi-title, aside > h2"))
+            .next()
+            .map(inner_text)
+            .filter(|name| !name.is_empty())
+            .ok_or("Couldn't parse set name, is this a set page?")?;
+
+        let mut release_dates = Vec::new();
+        for section in document.select(&selector("aside section")) {
+            let is_release_dates = section
+                .select(&selector("h2"))
+                .next()
+                .is_some_and(|h| inner_text(h) == "Release dates");
+            if !is_release_dates {
+                continue;
+            }
+            for item in section.select(&selector(".pi-data")) {
+                let region = item
+                    .select(&selector(".pi-data-label"))
+                    .next()
+                    .map(inner_text)
+                    .unwrap_or_default();
+                let date = item
+                    .select(&selector(".pi-data-value"))
+                    .next()
+                    .map(|v| strip_footnotes(inner_text(v).lines().next().unwrap_or("")));
+                if let Some(date) = date.filter(|d| !d.is_empty()) {
+                    release_dates.push(ReleaseDate { region, date });
+                }
+            }
+        }
+
+        let mut card_lists = Vec::new();
+        let table = selector("table.card-list");
+        let tabbers: Vec<ElementRef> = document
+            .select(&selector(".wds-tabber"))
+            .filter(|tabber| tabber.select(&table).next().is_some())
+            .collect();
+        for tabber in &tabbers {
+            let labels: Vec<String> = tabber
+                .select(&selector(".wds-tabs__tab"))
+                .map(inner_text)
+                .collect();
+            for (i, content) in tabber.select(&selector(".wds-tab__content")).enumerate() {
+                if let Some(t) = content.select(&table).next() {
+                    card_lists.push(CardList {
+                        region: labels.get(i).cloned().unwrap_or_default(),
+                        current: content
+                            .value()
+                            .has_class("wds-is-current", scraper::CaseSensitivity::CaseSensitive),
+                        cards: parse_table(t),
+                    });
+                }
+            }
+        }
+        if tabbers.is_empty() {
+            // a set released in one region has its table without tabs
+            for t in document.select(&table) {
+                card_lists.push(CardList {
+                    region: String::new(),
+                    current: card_lists.is_empty(),
+                    cards: parse_table(t),
+                });
+            }
+        }
+        card_lists.retain(|list| !list.cards.is_empty());
+        if card_lists.is_empty() {
+            return Err("Couldn't find a card list on the page".to_string());
+        }
+
+        Ok(SetPage {
+            name,
+            release_dates,
+            card_lists,
+        })
+    }
+
+    /// Card list of a region tab (case-insensitive), the tab open on load when `region` is None
+    pub fn card_list(&self, region: Option<&str>) -> Result<&CardList, String> {
+        let list = match region {
+            Some(region) => self
+                .card_lists
+                .iter()
+                .find(|list| list.region.eq_ignore_ascii_case(region.trim())),
+            None => self
+                .card_lists
+                .iter()
+                .find(|list| list.current)
+                .or(self.card_lists.first()),
+        };
+        list.ok_or_else(|| {
+            let regions: Vec<&str> = self.card_lists.iter().map(|l| l.region.as_str()).collect();
+            format!(
+                "No card list for region '{}', the page has: {}",
+                region.unwrap_or_default(),
+                regions.join(", ")
+            )
+        })
+    }
+
+    /// Release date for a region tab: "English (na)" is released on the "North American"
+    /// date, "French" on the "French" one. Falls back to the first date of the infobox.
+    pub fn release_date(&self, region: &str) -> Option<&str> {
+        let region = region.to_lowercase();
+        let label = if region.contains("(na)") {
+            "north america".to_string()
+        } else if region.contains("(eu)") {
+            "europe".to_string()
+        } else if region.contains("(au)") || region.contains("(oc)") {
+            "oceani".to_string()
+        } else if region.contains("(ae)") {
+            "asian".to_string()
+        } else {
+            region.split(' ').next().unwrap_or("").to_string()
+        };
+        self.release_dates
+            .iter()
+            .find(|d| !label.is_empty() && d.region.to_lowercase().starts_with(&label))
+            .or(self.release_dates.first())
+            .map(|d| d.date.as_str())
+    }
+
+    /// The series json of one region, as `add json` reads it
+    pub fn to_series_json(&self, region: Option<&str>) -> Result<SeriesJson, String> {
+        let list = self.card_list(region)?;
+        let release_date = self
+            .release_date(&list.region)
+            .ok_or("Couldn't parse release date")?;
+        let prefix = list.cards[0]
+            .card_number
+            .split('-')
+            .next()
+            .map(str::to_string);
+        Ok(SeriesJson {
+            name: self.name.clone(),
+            ncards: list.cards.len() as i32,
+            release_date: release_date.to_string(),
+            cards: list.cards.clone(),
+            prefix,
+        })
+    }
+}
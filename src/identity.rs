@@ -0,0 +1,146 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::card::sql_as_str;
+
+/// Attribute of a monster card
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Attribute {
+    Dark,
+    Light,
+    Earth,
+    Water,
+    Fire,
+    Wind,
+    Divine,
+}
+
+impl Attribute {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Attribute::Dark => "DARK",
+            Attribute::Light => "LIGHT",
+            Attribute::Earth => "EARTH",
+            Attribute::Water => "WATER",
+            Attribute::Fire => "FIRE",
+            Attribute::Wind => "WIND",
+            Attribute::Divine => "DIVINE",
+        }
+    }
+}
+
+impl FromStr for Attribute {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "DARK" => Ok(Attribute::Dark),
+            "LIGHT" => Ok(Attribute::Light),
+            "EARTH" => Ok(Attribute::Earth),
+            "WATER" => Ok(Attribute::Water),
+            "FIRE" => Ok(Attribute::Fire),
+            "WIND" => Ok(Attribute::Wind),
+            "DIVINE" => Ok(Attribute::Divine),
+            _ => Err(format!(
+                "unknown attribute '{}', expected one of DARK|LIGHT|EARTH|WATER|FIRE|WIND|DIVINE",
+                s
+            )),
+        }
+    }
+}
+
+sql_as_str!(Attribute);
+
+/// Game stats of a monster, all missing for spells and traps.
+/// A monster has one of level (most monsters), rank (Xyz) or link rating (Link).
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, ToSchema)]
+pub struct CardStats {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribute: Option<Attribute>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_rating: Option<i32>,
+    /// Monster type, e.g. "Spellcaster" or "Winged Beast"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monster_type: Option<String>,
+    /// Missing for "?" ATK
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atk: Option<i32>,
+    /// Missing for "?" DEF and Link monsters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub def: Option<i32>,
+}
+
+impl CardStats {
+    pub fn is_empty(&self) -> bool {
+        *self == CardStats::default()
+    }
+
+    /// Level, rank or link rating with its name, e.g. "Rank 4"
+    pub fn stars(&self) -> Option<String> {
+        self.level
+            .map(|l| format!("Level {}", l))
+            .or(self.rank.map(|r| format!("Rank {}", r)))
+            .or(self.link_rating.map(|l| format!("Link {}", l)))
+    }
+
+    /// Error message when a stat is out of the range printed on cards
+    pub fn check(&self) -> Result<(), String> {
+        let in_range = |name: &str, value: Option<i32>, min: i32, max: i32| match value {
+            Some(v) if v < min || v > max => Err(format!(
+                "{} must be between {} and {}, found {}",
+                name, min, max, v
+            )),
+            _ => Ok(()),
+        };
+        in_range("level", self.level, 0, 13)?;
+        in_range("rank", self.rank, 0, 13)?;
+        in_range("link rating", self.link_rating, 1, 8)?;
+        in_range("atk", self.atk, 0, 100_000)?;
+        in_range("def", self.def, 0, 100_000)?;
+        let ratings = [self.level, self.rank, self.link_rating];
+        if ratings.iter().filter(|r| r.is_some()).count() > 1 {
+            return Err("a monster has only one of level, rank and link rating".into());
+        }
+        Ok(())
+    }
+}
+
+/// A card as a game piece, shared by all its printings: "Dark Magician" is one identity
+/// printed in many sets
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct CardIdentity {
+    pub id: i32,
+    pub name: String,
+    pub passcode: Option<i64>,
+    pub text: Option<String>,
+    #[serde(flatten)]
+    pub stats: CardStats,
+}
+
+/// One printing of a card: where it was printed, under which number and in which rarity
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct Printing {
+    pub number: String,
+    pub region: String,
+    pub series_id: i32,
+    pub series: String,
+    pub release_date: String,
+    pub rarity: String,
+    pub in_collection: i32,
+}
+
+/// An identity with its printings, oldest first, and the copies owned over all of them
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CardIdentityPrintings {
+    #[serde(flatten)]
+    pub identity: CardIdentity,
+    pub owned_copies: i32,
+    pub printings: Vec<Printing>,
+}
@@ -1,14 +1,24 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+use crate::identity::CardStats;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CardJson {
     pub card_number: String,
     pub name: String,
     pub rarity: String,
     pub category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passcode: Option<i64>,
+    /// Card text (lore), indexed for full-text search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Attribute, level, ATK/DEF and the other stats of monsters
+    #[serde(flatten)]
+    pub stats: CardStats,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SeriesJson {
     pub name: String,
     pub ncards: i32,
@@ -16,3 +26,28 @@ pub struct SeriesJson {
     pub cards: Vec<CardJson>,
     pub prefix: Option<String>,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasscodeJson {
+    #[serde(alias = "id")]
+    pub passcode: i64,
+    pub name: String,
+}
+
+/// Passcode to card name mapping, either a plain list of `{passcode, name}` objects
+/// or the `{"data": [{id, name, ...}]}` document served by the YGOPRODeck card database
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum PasscodesJson {
+    YgoProDeck { data: Vec<PasscodeJson> },
+    List(Vec<PasscodeJson>),
+}
+
+impl PasscodesJson {
+    pub fn into_entries(self) -> Vec<PasscodeJson> {
+        match self {
+            PasscodesJson::YgoProDeck { data } => data,
+            PasscodesJson::List(list) => list,
+        }
+    }
+}
@@ -1,19 +1,33 @@
+pub mod backup;
 pub mod card;
+pub mod cardnumber;
 pub mod cardtype;
 pub mod cli;
+pub mod collection_csv;
+pub mod config;
 pub mod copy;
 pub mod db;
 mod dberror; //custom db errors
+pub mod deck;
+pub mod fandom;
+pub mod identity;
 pub mod jsoncards;
+pub mod migrations;
+pub mod pool;
+pub mod query;
 pub mod rarity;
+pub mod search;
 pub mod series;
+pub mod stats;
+pub mod want;
+pub mod ydk;
 
 pub mod routes;
 
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
-use crate::db::DatabaseConnection;
+use crate::pool::DatabasePool;
 
 pub struct AppState {
-    pub db: Arc<Mutex<DatabaseConnection>>,
+    pub db: Arc<DatabasePool>,
 }
@@ -1,19 +1,27 @@
 use std::{
     error::Error,
-    io::{self, BufReader, Write},
+    io::{self, BufReader, IsTerminal, Write},
 };
 
 use card_collection_manager::{
-    card::{Card, DatabaseCard},
+    backup::Backup,
+    card::{Card, CardResult, CopyAttributes, DatabaseCard},
+    cardtype::CardTypeDefinitions,
     cli::{Args, Command},
+    collection_csv::{CsvImportOptions, read_csv, write_csv},
     copy::add_file_to_clipboard,
-    db::{get_series_and_number, setup},
+    db::{DatabaseConnection, setup},
+    deck::{DeckCardRequest, DeckCheck},
+    fandom::SetPage,
     jsoncards,
+    query::Query,
+    search::Highlight,
     series::Series,
+    want::{NewWant, Want},
+    ydk::{Ydk, YdkReport},
 };
 
 use clap::Parser;
-use open;
 
 fn prompt_user_series() -> Result<Series, Box<dyn Error>> {
     let mut name = String::new();
@@ -90,7 +98,7 @@ fn prompt_user_card() -> Result<DatabaseCard, Box<dyn Error>> {
         name,
         series_id,
         number,
-        collection_number: collection_number,
+        collection_number,
         in_collection: 0,
         rarity_id,
         card_type_id,
@@ -98,14 +106,75 @@ fn prompt_user_card() -> Result<DatabaseCard, Box<dyn Error>> {
 }
 
 fn format_card(card: &Card, formatter: &str) -> String {
+    // "?" for monsters with unknown ATK/DEF, empty for spells, traps and the DEF of Links
+    let stat = |value: Option<i32>, has_stat: bool| match value {
+        Some(v) => v.to_string(),
+        None if has_stat && card.stats.attribute.is_some() => "?".to_string(),
+        None => String::new(),
+    };
     formatter
         .replace("{name}", &card.name)
         .replace("{number}", &card.number)
+        .replace("{region}", &card.region)
         .replace("{collection_number}", &card.collection_number.to_string())
         .replace("{rarity}", &card.rarity.name)
         .replace("{series}", &card.series.name)
         .replace("{cardtype}", &card.cardtype.display())
         .replace("{in_collection}", &card.in_collection.to_string())
+        .replace(
+            "{attribute}",
+            &card
+                .stats
+                .attribute
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        )
+        .replace("{level}", &card.stats.stars().unwrap_or_default())
+        .replace(
+            "{monster_type}",
+            card.stats.monster_type.as_deref().unwrap_or(""),
+        )
+        .replace("{atk}", &stat(card.stats.atk, true))
+        .replace(
+            "{def}",
+            &stat(card.stats.def, card.stats.link_rating.is_none()),
+        )
+        .replace(
+            "{copies}",
+            &card
+                .copies
+                .iter()
+                .map(|c| c.display())
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+}
+
+// Ask to create the card types of `categories` that are not in the database,
+// refusing the import when the answer is no
+fn confirm_missing_card_types<'a>(
+    db: &DatabaseConnection,
+    categories: impl IntoIterator<Item = &'a str>,
+) -> Result<(), Box<dyn Error>> {
+    let missing = db.missing_card_types(categories)?;
+    if missing.is_empty() {
+        return Ok(());
+    }
+    println!("Card types not in the database:");
+    for cardtype in &missing {
+        println!("  {}", cardtype.display());
+    }
+    let mut answer = String::new();
+    print!("Create these {} card types? [y/N] ", missing.len());
+    io::stdout().flush()?;
+    io::stdin().read_line(&mut answer)?;
+    if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+        return Err("Import cancelled, the card types were not created".into());
+    }
+    for cardtype in &missing {
+        db.create_card_type(cardtype)?;
+    }
+    Ok(())
 }
 
 fn print_cards(cards: Vec<Card>, hide_collected: bool, formatter: &str) {
@@ -121,11 +190,91 @@ fn print_cards(cards: Vec<Card>, hide_collected: bool, formatter: &str) {
             if hide_collected && card.in_collection > 0 {
                 continue;
             }
-            println!("{}", format_card(&card, &formatter));
+            println!("{}", format_card(&card, formatter));
+        }
+    }
+}
+
+fn print_deck_check(check: &DeckCheck) {
+    println!("Deck '{}':", check.deck);
+    let mut section = None;
+    for e in &check.entries {
+        if section != Some(&e.entry.section) {
+            section = Some(&e.entry.section);
+            println!("{} deck:", e.entry.section);
+        }
+        let mut status = Vec::new();
+        if e.missing > 0 {
+            status.push(format!("missing {}", e.missing));
+        }
+        if e.committed > 0 {
+            let decks: Vec<_> = e
+                .other_decks
+                .iter()
+                .map(|d| format!("{} x{}", d.deck, d.quantity))
+                .collect();
+            status.push(format!("{} committed to {}", e.committed, decks.join(", ")));
+        }
+        if status.is_empty() {
+            status.push(format!("ok ({})", e.printings.join(", ")));
         }
+        println!(
+            "  {} x {} | owned {} | {}",
+            e.entry.quantity,
+            e.entry.card_name,
+            e.owned,
+            status.join("; ")
+        );
+    }
+    if check.is_complete() {
+        println!("All cards are available.");
     }
 }
 
+fn print_unresolved(report: &YdkReport, what: &str) {
+    if report.unresolved.is_empty() {
+        return;
+    }
+    println!("Skipped {} unresolved {}:", report.unresolved.len(), what);
+    for (section, value) in &report.unresolved {
+        println!("  {} | {}", section, value);
+    }
+}
+
+fn print_card_results(results: &[CardResult], action: &str) {
+    for result in results {
+        match (&result.in_collection, &result.error) {
+            (Some(count), _) => println!(
+                "Card {} {} {} copies in collection.",
+                result.number, action, count
+            ),
+            (None, Some(error)) => println!("Card {} failed: {}", result.number, error),
+            (None, None) => {}
+        }
+    }
+    let failed = results.iter().filter(|r| !r.is_ok()).count();
+    if failed > 0 {
+        println!("{} of {} cards failed.", failed, results.len());
+    }
+}
+
+fn print_want(want: &Want) {
+    let max_price = want
+        .max_price
+        .map(|p| format!("{:.2}", p))
+        .unwrap_or_else(|| "-".into());
+    println!(
+        "|{}|{}|{}| want {} (have {}) | max price {} | priority {}",
+        want.series,
+        want.number,
+        want.name,
+        want.quantity,
+        want.in_collection,
+        max_price,
+        want.priority
+    );
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
 
@@ -140,6 +289,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             kind,
             name,
             filename,
+            create_types,
         } => {
             match kind.as_str() {
                 "series" => {
@@ -160,34 +310,14 @@ fn main() -> Result<(), Box<dyn Error>> {
                     let reader = BufReader::new(file);
 
                     let series_json: jsoncards::SeriesJson = serde_json::from_reader(reader)?;
-
-                    // Insert series
-                    let series = Series {
-                        id: None,
-                        name: series_json.name.clone(),
-                        release_date: series_json.release_date,
-                        n_cards: series_json.ncards,
-                        prefix: Some(series_json.prefix.unwrap_or(String::from(""))),
-                    };
-
-                    let series_id = db.insert_series(&series)?;
-                    let mut cnt = 0;
-                    for c in series_json.cards {
-                        let (_, collection_number) = get_series_and_number(&c.card_number);
-                        let card = DatabaseCard {
-                            name: c.name.clone(),
-                            number: c.card_number,
-                            collection_number: collection_number,
-                            rarity_id: db.get_rarity_id(&c.rarity)?, // directly i32
-                            series_id: series_id,
-                            in_collection: 0,
-                            card_type_id: db.get_card_type_id(&c.category)?,
-                        };
-                        let inserted_id = db.insert_card(&card)?;
-                        if inserted_id != 0 {
-                            cnt += 1;
-                        }
+                    if create_types {
+                        confirm_missing_card_types(
+                            &db,
+                            series_json.cards.iter().map(|c| c.category.as_str()),
+                        )?;
                     }
+
+                    let (_, cnt) = db.add_series_json(series_json)?;
                     println!("Inserted {} cards", cnt);
                 }
                 "rarity" => {
@@ -197,11 +327,10 @@ fn main() -> Result<(), Box<dyn Error>> {
                 }
                 "card-type" => {
                     let n = name.expect("--name for card-type is required");
-                    let mut parts = n.splitn(2, ' '); // split into at most 2 parts
-                    let subtype = parts.next().unwrap_or(""); //first part is subtype e.g. EFFECT
-                    let maintype = parts.next().expect("main type cannot be empty.");
-                    db.insert_card_type(maintype, subtype)?;
-                    println!("Inserted card type '{}'", n);
+                    // e.g. "Synchro Tuner Effect Monster", see cardtypes.toml
+                    let cardtype = CardTypeDefinitions::bundled().parse_card_type(&n)?;
+                    db.create_card_type(&cardtype)?;
+                    println!("Inserted card type '{}'", cardtype.display());
                 }
                 _ => {
                     println!("Unknown kind: {}", kind);
@@ -229,54 +358,496 @@ fn main() -> Result<(), Box<dyn Error>> {
                 "series" => {
                     // list current unique series in db
                     let series_list = db.get_unique_series()?;
-                    let mut cnt = 1;
-                    if series_list.len() == 0 {
+                    if series_list.is_empty() {
                         println!("No series in current database");
                     }
-                    for s in series_list {
+                    for (cnt, s) in series_list.iter().enumerate() {
                         println!(
                             "{}. {} | {} | {} cards",
-                            cnt, s.name, s.release_date, s.n_cards
+                            cnt + 1,
+                            s.name,
+                            s.release_date,
+                            s.n_cards
+                        );
+                    }
+                }
+                "regions" => {
+                    let series_name = name.expect("--name is required for list regions");
+                    let series_id = db.get_series_id_by_name(&series_name)?;
+                    for r in db.get_series_regions(series_id)? {
+                        println!(
+                            "{} | {} | {} | {}/{} cards | {} owned | {}",
+                            if r.region.region.is_empty() {
+                                "-"
+                            } else {
+                                &r.region.region
+                            },
+                            r.region.language,
+                            r.region.release_date.as_deref().unwrap_or("unknown"),
+                            r.cards,
+                            r.region.n_cards,
+                            r.owned_cards,
+                            r.rarities.join(", ")
                         );
-                        cnt += 1;
                     }
                 }
+                "identities" => {
+                    // every card once, with the printings of it
+                    let owned = hide_collected.then_some(false);
+                    let identities = db.get_identities(name.as_deref(), owned)?;
+                    if identities.is_empty() {
+                        println!("No results.");
+                    }
+                    for identity in identities {
+                        println!(
+                            "{} | {} printings | {} owned",
+                            identity.identity.name,
+                            identity.printings.len(),
+                            identity.owned_copies
+                        );
+                        for p in identity.printings {
+                            println!(
+                                "    {} | {} | {} | {}",
+                                p.number, p.series, p.rarity, p.in_collection
+                            );
+                        }
+                    }
+                }
+                "slot" => {
+                    let number = name.expect("--name is required for list slot");
+                    let cards = db.get_slot_cards(&number)?;
+                    print_cards(cards, hide_collected, &formatter);
+                }
                 "rarities" => {
-                    println!("Not yet implemented...");
+                    for r in db.get_rarities()? {
+                        println!(
+                            "{} | {} cards | {} copies owned",
+                            r.rarity.name, r.cards, r.owned_copies
+                        );
+                    }
                 }
                 "card-types" => {
-                    println!("Not yet implemented...");
+                    for t in db.get_card_types()? {
+                        println!(
+                            "{} | {} cards | {} copies owned",
+                            t.display, t.cards, t.owned_copies
+                        );
+                    }
                 }
                 _ => {
                     println!("Unknown kind: {}", kind);
                 }
             }
         }
-        Command::Collect { id, count } => {
+        Command::Collect {
+            id,
+            count,
+            condition,
+            edition,
+            language,
+            notes,
+            price,
+            date,
+            mode,
+        } => {
             //for collecting card id's (e.g. PSV-EN001)
+            let copy = CopyAttributes {
+                condition,
+                edition,
+                language,
+                notes,
+                price,
+                date,
+            };
 
-            for card_id in id {
-                let new_count = db.collect_card(&card_id, count)?;
-                println!(
-                    "Card {} now has {} copies in collection.",
-                    card_id, new_count
-                );
-            }
+            let results = db.collect_cards(&id, count, &copy, mode)?;
+            print_card_results(&results, "now has");
         }
-        Command::Sell { id, count } => {
+        Command::Sell {
+            id,
+            count,
+            condition,
+            edition,
+            language,
+            price,
+            date,
+            mode,
+        } => {
             //for collecting card id's (e.g. PSV-EN001)
-            if id.len() == 0 {
+            let copy = CopyAttributes {
+                condition,
+                edition,
+                language,
+                notes: None,
+                price,
+                date,
+            };
+            if id.is_empty() {
                 eprintln!("--id is required for a sell action"); // print to stderr
                 std::process::exit(1); // exit with error code
             }
 
-            for card_id in id {
-                let new_count = db.sell_card(&card_id, count)?;
+            let results = db.sell_cards(&id, count, &copy, mode)?;
+            print_card_results(&results, "removed, now has");
+        }
+        Command::Price {
+            id,
+            price,
+            condition,
+            date,
+        } => match price {
+            Some(p) => {
+                db.insert_price(&id, &condition, p, date.as_deref())?;
+                println!("Recorded price {:.2} for {} ({})", p, id, condition);
+            }
+            None => {
+                let prices = db.get_prices(&id)?;
+                if prices.is_empty() {
+                    println!("No prices recorded for {}", id);
+                }
+                for p in prices {
+                    println!(
+                        "{} | {} | {} | {} | {:.2}",
+                        p.date, p.number, p.rarity, p.condition, p.price
+                    );
+                }
+            }
+        },
+        Command::Value {} => {
+            let value = db.get_collection_value()?;
+            if value.series.is_empty() {
+                println!("No copies in collection");
+            }
+            println!("series | owned | sold | market value | cost basis | unrealized | realized");
+            for s in value.series.iter().chain(std::iter::once(&value.total)) {
+                println!(
+                    "{} | {} | {} | {:.2} | {:.2} | {:.2} | {:.2}",
+                    s.series,
+                    s.owned_copies,
+                    s.sold_copies,
+                    s.market_value,
+                    s.cost_basis,
+                    s.unrealized_profit,
+                    s.realized_profit
+                );
+            }
+            if value.total.unpriced_copies > 0 {
+                println!(
+                    "{} copies have no market price and are valued at 0.",
+                    value.total.unpriced_copies
+                );
+            }
+        }
+        Command::Stats { rarities } => {
+            let stats = db.get_completion_stats()?;
+            if stats.series.is_empty() {
+                println!("No series in database");
+            }
+            println!("series | defined/cards | owned | completion | copies | duplicates");
+            for s in stats.series.iter().chain(std::iter::once(&stats.total)) {
+                println!(
+                    "{} | {}/{} | {} | {:.1}% | {} | {}",
+                    s.series,
+                    s.cards_defined,
+                    s.n_cards,
+                    s.owned_cards,
+                    s.completion,
+                    s.total_copies,
+                    s.duplicates
+                );
+                if rarities {
+                    for r in &s.rarities {
+                        println!(
+                            "    {} | {}/{} | {:.1}%",
+                            r.rarity, r.owned_cards, r.cards, r.completion
+                        );
+                    }
+                }
+            }
+            println!(
+                "{} of {} series complete",
+                stats.completed_series,
+                stats.series.len()
+            );
+        }
+        Command::Want {
+            action,
+            id,
+            quantity,
+            max_price,
+            priority,
+            series,
+        } => match action.as_str() {
+            "add" => {
+                let number = id.expect("--id is required for 'want add'");
+                let want = db.upsert_want(&NewWant {
+                    number,
+                    quantity,
+                    max_price,
+                    priority,
+                })?;
+                println!(
+                    "Want {} x {} ({} in collection)",
+                    want.quantity, want.number, want.in_collection
+                );
+            }
+            "remove" => {
+                let number = id.expect("--id is required for 'want remove'");
+                db.delete_want(&number)?;
+                println!("Removed {} from want list", number);
+            }
+            "list" => {
+                let wants = db.get_wants()?;
+                if wants.is_empty() {
+                    println!("Want list is empty");
+                }
+                for w in wants {
+                    print_want(&w);
+                }
+            }
+            "missing" => {
+                let wants = db.get_missing_wants(series.as_deref())?;
+                if wants.is_empty() {
+                    println!("Nothing missing.");
+                }
+                let mut current_series = None;
+                for w in wants {
+                    if current_series.as_ref() != Some(&w.series) {
+                        println!("{}:", w.series);
+                        current_series = Some(w.series.clone());
+                    }
+                    println!(
+                        "  |{}|{}| missing {} of {}",
+                        w.number,
+                        w.name,
+                        w.missing(),
+                        w.quantity
+                    );
+                }
+            }
+            _ => {
+                println!("Unknown want action: {}", action);
+            }
+        },
+        Command::Deck {
+            action,
+            name,
+            card,
+            section,
+            quantity,
+        } => {
+            if action == "list" {
+                for deck in db.get_decks()? {
+                    let total: i32 = deck.entries.iter().map(|e| e.quantity).sum();
+                    println!("{} | {} cards", deck.name, total);
+                }
+                return Ok(());
+            }
+
+            let deck = name.expect("a deck name is required, e.g. 'deck show \"My deck\"'");
+            match action.as_str() {
+                "create" => {
+                    db.create_deck(&deck)?;
+                    println!("Created deck '{}'", deck);
+                }
+                "delete" => {
+                    db.delete_deck(&deck)?;
+                    println!("Deleted deck '{}'", deck);
+                }
+                "add" | "remove" => {
+                    let request = DeckCardRequest {
+                        card_name: card.expect("--card is required for 'deck add|remove'"),
+                        section,
+                        quantity,
+                    };
+                    if action == "add" {
+                        db.add_deck_card(&deck, &request)?;
+                        println!(
+                            "Added {} x {} to the {} deck",
+                            request.quantity, request.card_name, request.section
+                        );
+                    } else {
+                        db.remove_deck_card(&deck, &request)?;
+                        println!(
+                            "Removed {} x {} from the {} deck",
+                            request.quantity, request.card_name, request.section
+                        );
+                    }
+                }
+                "show" => {
+                    let check = db.check_deck(&deck)?;
+                    print_deck_check(&check);
+                }
+                _ => {
+                    println!("Unknown deck action: {}", action);
+                }
+            }
+        }
+        Command::Import {
+            kind,
+            file,
+            name,
+            dry_run,
+            upsert,
+            region,
+            output,
+            create_types,
+        } => match kind.as_str() {
+            "csv" => {
+                let rows = read_csv(std::fs::File::open(&file)?)?;
+                if create_types && !dry_run {
+                    confirm_missing_card_types(
+                        &db,
+                        rows.iter()
+                            .filter_map(|(_, row)| row.as_ref().ok())
+                            .map(|row| row.card_type.as_str()),
+                    )?;
+                }
+                let report = db.import_csv_rows(
+                    rows,
+                    &CsvImportOptions {
+                        dry_run,
+                        upsert,
+                        create_types: false,
+                    },
+                )?;
+                if report.dry_run {
+                    println!("Dry run, no changes were made.");
+                }
                 println!(
-                    "Card removed. Card {} now has {} copies in collection.",
-                    card_id, new_count
+                    "Inserted {} cards, updated {}, unchanged {}, {} errors",
+                    report.inserted,
+                    report.updated,
+                    report.unchanged,
+                    report.errors.len()
                 );
+                for e in report.errors {
+                    println!(
+                        "  line {} | {} | {}",
+                        e.line,
+                        e.number.unwrap_or_default(),
+                        e.message
+                    );
+                }
             }
+            "ydk" => {
+                let text = std::fs::read_to_string(&file)?;
+                let ydk = Ydk::parse(&text)?;
+                let deck = name.unwrap_or_else(|| {
+                    std::path::Path::new(&file)
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().into_owned())
+                        .unwrap_or(file.clone())
+                });
+                let report = db.import_ydk(&deck, &ydk)?;
+                println!("Imported {} cards into deck '{}'", report.resolved, deck);
+                print_unresolved(&report, "passcodes");
+            }
+            "html" => {
+                let page = SetPage::parse(&std::fs::read_to_string(&file)?)?;
+                if region.as_deref() == Some("all") {
+                    // every tab is a region of the same series
+                    if output.is_some() {
+                        return Err("--output takes a single region, not --region all".into());
+                    }
+                    if create_types {
+                        confirm_missing_card_types(
+                            &db,
+                            page.card_lists
+                                .iter()
+                                .flat_map(|list| &list.cards)
+                                .map(|c| c.category.as_str()),
+                        )?;
+                    }
+                    for list in &page.card_lists {
+                        let series_json = page.to_series_json(Some(&list.region))?;
+                        let (_, cnt) = db.add_series_json(series_json)?;
+                        println!(
+                            "Inserted {} cards of '{}' ({})",
+                            cnt, page.name, list.region
+                        );
+                    }
+                } else {
+                    let series_json = page.to_series_json(region.as_deref())?;
+                    match output {
+                        Some(output) => {
+                            std::fs::write(&output, serde_json::to_string_pretty(&series_json)?)?;
+                            println!(
+                                "Wrote '{}' with {} cards to {}",
+                                series_json.name, series_json.ncards, output
+                            );
+                        }
+                        None => {
+                            if create_types {
+                                confirm_missing_card_types(
+                                    &db,
+                                    series_json.cards.iter().map(|c| c.category.as_str()),
+                                )?;
+                            }
+                            let name = series_json.name.clone();
+                            let (_, cnt) = db.add_series_json(series_json)?;
+                            println!("Inserted series '{}' with {} cards", name, cnt);
+                        }
+                    }
+                }
+            }
+            "passcodes" => {
+                let reader = BufReader::new(std::fs::File::open(&file)?);
+                let passcodes: jsoncards::PasscodesJson = serde_json::from_reader(reader)?;
+                let n = db.load_passcodes(&passcodes.into_entries())?;
+                println!("Loaded {} passcodes", n);
+            }
+            _ => {
+                println!("Unknown kind: {}", kind);
+            }
+        },
+        Command::Export { kind, file, name } => match kind.as_str() {
+            "csv" => {
+                let cards = db.get_cards(None)?;
+                write_csv(std::fs::File::create(&file)?, &cards)?;
+                println!("Exported {} cards to {}", cards.len(), file);
+            }
+            "ydk" => {
+                let (ydk, report) = match &name {
+                    Some(deck) => db.export_ydk(deck)?,
+                    None => db.export_collection_ydk()?,
+                };
+                std::fs::write(&file, ydk.to_ydk_string())?;
+                println!("Exported {} cards to {}", report.resolved, file);
+                print_unresolved(&report, "card names without a passcode");
+            }
+            _ => {
+                println!("Unknown kind: {}", kind);
+            }
+        },
+        Command::Backup { file } => {
+            let backup = db.create_backup()?;
+            std::fs::write(&file, serde_json::to_string_pretty(&backup)?)?;
+            println!(
+                "Backed up {} series and {} cards to {}",
+                backup.series.len(),
+                backup.cards.len(),
+                file
+            );
+        }
+        Command::Restore { file, policy } => {
+            let backup: Backup = serde_json::from_str(&std::fs::read_to_string(&file)?)?;
+            let report = db.restore_backup(&backup, &policy)?;
+            println!(
+                "Restored {} series, {} regions, {} card identities, {} new cards ({} skipped, {} overwritten, {} merged) and {} copies",
+                report.series,
+                report.regions,
+                report.identities,
+                report.cards_inserted,
+                report.cards_skipped,
+                report.cards_overwritten,
+                report.cards_merged,
+                report.copies
+            );
+            println!(
+                "Restored {} prices, {} wants, {} decks and {} passcodes",
+                report.prices, report.wants, report.decks, report.passcodes
+            );
         }
         Command::Find {
             kind,
@@ -287,8 +858,41 @@ fn main() -> Result<(), Box<dyn Error>> {
             match kind.as_str() {
                 "cards" => {
                     let q = query.expect("A query is required for 'find cards query'");
-                    let cards = db.get_cards(Some(q.as_str()))?;
-                    print_cards(cards, hide_collected, &formatter);
+                    let parsed = match Query::parse(&q) {
+                        Ok(parsed) => parsed,
+                        Err(e) => {
+                            eprintln!("{}\n{}^", q, " ".repeat(e.position));
+                            eprintln!("Invalid query {}", e);
+                            std::process::exit(1);
+                        }
+                    };
+                    if parsed.is_plain_text() {
+                        // plain words: ranked full-text search, highlighted only on a terminal
+                        // so piped output holds no escape codes
+                        let highlight = if io::stdout().is_terminal() {
+                            Highlight::TERMINAL
+                        } else {
+                            Highlight::PLAIN
+                        };
+                        let hits: Vec<_> = db
+                            .search_cards(&q, None, highlight)?
+                            .into_iter()
+                            .filter(|hit| !(hide_collected && hit.card.in_collection > 0))
+                            .collect();
+                        if hits.is_empty() {
+                            println!("No results.");
+                        }
+                        for hit in hits {
+                            let formatter = formatter.replace("{highlight}", &hit.name_highlight);
+                            println!("{}", format_card(&hit.card, &formatter));
+                            if let Some(snippet) = hit.snippet {
+                                println!("    {}", snippet);
+                            }
+                        }
+                    } else {
+                        let formatter = formatter.replace("{highlight}", "{name}");
+                        print_cards(db.find_cards(&parsed)?, hide_collected, &formatter);
+                    }
                 }
                 "serie" | "series" => {
                     let q = query.expect("A query is required for 'find serie query'");
@@ -0,0 +1,401 @@
+// Schema migrations, applied in order by `DatabaseConnection::migrate`.
+//
+// The schema version of a database is stored in `PRAGMA user_version`. Entry `i` of
+// `MIGRATIONS` upgrades a database from version `i` to version `i + 1`, so the last
+// version a binary knows about is `MIGRATIONS.len()`.
+//
+// Never edit a migration that has been released: append a new one instead.
+//
+// Foreign keys are enforced on every connection (see `DatabaseConnection::new`), so deletes
+// can leave the rows that reference a deleted row to `ON DELETE CASCADE`.
+
+/// Version 1: the original schema. Databases created before migrations existed
+/// already have these tables at `user_version` 0, hence the `IF NOT EXISTS`.
+const V1_INITIAL_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS rarity (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS card_type (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    maintype TEXT NOT NULL,
+    subtype TEXT,
+    UNIQUE (maintype, subtype) -- unique constraint for the two columns together
+);
+
+CREATE TABLE IF NOT EXISTS series (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    release_date DATE NOT NULL,
+    prefix TEXT,
+    n_cards INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS cards (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    series_id INTEGER NOT NULL,
+    collection_number INTEGER NOT NULL,
+    number TEXT NOT NULL UNIQUE,
+    in_collection INTEGER NOT NULL DEFAULT 0,
+    rarity_id INTEGER NOT NULL,
+    card_type_id INTEGER NOT NULL,
+    FOREIGN KEY (rarity_id) REFERENCES rarity(id)
+    FOREIGN KEY (series_id) REFERENCES series(id)
+    FOREIGN KEY (card_type_id) REFERENCES card_type(id)
+);
+";
+
+/// Version 2: individual physical copies. `cards.in_collection` is kept as a
+/// cached count of the copies, maintained by triggers.
+const V2_COPIES: &str = "
+CREATE TABLE copies (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    card_id INTEGER NOT NULL,
+    condition TEXT NOT NULL DEFAULT 'NM',
+    edition TEXT NOT NULL DEFAULT 'Unlimited',
+    language TEXT NOT NULL DEFAULT 'EN',
+    notes TEXT,
+    FOREIGN KEY (card_id) REFERENCES cards(id) ON DELETE CASCADE
+);
+
+CREATE INDEX copies_card_id ON copies (card_id);
+
+-- every previously counted copy becomes a default copy
+WITH RECURSIVE n(i) AS (
+    SELECT 1
+    UNION ALL
+    SELECT i + 1 FROM n WHERE i < (SELECT MAX(in_collection) FROM cards)
+)
+INSERT INTO copies (card_id)
+SELECT c.id FROM cards c JOIN n ON n.i <= c.in_collection
+ORDER BY c.id;
+
+CREATE TRIGGER copies_after_insert AFTER INSERT ON copies
+BEGIN
+    UPDATE cards SET in_collection = (SELECT COUNT(*) FROM copies WHERE card_id = NEW.card_id)
+    WHERE id = NEW.card_id;
+END;
+
+CREATE TRIGGER copies_after_delete AFTER DELETE ON copies
+BEGIN
+    UPDATE cards SET in_collection = (SELECT COUNT(*) FROM copies WHERE card_id = OLD.card_id)
+    WHERE id = OLD.card_id;
+END;
+
+CREATE TRIGGER copies_after_update AFTER UPDATE OF card_id ON copies
+BEGIN
+    UPDATE cards SET in_collection = (SELECT COUNT(*) FROM copies WHERE card_id = cards.id)
+    WHERE id IN (OLD.card_id, NEW.card_id);
+END;
+";
+
+/// Version 3: purchase and sale prices of copies plus market price history.
+/// Sold copies are kept for profit reporting, so they no longer count as owned.
+const V3_PRICES: &str = "
+ALTER TABLE copies ADD COLUMN purchase_price REAL;
+ALTER TABLE copies ADD COLUMN purchase_date DATE;
+ALTER TABLE copies ADD COLUMN sale_price REAL;
+ALTER TABLE copies ADD COLUMN sale_date DATE;
+
+CREATE TABLE price (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    number TEXT NOT NULL,
+    rarity_id INTEGER NOT NULL,
+    condition TEXT NOT NULL DEFAULT 'NM',
+    price REAL NOT NULL,
+    recorded_at DATE NOT NULL,
+    FOREIGN KEY (rarity_id) REFERENCES rarity(id)
+);
+
+CREATE INDEX price_lookup ON price (number, rarity_id, condition, recorded_at);
+
+DROP TRIGGER copies_after_insert;
+DROP TRIGGER copies_after_delete;
+DROP TRIGGER copies_after_update;
+
+CREATE TRIGGER copies_after_insert AFTER INSERT ON copies
+BEGIN
+    UPDATE cards SET in_collection =
+        (SELECT COUNT(*) FROM copies WHERE card_id = NEW.card_id AND sale_date IS NULL)
+    WHERE id = NEW.card_id;
+END;
+
+CREATE TRIGGER copies_after_delete AFTER DELETE ON copies
+BEGIN
+    UPDATE cards SET in_collection =
+        (SELECT COUNT(*) FROM copies WHERE card_id = OLD.card_id AND sale_date IS NULL)
+    WHERE id = OLD.card_id;
+END;
+
+CREATE TRIGGER copies_after_update AFTER UPDATE OF card_id, sale_date ON copies
+BEGIN
+    UPDATE cards SET in_collection =
+        (SELECT COUNT(*) FROM copies WHERE card_id = cards.id AND sale_date IS NULL)
+    WHERE id IN (OLD.card_id, NEW.card_id);
+END;
+";
+
+/// Version 4: want list, one entry per card number
+const V4_WANTS: &str = "
+CREATE TABLE wants (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    card_id INTEGER NOT NULL UNIQUE,
+    quantity INTEGER NOT NULL DEFAULT 1,
+    max_price REAL,
+    priority INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (card_id) REFERENCES cards(id) ON DELETE CASCADE
+);
+";
+
+/// Version 5: decks, whose entries reference card names rather than printings
+const V5_DECKS: &str = "
+CREATE TABLE decks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE deck_cards (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    deck_id INTEGER NOT NULL,
+    card_name TEXT NOT NULL,
+    section TEXT NOT NULL CHECK (section IN ('main', 'extra', 'side')),
+    quantity INTEGER NOT NULL DEFAULT 1,
+    UNIQUE (deck_id, card_name, section),
+    FOREIGN KEY (deck_id) REFERENCES decks(id) ON DELETE CASCADE
+);
+";
+
+/// Version 6: YGOPro passcodes, on cards and as a standalone passcode -> name mapping
+const V6_PASSCODES: &str = "
+ALTER TABLE cards ADD COLUMN passcode INTEGER;
+CREATE INDEX cards_passcode ON cards (passcode);
+
+CREATE TABLE passcodes (
+    passcode INTEGER PRIMARY KEY,
+    name TEXT NOT NULL
+);
+CREATE INDEX passcodes_name ON passcodes (name COLLATE NOCASE);
+";
+
+/// Version 7: card text and a full-text index over cards, kept in sync by triggers.
+/// The index stores the series name so cards can be found by the set they belong to.
+const V7_SEARCH: &str = "
+ALTER TABLE cards ADD COLUMN text TEXT;
+
+CREATE VIRTUAL TABLE cards_fts USING fts5 (
+    name, number, series, text,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO cards_fts (rowid, name, number, series, text)
+SELECT c.id, c.name, c.number, s.name, c.text
+FROM cards c LEFT JOIN series s ON s.id = c.series_id;
+
+CREATE TRIGGER cards_fts_after_insert AFTER INSERT ON cards
+BEGIN
+    INSERT INTO cards_fts (rowid, name, number, series, text)
+    VALUES (NEW.id, NEW.name, NEW.number,
+        (SELECT name FROM series WHERE id = NEW.series_id), NEW.text);
+END;
+
+CREATE TRIGGER cards_fts_after_delete AFTER DELETE ON cards
+BEGIN
+    DELETE FROM cards_fts WHERE rowid = OLD.id;
+END;
+
+CREATE TRIGGER cards_fts_after_update AFTER UPDATE OF name, number, series_id, text ON cards
+BEGIN
+    UPDATE cards_fts SET
+        name = NEW.name,
+        number = NEW.number,
+        series = (SELECT name FROM series WHERE id = NEW.series_id),
+        text = NEW.text
+    WHERE rowid = NEW.id;
+END;
+
+CREATE TRIGGER series_fts_after_update AFTER UPDATE OF name ON series
+BEGIN
+    UPDATE cards_fts SET series = NEW.name
+    WHERE rowid IN (SELECT id FROM cards WHERE series_id = NEW.id);
+END;
+";
+
+/// Version 8: regional printings of a series. The region code of a card is taken from its
+/// number (`LOB-EN001` -> `EN`, `LOB-G001` -> `G`, `LOB-001` -> empty) and every region of a
+/// series gets its own release date and size. Cards with the same series and collection number
+/// are the same slot of the set in different regions, collected separately.
+const V8_REGIONS: &str = "
+ALTER TABLE cards ADD COLUMN region TEXT GENERATED ALWAYS AS (
+    CASE WHEN instr(number, '-') > 0
+    THEN upper(rtrim(rtrim(substr(number, instr(number, '-') + 1),
+        'abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ'), '0123456789'))
+    ELSE '' END
+) VIRTUAL;
+CREATE INDEX cards_slot ON cards (series_id, collection_number);
+
+-- language of the cards printed with a region code, codes missing here are their own language
+CREATE TABLE region_languages (
+    region TEXT PRIMARY KEY,
+    language TEXT NOT NULL
+);
+INSERT INTO region_languages (region, language) VALUES
+    ('', 'EN'), ('EN', 'EN'), ('E', 'EN'), ('AE', 'EN'),
+    ('DE', 'DE'), ('G', 'DE'), ('FR', 'FR'), ('F', 'FR'), ('IT', 'IT'), ('I', 'IT'),
+    ('SP', 'SP'), ('S', 'SP'), ('PT', 'PT'), ('P', 'PT'),
+    ('JP', 'JP'), ('JA', 'JP'), ('KR', 'KR'), ('K', 'KR');
+
+CREATE TABLE series_regions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    series_id INTEGER NOT NULL,
+    region TEXT NOT NULL,
+    language TEXT NOT NULL,
+    release_date DATE,
+    n_cards INTEGER NOT NULL DEFAULT 0,
+    UNIQUE (series_id, region),
+    FOREIGN KEY (series_id) REFERENCES series(id) ON DELETE CASCADE
+);
+
+-- every region a series has cards in, dated and sized like the series
+INSERT INTO series_regions (series_id, region, language, release_date, n_cards)
+SELECT DISTINCT c.series_id, c.region,
+    COALESCE((SELECT language FROM region_languages WHERE region = c.region), c.region),
+    s.release_date, s.n_cards
+FROM cards c JOIN series s ON s.id = c.series_id
+ORDER BY c.series_id, c.region;
+
+CREATE TRIGGER series_regions_after_card_insert AFTER INSERT ON cards
+BEGIN
+    INSERT OR IGNORE INTO series_regions (series_id, region, language, release_date, n_cards)
+    SELECT NEW.series_id, NEW.region,
+        COALESCE((SELECT language FROM region_languages WHERE region = NEW.region), NEW.region),
+        s.release_date, s.n_cards
+    FROM series s WHERE s.id = NEW.series_id;
+END;
+
+CREATE TRIGGER series_regions_after_card_update AFTER UPDATE OF number, series_id ON cards
+BEGIN
+    INSERT OR IGNORE INTO series_regions (series_id, region, language, release_date, n_cards)
+    SELECT NEW.series_id, NEW.region,
+        COALESCE((SELECT language FROM region_languages WHERE region = NEW.region), NEW.region),
+        s.release_date, s.n_cards
+    FROM series s WHERE s.id = NEW.series_id;
+END;
+";
+
+/// Version 9: card identities. A row of `cards` is a printing of a card (series, number,
+/// rarity); its name, passcode, text and game stats move to `card_identities`, shared by all
+/// printings of the card. Existing printings are grouped by name, ignoring case.
+const V9_CARD_IDENTITIES: &str = "
+CREATE TABLE card_identities (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    passcode INTEGER,
+    text TEXT,
+    attribute TEXT,
+    level INTEGER,
+    atk INTEGER,
+    def INTEGER
+);
+CREATE INDEX card_identities_passcode ON card_identities (passcode);
+
+-- the oldest spelling of a name wins, the passcode and text of any printing are kept
+INSERT OR IGNORE INTO card_identities (name) SELECT name FROM cards ORDER BY id;
+UPDATE card_identities SET
+    passcode = (SELECT MIN(c.passcode) FROM cards c
+                WHERE c.name = card_identities.name COLLATE NOCASE),
+    text = (SELECT c.text FROM cards c
+            WHERE c.name = card_identities.name COLLATE NOCASE AND c.text IS NOT NULL
+            ORDER BY c.id LIMIT 1);
+
+ALTER TABLE cards ADD COLUMN identity_id INTEGER REFERENCES card_identities(id);
+UPDATE cards SET identity_id =
+    (SELECT i.id FROM card_identities i WHERE i.name = cards.name COLLATE NOCASE);
+CREATE INDEX cards_identity_id ON cards (identity_id);
+
+DROP TRIGGER cards_fts_after_insert;
+DROP TRIGGER cards_fts_after_update;
+DROP INDEX cards_passcode;
+ALTER TABLE cards DROP COLUMN name;
+ALTER TABLE cards DROP COLUMN passcode;
+ALTER TABLE cards DROP COLUMN text;
+
+-- the search index takes name and text from the identity
+UPDATE cards_fts SET
+    name = (SELECT i.name FROM cards c JOIN card_identities i ON i.id = c.identity_id
+            WHERE c.id = cards_fts.rowid),
+    text = (SELECT i.text FROM cards c JOIN card_identities i ON i.id = c.identity_id
+            WHERE c.id = cards_fts.rowid);
+
+CREATE TRIGGER cards_fts_after_insert AFTER INSERT ON cards
+BEGIN
+    INSERT INTO cards_fts (rowid, name, number, series, text)
+    VALUES (NEW.id,
+        (SELECT name FROM card_identities WHERE id = NEW.identity_id),
+        NEW.number,
+        (SELECT name FROM series WHERE id = NEW.series_id),
+        (SELECT text FROM card_identities WHERE id = NEW.identity_id));
+END;
+
+CREATE TRIGGER cards_fts_after_update AFTER UPDATE OF identity_id, number, series_id ON cards
+BEGIN
+    UPDATE cards_fts SET
+        name = (SELECT name FROM card_identities WHERE id = NEW.identity_id),
+        number = NEW.number,
+        series = (SELECT name FROM series WHERE id = NEW.series_id),
+        text = (SELECT text FROM card_identities WHERE id = NEW.identity_id)
+    WHERE rowid = NEW.id;
+END;
+
+CREATE TRIGGER card_identities_fts_after_update AFTER UPDATE OF name, text ON card_identities
+BEGIN
+    UPDATE cards_fts SET name = NEW.name, text = NEW.text
+    WHERE rowid IN (SELECT id FROM cards WHERE identity_id = NEW.id);
+END;
+";
+
+/// Version 10: the rest of the monster stats, rank for Xyz monsters, link rating for Link
+/// monsters and the monster type.
+const V10_CARD_STATS: &str = "
+ALTER TABLE card_identities ADD COLUMN rank INTEGER;
+ALTER TABLE card_identities ADD COLUMN link_rating INTEGER;
+ALTER TABLE card_identities ADD COLUMN monster_type TEXT;
+";
+
+/// Version 11: flip and union monsters are effect monsters. Wiki categories call them
+/// "Flip Effect Monster" and "Union Effect Monster", stored as sub types "Flip Effect" and
+/// "Union Effect", so the sub types "Flip" and "Union" seeded before are renamed. Where both
+/// exist already, the cards move to the effect type and the old one is dropped.
+const V11_EFFECT_SUBTYPES: &str = "
+UPDATE card_type SET subtype = subtype || ' Effect'
+WHERE maintype = 'Monster' AND subtype IN ('Flip', 'Union')
+    AND NOT EXISTS (SELECT 1 FROM card_type t
+                    WHERE t.maintype = 'Monster' AND t.subtype = card_type.subtype || ' Effect');
+
+UPDATE cards SET card_type_id =
+    (SELECT n.id FROM card_type o JOIN card_type n
+        ON n.maintype = o.maintype AND n.subtype = o.subtype || ' Effect'
+     WHERE o.id = cards.card_type_id)
+WHERE card_type_id IN
+    (SELECT id FROM card_type WHERE maintype = 'Monster' AND subtype IN ('Flip', 'Union'));
+DELETE FROM card_type WHERE maintype = 'Monster' AND subtype IN ('Flip', 'Union');
+";
+
+/// Ordered list of upgrade steps, see the module comment.
+pub const MIGRATIONS: &[&str] = &[
+    V1_INITIAL_SCHEMA,
+    V2_COPIES,
+    V3_PRICES,
+    V4_WANTS,
+    V5_DECKS,
+    V6_PASSCODES,
+    V7_SEARCH,
+    V8_REGIONS,
+    V9_CARD_IDENTITIES,
+    V10_CARD_STATS,
+    V11_EFFECT_SUBTYPES,
+];
+
+/// Schema version this binary creates and understands.
+pub const SCHEMA_VERSION: i32 = MIGRATIONS.len() as i32;
@@ -0,0 +1,122 @@
+// Connections of the server. One connection writes, a set of read-only connections on the same
+// file serves the reads. With write-ahead logging readers see the last committed state and
+// neither block each other nor the writer. In-memory databases cannot be shared between
+// connections, so there every request goes through the writer.
+
+use std::error::Error;
+use std::ops::Deref;
+use std::sync::{Condvar, Mutex, PoisonError};
+
+use rusqlite::Result;
+
+use crate::db::{DatabaseConnection, setup};
+
+pub struct DatabasePool {
+    writer: Slots,
+    readers: Slots,
+}
+
+// Idle connections of one kind, waiting requests are woken when one is returned
+struct Slots {
+    idle: Mutex<Vec<DatabaseConnection>>,
+    returned: Condvar,
+    size: usize,
+}
+
+/// A connection taken from the pool, given back when dropped
+pub struct PooledConnection<'a> {
+    slots: &'a Slots,
+    conn: Option<DatabaseConnection>,
+}
+
+impl DatabasePool {
+    /// Pool around an already set up database, opening `readers` read connections next to it
+    pub fn new(writer: DatabaseConnection, readers: usize) -> Result<Self> {
+        let mut read_connections = Vec::new();
+        if let Some(path) = writer.path().map(str::to_string)
+            && writer.enable_wal()?
+        {
+            for _ in 0..readers {
+                read_connections.push(DatabaseConnection::open_read_only(&path)?);
+            }
+        }
+        Ok(DatabasePool {
+            writer: Slots::new(vec![writer]),
+            readers: Slots::new(read_connections),
+        })
+    }
+
+    /// Open (or create) and migrate a database file like `setup` and pool its connections
+    pub fn open(dbname: &str, readers: usize) -> Result<Self, Box<dyn Error>> {
+        Ok(DatabasePool::new(setup(dbname)?, readers)?)
+    }
+
+    /// Number of read-only connections, 0 when reads share the writer
+    pub fn readers(&self) -> usize {
+        self.readers.size
+    }
+
+    /// A connection for queries only, waiting while all of them are in use
+    pub fn read(&self) -> PooledConnection<'_> {
+        if self.readers.size == 0 {
+            return self.write();
+        }
+        self.readers.take()
+    }
+
+    /// The only connection allowed to change the database, waiting while it is in use
+    pub fn write(&self) -> PooledConnection<'_> {
+        self.writer.take()
+    }
+}
+
+impl Slots {
+    fn new(connections: Vec<DatabaseConnection>) -> Self {
+        Slots {
+            size: connections.len(),
+            idle: Mutex::new(connections),
+            returned: Condvar::new(),
+        }
+    }
+
+    fn take(&self) -> PooledConnection<'_> {
+        // the lock only guards pushing and popping, a poisoned lock still holds a valid list
+        let mut idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            if let Some(conn) = idle.pop() {
+                return PooledConnection {
+                    slots: self,
+                    conn: Some(conn),
+                };
+            }
+            idle = self
+                .returned
+                .wait(idle)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = DatabaseConnection;
+
+    fn deref(&self) -> &DatabaseConnection {
+        self.conn
+            .as_ref()
+            .expect("connection is only taken on drop")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    // also runs when a request panics, open transactions are rolled back by their own drop
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.slots
+                .idle
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(conn);
+            self.slots.returned.notify_one();
+        }
+    }
+}
@@ -0,0 +1,442 @@
+// Card query language used by `find cards` and the card search API.
+//
+// A query is a list of terms separated by whitespace, all of which must match:
+//   rarity:"ultra rare" type:trap series:LOB owned>0 name:dragon
+//   attribute:dark level>=7 atk>=2500 race:spellcaster
+//
+// A term is `field:value` or `field<op>value` with op one of `=`, `!=`, `>`, `>=`, `<`, `<=`
+// (comparisons only for numeric fields). Values containing spaces are written in double quotes.
+// A term starting with `-` is negated, e.g. `-type:spell`. Terms without a field are searched
+// for in the full-text index (see `search::fts_query`).
+
+use std::fmt;
+
+use rusqlite::types::Value;
+
+use crate::search::fts_query;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    /// Character offset in the query where the problem starts
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at position {}: {}", self.position + 1, self.message)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Name,
+    Number,
+    Rarity,
+    Type,
+    Series,
+    Text,
+    Owned,
+    Passcode,
+    Year,
+    Attribute,
+    MonsterType,
+    Level,
+    Rank,
+    Link,
+    Atk,
+    Def,
+}
+
+impl Field {
+    /// Accepted field names, shown in parse errors
+    pub const NAMES: &'static [&'static str] = &[
+        "name",
+        "number",
+        "rarity",
+        "type",
+        "series",
+        "text",
+        "owned",
+        "passcode",
+        "year",
+        "attribute",
+        "race",
+        "level",
+        "rank",
+        "link",
+        "atk",
+        "def",
+    ];
+
+    fn parse(name: &str) -> Option<Field> {
+        match name.to_lowercase().as_str() {
+            "name" => Some(Field::Name),
+            "number" | "id" => Some(Field::Number),
+            "rarity" => Some(Field::Rarity),
+            "type" | "cardtype" => Some(Field::Type),
+            "series" | "set" => Some(Field::Series),
+            "text" => Some(Field::Text),
+            "owned" | "in_collection" => Some(Field::Owned),
+            "passcode" => Some(Field::Passcode),
+            "year" => Some(Field::Year),
+            "attribute" | "attr" => Some(Field::Attribute),
+            "race" | "monster_type" | "monstertype" => Some(Field::MonsterType),
+            "level" | "lv" => Some(Field::Level),
+            "rank" => Some(Field::Rank),
+            "link" | "link_rating" => Some(Field::Link),
+            "atk" => Some(Field::Atk),
+            "def" => Some(Field::Def),
+            _ => None,
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            Field::Owned
+                | Field::Passcode
+                | Field::Year
+                | Field::Level
+                | Field::Rank
+                | Field::Link
+                | Field::Atk
+                | Field::Def
+        )
+    }
+
+    // SQL expression of a numeric field
+    fn column(&self) -> &'static str {
+        match self {
+            Field::Owned => "c.in_collection",
+            Field::Passcode => "i.passcode",
+            Field::Year => "CAST(strftime('%Y', s.release_date) AS INTEGER)",
+            Field::Level => "i.level",
+            Field::Rank => "i.rank",
+            Field::Link => "i.link_rating",
+            Field::Atk => "i.atk",
+            Field::Def => "i.def",
+            _ => unreachable!("only numeric fields have a single column"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// `:`, contains for text fields and equals for numeric ones
+    Match,
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Op {
+    fn sql(&self) -> &'static str {
+        match self {
+            Op::Match | Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TermKind {
+    /// Words without a field, matched against the full-text index
+    Text(String),
+    Field {
+        field: Field,
+        op: Op,
+        value: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub negated: bool,
+    pub kind: TermKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Query {
+    pub terms: Vec<Term>,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    /// Character offset of the next character
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn error<T>(&self, position: usize, message: String) -> Result<T, QueryError> {
+        Err(QueryError { position, message })
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    // A bare word (up to whitespace, an operator or a quote) or a quoted string
+    fn value(&mut self, stop_at_operator: bool) -> Result<String, QueryError> {
+        if self.peek() == Some('"') {
+            let start = self.pos;
+            self.pos += 1;
+            let mut value = String::new();
+            loop {
+                match self.peek() {
+                    None => return self.error(start, "unterminated quoted value".into()),
+                    Some('"') => {
+                        self.pos += 1;
+                        return Ok(value);
+                    }
+                    Some('\\') if self.chars.get(self.pos + 1).is_some() => {
+                        value.push(self.chars[self.pos + 1]);
+                        self.pos += 2;
+                    }
+                    Some(c) => {
+                        value.push(c);
+                        self.pos += 1;
+                    }
+                }
+            }
+        }
+
+        let mut value = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == '"' {
+                break;
+            }
+            if stop_at_operator && matches!(c, ':' | '=' | '!' | '<' | '>') {
+                break;
+            }
+            value.push(c);
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    fn operator(&mut self) -> Option<Op> {
+        let (op, len) = match (self.peek(), self.chars.get(self.pos + 1).copied()) {
+            (Some(':'), _) => (Op::Match, 1),
+            (Some('='), _) => (Op::Eq, 1),
+            (Some('!'), Some('=')) => (Op::Ne, 2),
+            (Some('>'), Some('=')) => (Op::Ge, 2),
+            (Some('>'), _) => (Op::Gt, 1),
+            (Some('<'), Some('=')) => (Op::Le, 2),
+            (Some('<'), _) => (Op::Lt, 1),
+            _ => return None,
+        };
+        self.pos += len;
+        Some(op)
+    }
+
+    fn term(&mut self) -> Result<Term, QueryError> {
+        let start = self.pos;
+        let negated = self.peek() == Some('-');
+        if negated {
+            self.pos += 1;
+        }
+
+        let word_start = self.pos;
+        let quoted = self.peek() == Some('"');
+        let word = self.value(true)?;
+        let op_start = self.pos;
+        let op = if quoted { None } else { self.operator() };
+
+        let Some(op) = op else {
+            if word.is_empty() {
+                return self.error(start, "expected a search term".into());
+            }
+            if fts_query(&word).is_none() {
+                return self.error(
+                    word_start,
+                    format!("'{}' contains no searchable words", word),
+                );
+            }
+            return Ok(Term {
+                negated,
+                kind: TermKind::Text(word),
+            });
+        };
+
+        let Some(field) = Field::parse(&word) else {
+            return self.error(
+                word_start,
+                format!(
+                    "unknown field '{}', expected one of {}",
+                    word,
+                    Field::NAMES.join(", ")
+                ),
+            );
+        };
+
+        let value_start = self.pos;
+        let value = self.value(false)?;
+        if value.is_empty() {
+            return self.error(value_start, format!("missing value for '{}'", word));
+        }
+        if field.is_numeric() {
+            if value.parse::<i64>().is_err() {
+                return self.error(
+                    value_start,
+                    format!("'{}' expects a number, found '{}'", word, value),
+                );
+            }
+        } else if !matches!(op, Op::Match | Op::Eq | Op::Ne) {
+            return self.error(
+                op_start,
+                format!("'{}' can only be compared with ':', '=' or '!='", word),
+            );
+        }
+
+        Ok(Term {
+            negated,
+            kind: TermKind::Field { field, op, value },
+        })
+    }
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, QueryError> {
+        let mut parser = Parser {
+            chars: input.chars().collect(),
+            pos: 0,
+        };
+        let mut terms = Vec::new();
+        parser.skip_whitespace();
+        while parser.peek().is_some() {
+            terms.push(parser.term()?);
+            if parser.peek().is_some_and(|c| !c.is_whitespace()) {
+                return parser.error(parser.pos, "expected whitespace between terms".into());
+            }
+            parser.skip_whitespace();
+        }
+        Ok(Query { terms })
+    }
+
+    /// True when the query only has (non-negated) words without a field,
+    /// which is answered best by a ranked full-text search
+    pub fn is_plain_text(&self) -> bool {
+        !self.terms.is_empty()
+            && self
+                .terms
+                .iter()
+                .all(|t| !t.negated && matches!(t.kind, TermKind::Text(_)))
+    }
+
+    /// Words of all text terms, space separated
+    pub fn text(&self) -> String {
+        self.terms
+            .iter()
+            .filter_map(|t| match &t.kind {
+                TermKind::Text(words) => Some(words.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// WHERE clause over the aliases of the card query (`c` cards, `i` card_identities,
+    /// `r` rarity, `t` card_type, `s` series) with its parameters. An empty query matches every card.
+    pub fn to_sql(&self) -> (String, Vec<Value>) {
+        let mut params: Vec<Value> = Vec::new();
+        let mut clauses = Vec::new();
+
+        for term in &self.terms {
+            let clause = match &term.kind {
+                TermKind::Text(words) => {
+                    params.push(Value::Text(fts_query(words).unwrap_or_default()));
+                    format!(
+                        "c.id IN (SELECT rowid FROM cards_fts WHERE cards_fts MATCH ?{})",
+                        params.len()
+                    )
+                }
+                TermKind::Field { field, op, value } if field.is_numeric() => {
+                    params.push(Value::Integer(value.parse().unwrap_or_default()));
+                    format!("{} {} ?{}", field.column(), op.sql(), params.len())
+                }
+                TermKind::Field { field, op, value } => {
+                    // rarities are matched in full, `rarity:rare` should not find Super Rares,
+                    // and so are attributes and monster types (`race:beast` is not a Winged Beast)
+                    let exact = *op != Op::Match
+                        || matches!(field, Field::Rarity | Field::Attribute | Field::MonsterType);
+                    params.push(Value::Text(if exact {
+                        value.clone()
+                    } else {
+                        // what the user typed is matched literally
+                        let escaped = value
+                            .replace('\\', "\\\\")
+                            .replace('%', "\\%")
+                            .replace('_', "\\_");
+                        format!("%{}%", escaped)
+                    }));
+                    let param = format!("?{}", params.len());
+                    let matches = |column: &str| {
+                        if exact {
+                            format!("{} = {} COLLATE NOCASE", column, param)
+                        } else {
+                            format!("{} LIKE {} ESCAPE '\\'", column, param)
+                        }
+                    };
+                    let clause = match field {
+                        Field::Name => matches("i.name"),
+                        Field::Number => matches("c.number"),
+                        Field::Rarity => matches("r.name"),
+                        // `type:trap` finds the main type, `type:"effect monster"` the full name
+                        Field::Type => format!(
+                            "({} OR {} OR {})",
+                            matches("t.maintype"),
+                            matches("t.subtype"),
+                            matches("t.subtype || ' ' || t.maintype")
+                        ),
+                        // `series:LOB` finds the prefix, anything else the series name
+                        Field::Series => {
+                            let clause = matches("s.name");
+                            params.push(Value::Text(value.clone()));
+                            format!(
+                                "({} OR s.prefix = ?{} COLLATE NOCASE)",
+                                clause,
+                                params.len()
+                            )
+                        }
+                        Field::Text => matches("COALESCE(i.text, '')"),
+                        Field::Attribute => matches("COALESCE(i.attribute, '')"),
+                        Field::MonsterType => matches("COALESCE(i.monster_type, '')"),
+                        _ => unreachable!("numeric fields are handled above"),
+                    };
+                    if *op == Op::Ne {
+                        format!("NOT {}", clause)
+                    } else {
+                        clause
+                    }
+                }
+            };
+            clauses.push(if term.negated {
+                format!("NOT ({})", clause)
+            } else {
+                clause
+            });
+        }
+
+        if clauses.is_empty() {
+            ("1".to_string(), params)
+        } else {
+            (clauses.join(" AND "), params)
+        }
+    }
+}
@@ -1,7 +1,17 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Rarity {
     pub id: i32,
     pub name: String,
 }
+
+/// A rarity with the number of cards printed in it and the owned copies of those cards
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct RarityUsage {
+    #[serde(flatten)]
+    pub rarity: Rarity,
+    pub cards: i32,
+    pub owned_copies: i32,
+}
@@ -1,23 +1,79 @@
 use axum::{Json, Router, http::StatusCode, response::IntoResponse, routing::get};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::{OpenApi, ToSchema};
+use utoipa_axum::{router::OpenApiRouter, routes};
 
 use crate::AppState;
 use crate::routes::cards; // bring in cards module
+use crate::routes::cardtypes; // bring in card types module
+use crate::routes::collection; // bring in collection import/export module
+use crate::routes::decks; // bring in decks module
+use crate::routes::error::ApiError;
+use crate::routes::identities; // bring in card identities module
+use crate::routes::rarities; // bring in rarities module
+use crate::routes::search; // bring in search module
 use crate::routes::series; // bring in series module
+use crate::routes::stats; // bring in stats module
+use crate::routes::wants; // bring in wants module
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Card Collection Manager API",
+        description = "Manage a trading card collection: series, cards and their printings, copies, wants and decks. \
+                       Failing requests answer with an `ApiError` body."
+    ),
+    servers((url = "/api")),
+    components(schemas(ApiError))
+)]
+struct ApiDoc;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 struct Health {
     status: &'static str,
 }
 
-pub fn routes() -> Router<Arc<AppState>> {
-    Router::new()
-        .route("/health", get(health))
+/// All API routes together with their OpenAPI description
+pub fn router() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .routes(routes!(health))
+        .merge(collection::routes()) // /export.csv and /import.csv
         .nest("/cards", cards::routes()) // mount cards under /cards
+        .nest("/card-identities", identities::routes()) // mount card identities under /card-identities
+        .nest("/card-types", cardtypes::routes()) // mount card types under /card-types
+        .nest("/decks", decks::routes()) // mount decks under /decks
+        .nest("/rarities", rarities::routes()) // mount rarities under /rarities
+        .nest("/search", search::routes()) // mount search under /search
         .nest("/series", series::routes()) // mount series under /series
+        .nest("/stats", stats::routes()) // mount stats under /stats
+        .nest("/wants", wants::routes()) // mount wants under /wants
 }
 
+/// The OpenAPI document served at `/openapi.json`
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    router().into_openapi()
+}
+
+pub fn routes() -> Router<Arc<AppState>> {
+    let (router, openapi) = router().split_for_parts();
+    router
+        .route("/openapi.json", get(move || async move { Json(openapi) }))
+        .fallback(unknown_route)
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, body = Health),
+    )
+)]
 async fn health() -> impl IntoResponse {
     (StatusCode::OK, Json(Health { status: "ok" }))
 }
+
+async fn unknown_route() -> ApiError {
+    ApiError::new(StatusCode::NOT_FOUND, "unknown_route", "No such API route")
+}
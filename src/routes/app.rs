@@ -0,0 +1,46 @@
+use axum::Router;
+use axum::http::HeaderValue;
+use std::error::Error;
+use std::sync::Arc;
+use tower_http::cors::{Any, CorsLayer};
+use tower_http::services::{ServeDir, ServeFile};
+use tower_http::trace::TraceLayer;
+
+use crate::AppState;
+use crate::config::ServerConfig;
+use crate::routes::api;
+
+/// The whole server: the API under `/api`, the frontend at all other paths when
+/// `static_dir` is set, CORS for the configured origins and a log line per request
+pub fn router(config: &ServerConfig, state: Arc<AppState>) -> Result<Router, Box<dyn Error>> {
+    let mut app = Router::new().nest("/api", api::routes());
+
+    if let Some(dir) = &config.static_dir {
+        if !dir.join("index.html").is_file() {
+            return Err(format!("No index.html in static dir {}", dir.display()).into());
+        }
+        // paths without a file are routes of the single page app
+        let frontend = ServeDir::new(dir).fallback(ServeFile::new(dir.join("index.html")));
+        app = app.fallback_service(frontend);
+    }
+
+    if !config.cors_origins.is_empty() {
+        let cors = CorsLayer::new().allow_methods(Any).allow_headers(Any);
+        let cors = if config.allows_any_origin() {
+            cors.allow_origin(Any)
+        } else {
+            let origins = config
+                .cors_origins
+                .iter()
+                .map(|origin| {
+                    HeaderValue::from_str(origin)
+                        .map_err(|_| format!("Invalid CORS origin: {}", origin))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            cors.allow_origin(origins)
+        };
+        app = app.layer(cors);
+    }
+
+    Ok(app.layer(TraceLayer::new_for_http()).with_state(state))
+}
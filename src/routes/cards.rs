@@ -1,7 +1,13 @@
-use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing::get};
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::task;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NewCard {
@@ -14,137 +20,445 @@ pub struct NewCard {
     pub card_type_id: i32,
 }
 
+use crate::card::{
+    BatchMode, Card, CardListOptions, CardResult, Copy, CopyAttributes, DatabaseCard,
+};
+use crate::db::get_series_and_number;
+use crate::identity::CardStats;
+use crate::query::Query as CardQuery;
 use crate::rarity::Rarity;
-use crate::{AppState, cardtype::CardType, dberror::DbError, series::Series};
+use crate::routes::error::{ApiError, ApiJson, ApiPath, ApiQuery, read_db, write_db};
+use crate::search::Highlight;
+use crate::{AppState, cardtype::CardType, series::Series};
 
-pub fn routes() -> Router<Arc<AppState>> {
-    Router::new().route(
-        "/",
-        get(list_cards).post(search_cards).put(update_card_count),
-    )
+pub fn routes() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(list_cards, search_cards, update_card_count))
+        .routes(routes!(collect_cards))
+        .routes(routes!(sell_cards))
+        .routes(routes!(get_card, create_card, patch_card, delete_card))
+        .routes(routes!(get_card_slot))
 }
 
-#[derive(Serialize)]
-struct CardWithMeta {
+#[derive(Serialize, ToSchema)]
+pub(crate) struct CardWithMeta {
+    /// Id of the card identity this is a printing of
+    identity_id: i32,
     number: String,
+    region: String,
     name: String,
     series: Series,
     in_collection: i32,
     cardtype: CardType,
     cardtype_display: String,
     rarity: Rarity,
+    text: Option<String>,
+    #[serde(flatten)]
+    stats: CardStats,
+    copies: Vec<Copy>,
 }
 
-pub async fn list_cards(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let db = state.db.clone();
-
-    let cards_with_meta: Vec<CardWithMeta> = task::spawn_blocking(move || {
-        let db = db.lock().unwrap();
-
-        // Assuming your DB returns Vec<(Card, String, String)>
-        db.get_cards(None)
-            .unwrap()
-            .into_iter()
-            .map(|card| CardWithMeta {
-                number: card.number,
-                name: card.name,
-                series: card.series,
-                in_collection: card.in_collection,
-                rarity: card.rarity,
-                cardtype: card.cardtype.clone(),
-                cardtype_display: card.cardtype.display(),
-            })
-            .collect()
-    })
-    .await
-    .unwrap();
+impl From<Card> for CardWithMeta {
+    fn from(card: Card) -> Self {
+        CardWithMeta {
+            identity_id: card.identity_id,
+            number: card.number,
+            region: card.region,
+            name: card.name,
+            series: card.series,
+            in_collection: card.in_collection,
+            rarity: card.rarity,
+            cardtype_display: card.cardtype.display(),
+            cardtype: card.cardtype,
+            text: card.text,
+            stats: card.stats,
+            copies: card.copies,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct CardPageResponse {
+    cards: Vec<CardWithMeta>,
+    /// Number of cards matching the filters, over all pages
+    total: i64,
+    limit: Option<i64>,
+    offset: i64,
+}
 
-    (StatusCode::OK, Json(cards_with_meta))
+/// Cards filtered, sorted and paged by the query parameters, see `CardListOptions`
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "cards",
+    params(CardListOptions),
+    responses(
+        (status = 200, description = "One page of cards", body = CardPageResponse),
+        (status = 400, description = "Invalid paging or sort parameters", body = ApiError),
+    )
+)]
+async fn list_cards(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(options): ApiQuery<CardListOptions>,
+) -> Result<Json<CardPageResponse>, ApiError> {
+    let page = read_db(&state, move |db| db.list_cards(&options)).await?;
+    Ok(Json(CardPageResponse {
+        cards: page.cards.into_iter().map(CardWithMeta::from).collect(),
+        total: page.total,
+        limit: page.limit,
+        offset: page.offset,
+    }))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct SearchRequest {
     name: Option<String>,
+    /// Query in the card query language, e.g. `rarity:"ultra rare" type:trap owned>0`
+    query: Option<String>,
 }
+
+enum CardSearch {
+    Query(CardQuery),
+    Text(String),
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "cards",
+    request_body = SearchRequest,
+    responses(
+        (status = 200, description = "Cards matching the name or query", body = Vec<CardWithMeta>),
+        (status = 400, description = "Neither name nor query given, or an invalid query", body = ApiError),
+    )
+)]
 async fn search_cards(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<SearchRequest>,
-) -> impl IntoResponse {
-    let query = match payload.name {
-        Some(ref q) if !q.is_empty() => q.clone(),
-        _ => return (StatusCode::FORBIDDEN, "name is required").into_response(),
+    ApiJson(payload): ApiJson<SearchRequest>,
+) -> Result<Json<Vec<CardWithMeta>>, ApiError> {
+    // plain words get a ranked full-text search, anything else is compiled to SQL
+    let search = match (payload.query, payload.name) {
+        (Some(q), _) if !q.trim().is_empty() => match CardQuery::parse(&q)? {
+            query if query.is_plain_text() => CardSearch::Text(query.text()),
+            query => CardSearch::Query(query),
+        },
+        (_, Some(name)) if !name.is_empty() => CardSearch::Text(name),
+        _ => return Err(ApiError::bad_request("name or query is required")),
     };
 
-    let db = state.db.clone();
-
-    let cards_with_meta: Vec<CardWithMeta> = task::spawn_blocking(move || {
-        let db: std::sync::MutexGuard<'_, crate::db::DatabaseConnection> = db.lock().unwrap();
-        // Assuming your DB returns Vec<(Card, String, String)>
-        db.get_cards(Some(&query))
-            .unwrap()
-            .into_iter()
-            .map(|card| CardWithMeta {
-                number: card.number,
-                name: card.name,
-                series: card.series,
-                in_collection: card.in_collection,
-                rarity: card.rarity,
-                cardtype: card.cardtype.clone(),
-                cardtype_display: card.cardtype.display(),
-            })
-            .collect()
+    let cards: Vec<Card> = read_db(&state, move |db| match search {
+        CardSearch::Query(query) => db.find_cards(&query),
+        CardSearch::Text(words) => db
+            .search_cards(&words, None, Highlight::HTML)
+            .map(|hits| hits.into_iter().map(|hit| hit.card).collect()),
     })
-    .await
-    .unwrap();
+    .await?;
 
-    (StatusCode::OK, Json(cards_with_meta)).into_response()
+    Ok(Json(cards.into_iter().map(CardWithMeta::from).collect()))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateCardRequest {
     pub id: String,
     #[serde(default)] // optional, defaults to None if missing
     pub number: Option<i32>, //number in collection, defaults to add 1
+    #[serde(flatten)] // condition, edition, language and notes of the copies
+    pub copy: CopyAttributes,
 }
 
+#[utoipa::path(
+    put,
+    path = "/",
+    tag = "cards",
+    request_body = UpdateCardRequest,
+    responses(
+        (status = 200, description = "Copies in the collection afterwards, -1 after a sale", body = i32),
+        (status = 400, description = "Invalid count or copy attributes", body = ApiError),
+        (status = 404, description = "Unknown card", body = ApiError),
+    )
+)]
 async fn update_card_count(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<UpdateCardRequest>,
-) -> impl IntoResponse {
-    let db = state.db.clone();
-
+    ApiJson(payload): ApiJson<UpdateCardRequest>,
+) -> Result<Json<i32>, ApiError> {
     //default to add one
     let number = payload.number; // Option<i32>
 
     let id = payload.id;
+    let copy = payload.copy;
 
-    let result: Result<i32, DbError> = task::spawn_blocking(move || {
-        let db = db.lock().unwrap();
+    let result = write_db(&state, move |db| match number {
+        Some(-1) => {
+            // Selling card
+            db.sell_card(&id, 1, &copy).map(|_| -1) // return -1 or any meaningful marker
+        }
+        other => {
+            // Collecting card, default Some(1)
+            db.collect_card(&id, other, &copy)
+        }
+    })
+    .await?;
 
-        match number {
-            Some(-1) => {
-                // Selling card
-                db.sell_card(&id, 1).map(|_| -1) // return -1 or any meaningful marker
-            }
-            other => {
-                // Collecting card, default Some(1)
-                //let count = other.or(Some(1));
-                db.collect_card(&id, other)
-            }
+    Ok(Json(result))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchCardRequest {
+    /// Card numbers or ranges, e.g. "LOB-001" or "LOB-001-010"
+    pub ids: Vec<String>,
+    #[serde(default)]
+    pub count: Option<i32>,
+    #[serde(default)]
+    pub mode: BatchMode,
+    #[serde(flatten)]
+    pub copy: CopyAttributes,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BatchAction {
+    Collect,
+    Sell,
+}
+
+async fn run_batch(
+    state: Arc<AppState>,
+    payload: BatchCardRequest,
+    action: BatchAction,
+) -> Result<Json<Vec<CardResult>>, ApiError> {
+    let results = write_db(&state, move |db| match action {
+        BatchAction::Collect => {
+            db.collect_cards(&payload.ids, payload.count, &payload.copy, payload.mode)
         }
+        BatchAction::Sell => db.sell_cards(
+            &payload.ids,
+            payload.count.unwrap_or(1),
+            &payload.copy,
+            payload.mode,
+        ),
+    })
+    .await?;
+    Ok(Json(results))
+}
+
+/// Collect several card numbers and ranges in one transaction
+#[utoipa::path(
+    post,
+    path = "/collect",
+    tag = "cards",
+    request_body = BatchCardRequest,
+    responses(
+        (status = 200, description = "Result per card number", body = Vec<CardResult>),
+        (status = 400, description = "A card failed in all-or-nothing mode", body = ApiError),
+        (status = 404, description = "Unknown card in all-or-nothing mode", body = ApiError),
+    )
+)]
+async fn collect_cards(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<BatchCardRequest>,
+) -> Result<Json<Vec<CardResult>>, ApiError> {
+    run_batch(state, payload, BatchAction::Collect).await
+}
+
+/// Sell several card numbers and ranges in one transaction
+#[utoipa::path(
+    post,
+    path = "/sell",
+    tag = "cards",
+    request_body = BatchCardRequest,
+    responses(
+        (status = 200, description = "Result per card number", body = Vec<CardResult>),
+        (status = 400, description = "A card failed in all-or-nothing mode", body = ApiError),
+        (status = 404, description = "Unknown card in all-or-nothing mode", body = ApiError),
+    )
+)]
+async fn sell_cards(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<BatchCardRequest>,
+) -> Result<Json<Vec<CardResult>>, ApiError> {
+    run_batch(state, payload, BatchAction::Sell).await
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateCardRequest {
+    name: String,
+    series_id: i32,
+    /// Defaults to the number at the end of the card number
+    collection_number: Option<i32>,
+    rarity_id: i32,
+    card_type_id: i32,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct PatchCardRequest {
+    name: Option<String>,
+    series_id: Option<i32>,
+    /// Renumber the card
+    number: Option<String>,
+    collection_number: Option<i32>,
+    rarity_id: Option<i32>,
+    card_type_id: Option<i32>,
+    text: Option<String>,
+}
+
+// unknown series, rarities and card types in the request body are
+// validation errors rather than a missing card
+fn invalid_reference(mut e: ApiError) -> ApiError {
+    if matches!(
+        e.code,
+        "unknown_series" | "unknown_rarity" | "unknown_card_type"
+    ) {
+        e.status = StatusCode::UNPROCESSABLE_ENTITY;
+    }
+    e
+}
+
+#[utoipa::path(
+    get,
+    path = "/{number}",
+    tag = "cards",
+    params(("number" = String, Path, description = "Card number, e.g. LOB-EN001")),
+    responses(
+        (status = 200, body = CardWithMeta),
+        (status = 404, description = "Unknown card", body = ApiError),
+    )
+)]
+async fn get_card(
+    State(state): State<Arc<AppState>>,
+    ApiPath(number): ApiPath<String>,
+) -> Result<Json<CardWithMeta>, ApiError> {
+    let card = read_db(&state, move |db| db.get_card(&number)).await?;
+    Ok(Json(CardWithMeta::from(card)))
+}
+
+/// The card in all regions of its series: the cards with its collection number
+#[utoipa::path(
+    get,
+    path = "/{number}/slot",
+    tag = "cards",
+    params(("number" = String, Path, description = "Card number, e.g. LOB-EN001")),
+    responses(
+        (status = 200, description = "Cards of the slot ordered by region, including this one", body = Vec<CardWithMeta>),
+        (status = 404, description = "Unknown card", body = ApiError),
+    )
+)]
+async fn get_card_slot(
+    State(state): State<Arc<AppState>>,
+    ApiPath(number): ApiPath<String>,
+) -> Result<Json<Vec<CardWithMeta>>, ApiError> {
+    let cards = read_db(&state, move |db| db.get_slot_cards(&number)).await?;
+    Ok(Json(cards.into_iter().map(CardWithMeta::from).collect()))
+}
+
+/// Add a card to the catalogue, without copies
+#[utoipa::path(
+    post,
+    path = "/{number}",
+    tag = "cards",
+    params(("number" = String, Path, description = "Number of the new card")),
+    request_body = CreateCardRequest,
+    responses(
+        (status = 201, body = CardWithMeta),
+        (status = 400, description = "Missing name or number", body = ApiError),
+        (status = 409, description = "The card number exists", body = ApiError),
+        (status = 422, description = "Unknown series, rarity or card type", body = ApiError),
+    )
+)]
+async fn create_card(
+    State(state): State<Arc<AppState>>,
+    ApiPath(number): ApiPath<String>,
+    ApiJson(payload): ApiJson<CreateCardRequest>,
+) -> Result<Response, ApiError> {
+    let card = write_db(&state, move |db| {
+        db.transaction(|db| {
+            let card = DatabaseCard {
+                name: payload.name,
+                series_id: payload.series_id,
+                collection_number: payload
+                    .collection_number
+                    .unwrap_or_else(|| get_series_and_number(&number).1),
+                number,
+                in_collection: 0,
+                rarity_id: payload.rarity_id,
+                card_type_id: payload.card_type_id,
+            };
+            let card = db.create_card(&card)?;
+            match payload.text {
+                Some(text) => {
+                    db.set_card_text(&card.number, &text)?;
+                    db.get_card(&card.number)
+                }
+                None => Ok(card),
+            }
+        })
     })
     .await
-    .unwrap();
-
-    match result {
-        Ok(card) => (StatusCode::OK, Json(card)).into_response(),
-        Err(e) => {
-            // map DB errors into proper HTTP codes
-            let status = match e {
-                DbError::InvalidOperation(_) => StatusCode::BAD_REQUEST, // catch all InvalidOperation
-                _ => StatusCode::INTERNAL_SERVER_ERROR,
+    .map_err(invalid_reference)?;
+    Ok((StatusCode::CREATED, Json(CardWithMeta::from(card))).into_response())
+}
+
+/// Update the catalogue fields of a card, copies are changed through collect and sell
+#[utoipa::path(
+    patch,
+    path = "/{number}",
+    tag = "cards",
+    params(("number" = String, Path, description = "Card number")),
+    request_body = PatchCardRequest,
+    responses(
+        (status = 200, body = CardWithMeta),
+        (status = 404, description = "Unknown card", body = ApiError),
+        (status = 409, description = "The new card number exists", body = ApiError),
+        (status = 422, description = "Unknown series, rarity or card type", body = ApiError),
+    )
+)]
+async fn patch_card(
+    State(state): State<Arc<AppState>>,
+    ApiPath(number): ApiPath<String>,
+    ApiJson(payload): ApiJson<PatchCardRequest>,
+) -> Result<Json<CardWithMeta>, ApiError> {
+    let card = write_db(&state, move |db| {
+        db.transaction(|db| {
+            // fields missing from the request keep their current value
+            let current = db.get_database_card(&number)?;
+            let card = DatabaseCard {
+                name: payload.name.unwrap_or(current.name),
+                series_id: payload.series_id.unwrap_or(current.series_id),
+                number: payload.number.unwrap_or(current.number),
+                collection_number: payload
+                    .collection_number
+                    .unwrap_or(current.collection_number),
+                in_collection: current.in_collection,
+                rarity_id: payload.rarity_id.unwrap_or(current.rarity_id),
+                card_type_id: payload.card_type_id.unwrap_or(current.card_type_id),
             };
-            (status, Json(format!("Database error: {}", e))).into_response()
-        }
-    }
+            db.update_card(&number, &card)?;
+            if let Some(text) = payload.text {
+                db.set_card_text(&card.number, &text)?;
+            }
+            db.get_card(&card.number)
+        })
+    })
+    .await
+    .map_err(invalid_reference)?;
+    Ok(Json(CardWithMeta::from(card)))
+}
+
+/// Delete a card without copies
+#[utoipa::path(
+    delete,
+    path = "/{number}",
+    tag = "cards",
+    params(("number" = String, Path, description = "Card number")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "Unknown card", body = ApiError),
+        (status = 409, description = "The card has copies", body = ApiError),
+    )
+)]
+async fn delete_card(
+    State(state): State<Arc<AppState>>,
+    ApiPath(number): ApiPath<String>,
+) -> Result<StatusCode, ApiError> {
+    write_db(&state, move |db| db.delete_card(&number)).await?;
+    Ok(StatusCode::NO_CONTENT)
 }
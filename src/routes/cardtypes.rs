@@ -0,0 +1,134 @@
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use std::sync::Arc;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::AppState;
+use crate::cardtype::{CardType, CardTypeUsage};
+use crate::routes::error::{ApiError, ApiJson, ApiPath, read_db, write_db};
+
+pub fn routes() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(list_card_types, create_card_type))
+        .routes(routes!(get_card_type, update_card_type, delete_card_type))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct UpdateCardTypeRequest {
+    main: Option<String>,
+    sub: Option<String>,
+}
+
+/// All card types with the number of cards and owned copies using them
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "card-types",
+    responses(
+        (status = 200, body = Vec<CardTypeUsage>),
+    )
+)]
+async fn list_card_types(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<CardTypeUsage>>, ApiError> {
+    let card_types = read_db(&state, |db| db.get_card_types()).await?;
+    Ok(Json(card_types))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "card-types",
+    params(("id" = i32, Path, description = "Card type id")),
+    responses(
+        (status = 200, body = CardTypeUsage),
+        (status = 404, description = "Unknown card type", body = ApiError),
+    )
+)]
+async fn get_card_type(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+) -> Result<Json<CardTypeUsage>, ApiError> {
+    let cardtype = read_db(&state, move |db| db.get_card_type(id)).await?;
+    Ok(Json(cardtype))
+}
+
+/// Add a card type, e.g. `{"main": "Spell Card", "sub": "Ritual"}`
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "card-types",
+    request_body = CardType,
+    responses(
+        (status = 201, body = CardTypeUsage),
+        (status = 400, description = "Empty main or sub type", body = ApiError),
+        (status = 409, description = "The card type exists", body = ApiError),
+    )
+)]
+async fn create_card_type(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<CardType>,
+) -> Result<Response, ApiError> {
+    let cardtype = write_db(&state, move |db| db.create_card_type(&payload)).await?;
+    Ok((StatusCode::CREATED, Json(cardtype)).into_response())
+}
+
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    tag = "card-types",
+    params(("id" = i32, Path, description = "Card type id")),
+    request_body = UpdateCardTypeRequest,
+    responses(
+        (status = 200, body = CardTypeUsage),
+        (status = 400, description = "Empty main or sub type", body = ApiError),
+        (status = 404, description = "Unknown card type", body = ApiError),
+        (status = 409, description = "The card type exists", body = ApiError),
+    )
+)]
+async fn update_card_type(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+    ApiJson(payload): ApiJson<UpdateCardTypeRequest>,
+) -> Result<Json<CardTypeUsage>, ApiError> {
+    let cardtype = write_db(&state, move |db| {
+        // fields missing from the request keep their current value
+        let current = db.get_card_type(id)?.cardtype;
+        db.update_card_type(
+            id,
+            &CardType {
+                main: payload.main.unwrap_or(current.main),
+                sub: payload.sub.unwrap_or(current.sub),
+            },
+        )
+    })
+    .await?;
+    Ok(Json(cardtype))
+}
+
+/// Only card types without cards can be deleted
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "card-types",
+    params(("id" = i32, Path, description = "Card type id")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "Unknown card type", body = ApiError),
+        (status = 409, description = "Cards use the card type", body = ApiError),
+    )
+)]
+async fn delete_card_type(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+) -> Result<StatusCode, ApiError> {
+    write_db(&state, move |db| db.delete_card_type(id)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
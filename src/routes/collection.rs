@@ -0,0 +1,91 @@
+use axum::{
+    Json,
+    extract::{Multipart, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use std::sync::Arc;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::AppState;
+use crate::collection_csv::{CsvImportOptions, CsvImportReport, read_csv, write_csv};
+use crate::routes::error::{ApiError, ApiQuery, read_db, write_db};
+
+pub fn routes() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(export_csv))
+        .routes(routes!(import_csv))
+}
+
+/// Body of `POST /import.csv`, only used for the OpenAPI document
+#[derive(ToSchema)]
+#[allow(dead_code)]
+struct CsvUpload {
+    /// The csv file as written by `GET /export.csv`
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/export.csv",
+    tag = "collection",
+    responses(
+        (status = 200, description = "All cards as csv", content_type = "text/csv", body = String),
+    )
+)]
+async fn export_csv(State(state): State<Arc<AppState>>) -> Result<impl IntoResponse, ApiError> {
+    let cards = read_db(&state, |db| Ok(db.get_cards(None)?)).await?;
+    let mut body = Vec::new();
+    write_csv(&mut body, &cards)
+        .map_err(|e| ApiError::internal(format!("Could not write csv: {}", e)))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"collection.csv\"",
+            ),
+        ],
+        body,
+    ))
+}
+
+// multipart upload with the csv in a "file" field, options as query parameters
+#[utoipa::path(
+    post,
+    path = "/import.csv",
+    tag = "collection",
+    params(CsvImportOptions),
+    request_body(content = CsvUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Rows inserted, updated and rejected", body = CsvImportReport),
+        (status = 400, description = "Missing file field or unreadable csv", body = ApiError),
+    )
+)]
+async fn import_csv(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(options): ApiQuery<CsvImportOptions>,
+    mut multipart: Multipart,
+) -> Result<Json<CsvImportReport>, ApiError> {
+    let mut data = None;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() == Some("file") {
+            data = Some(field.bytes().await?);
+        }
+    }
+    let data = data.ok_or_else(|| ApiError::bad_request("file field is required"))?;
+
+    let rows = read_csv(data.as_ref()).map_err(|e| {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_csv",
+            format!("Invalid csv: {}", e),
+        )
+    })?;
+
+    let report = write_db(&state, move |db| db.import_csv_rows(rows, &options)).await?;
+    Ok(Json(report))
+}
@@ -0,0 +1,166 @@
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::AppState;
+use crate::deck::{Deck, DeckCardRequest, DeckCheck};
+use crate::routes::error::{ApiError, ApiJson, ApiPath, read_db, write_db};
+
+pub fn routes() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(list_decks, create_deck))
+        .routes(routes!(get_deck, delete_deck))
+        .routes(routes!(add_card, remove_card))
+        .routes(routes!(check_deck))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateDeckRequest {
+    name: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "decks",
+    responses(
+        (status = 200, body = Vec<Deck>),
+    )
+)]
+async fn list_decks(State(state): State<Arc<AppState>>) -> Result<Json<Vec<Deck>>, ApiError> {
+    let decks = read_db(&state, |db| db.get_decks()).await?;
+    Ok(Json(decks))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "decks",
+    request_body = CreateDeckRequest,
+    responses(
+        (status = 201, body = Deck),
+        (status = 409, description = "A deck with this name exists", body = ApiError),
+    )
+)]
+async fn create_deck(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<CreateDeckRequest>,
+) -> Result<Response, ApiError> {
+    let deck = write_db(&state, move |db| {
+        db.create_deck(&payload.name)?;
+        db.get_deck(&payload.name)
+    })
+    .await?;
+    Ok((StatusCode::CREATED, Json(deck)).into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/{name}",
+    tag = "decks",
+    params(("name" = String, Path, description = "Deck name")),
+    responses(
+        (status = 200, body = Deck),
+        (status = 404, description = "Unknown deck", body = ApiError),
+    )
+)]
+async fn get_deck(
+    State(state): State<Arc<AppState>>,
+    ApiPath(name): ApiPath<String>,
+) -> Result<Json<Deck>, ApiError> {
+    let deck = read_db(&state, move |db| db.get_deck(&name)).await?;
+    Ok(Json(deck))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{name}",
+    tag = "decks",
+    params(("name" = String, Path, description = "Deck name")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "Unknown deck", body = ApiError),
+    )
+)]
+async fn delete_deck(
+    State(state): State<Arc<AppState>>,
+    ApiPath(name): ApiPath<String>,
+) -> Result<StatusCode, ApiError> {
+    write_db(&state, move |db| db.delete_deck(&name)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/{name}/cards",
+    tag = "decks",
+    params(("name" = String, Path, description = "Deck name")),
+    request_body = DeckCardRequest,
+    responses(
+        (status = 200, body = Deck),
+        (status = 400, description = "Invalid quantity", body = ApiError),
+        (status = 404, description = "Unknown deck", body = ApiError),
+    )
+)]
+async fn add_card(
+    State(state): State<Arc<AppState>>,
+    ApiPath(name): ApiPath<String>,
+    ApiJson(payload): ApiJson<DeckCardRequest>,
+) -> Result<Json<Deck>, ApiError> {
+    let deck = write_db(&state, move |db| {
+        db.add_deck_card(&name, &payload)?;
+        db.get_deck(&name)
+    })
+    .await?;
+    Ok(Json(deck))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{name}/cards",
+    tag = "decks",
+    params(("name" = String, Path, description = "Deck name")),
+    request_body = DeckCardRequest,
+    responses(
+        (status = 200, body = Deck),
+        (status = 400, description = "Invalid quantity", body = ApiError),
+        (status = 404, description = "Unknown deck", body = ApiError),
+    )
+)]
+async fn remove_card(
+    State(state): State<Arc<AppState>>,
+    ApiPath(name): ApiPath<String>,
+    ApiJson(payload): ApiJson<DeckCardRequest>,
+) -> Result<Json<Deck>, ApiError> {
+    let deck = write_db(&state, move |db| {
+        db.remove_deck_card(&name, &payload)?;
+        db.get_deck(&name)
+    })
+    .await?;
+    Ok(Json(deck))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{name}/check",
+    tag = "decks",
+    params(("name" = String, Path, description = "Deck name")),
+    responses(
+        (status = 200, description = "Ownership of every deck entry", body = DeckCheck),
+        (status = 404, description = "Unknown deck", body = ApiError),
+    )
+)]
+async fn check_deck(
+    State(state): State<Arc<AppState>>,
+    ApiPath(name): ApiPath<String>,
+) -> Result<Json<DeckCheck>, ApiError> {
+    let check = read_db(&state, move |db| db.check_deck(&name)).await?;
+    Ok(Json(check))
+}
@@ -0,0 +1,164 @@
+// Errors of the HTTP API. Every failing request gets a JSON body
+//   {"code": "unknown_card", "message": "Encountered undefined card: LOB-999", "details": null}
+// where `code` is stable for clients to match on, `message` is meant for people and `details`
+// holds extra data for some errors, e.g. the position of a query syntax error.
+
+use axum::{
+    Json,
+    extract::{
+        FromRequest, FromRequestParts,
+        multipart::MultipartError,
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::{Value, json};
+use tokio::task::{self, JoinError};
+use utoipa::ToSchema;
+
+use crate::AppState;
+use crate::db::DatabaseConnection;
+use crate::dberror::DbError;
+use crate::query::QueryError;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiError {
+    #[serde(skip)]
+    pub status: StatusCode,
+    /// Stable error code, e.g. `unknown_card` or `invalid_query`
+    pub code: &'static str,
+    pub message: String,
+    /// Extra data for some errors, e.g. `{"position": 10}` for an invalid query
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<Value>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        ApiError {
+            status,
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
+
+impl From<DbError> for ApiError {
+    fn from(e: DbError) -> Self {
+        let (status, code) = match &e {
+            DbError::UnknownRarity(_) => (StatusCode::NOT_FOUND, "unknown_rarity"),
+            DbError::UnknownSeries(_) => (StatusCode::NOT_FOUND, "unknown_series"),
+            DbError::UnknownCardType(_) => (StatusCode::NOT_FOUND, "unknown_card_type"),
+            DbError::UnknownCard(_) => (StatusCode::NOT_FOUND, "unknown_card"),
+            DbError::UnknownDeck(_) => (StatusCode::NOT_FOUND, "unknown_deck"),
+            DbError::UnknownRegion(_) => (StatusCode::NOT_FOUND, "unknown_region"),
+            DbError::UniqueConstraintViolation(_) => (StatusCode::CONFLICT, "already_exists"),
+            DbError::InUse(_) => (StatusCode::CONFLICT, "in_use"),
+            DbError::InvalidOperation(_) => (StatusCode::BAD_REQUEST, "invalid_operation"),
+            DbError::UnsupportedSchemaVersion(_) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "unsupported_schema_version",
+            ),
+            DbError::SqliteError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "database_error"),
+        };
+        ApiError::new(status, code, e.to_string())
+    }
+}
+
+impl From<QueryError> for ApiError {
+    fn from(e: QueryError) -> Self {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_query",
+            format!("Invalid query {}", e),
+        )
+        .with_details(json!({ "position": e.position }))
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(e: JsonRejection) -> Self {
+        ApiError::new(e.status(), "invalid_body", e.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(e: QueryRejection) -> Self {
+        ApiError::new(e.status(), "invalid_parameters", e.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(e: PathRejection) -> Self {
+        ApiError::new(e.status(), "invalid_path", e.body_text())
+    }
+}
+
+impl From<MultipartError> for ApiError {
+    fn from(e: MultipartError) -> Self {
+        ApiError::new(e.status(), "invalid_upload", e.body_text())
+    }
+}
+
+impl From<JoinError> for ApiError {
+    fn from(e: JoinError) -> Self {
+        ApiError::internal(format!("Request handler failed: {}", e))
+    }
+}
+
+/// `axum::Json` answering malformed bodies with an `ApiError`
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(ApiError))]
+pub struct ApiJson<T>(pub T);
+
+/// `axum::extract::Query` answering invalid parameters with an `ApiError`
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(ApiError))]
+pub struct ApiQuery<T>(pub T);
+
+/// `axum::extract::Path` answering invalid path segments with an `ApiError`
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(ApiError))]
+pub struct ApiPath<T>(pub T);
+
+/// Run a query `f` on a read connection on the blocking thread pool
+pub async fn read_db<T, F>(state: &AppState, f: F) -> Result<T, ApiError>
+where
+    F: FnOnce(&DatabaseConnection) -> Result<T, DbError> + Send + 'static,
+    T: Send + 'static,
+{
+    let pool = state.db.clone();
+    task::spawn_blocking(move || f(&pool.read()).map_err(ApiError::from)).await?
+}
+
+/// Run `f` on the write connection on the blocking thread pool, one request at a time
+pub async fn write_db<T, F>(state: &AppState, f: F) -> Result<T, ApiError>
+where
+    F: FnOnce(&DatabaseConnection) -> Result<T, DbError> + Send + 'static,
+    T: Send + 'static,
+{
+    let pool = state.db.clone();
+    task::spawn_blocking(move || f(&pool.write()).map_err(ApiError::from)).await?
+}
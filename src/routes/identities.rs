@@ -0,0 +1,64 @@
+use axum::{Json, extract::State};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::AppState;
+use crate::identity::CardIdentityPrintings;
+use crate::routes::error::{ApiError, ApiPath, ApiQuery, read_db};
+
+pub fn routes() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(list_identities))
+        .routes(routes!(get_identity))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct IdentityQuery {
+    /// Part of the card name
+    name: Option<String>,
+    /// `true` for cards with owned copies of any printing, `false` for cards without
+    owned: Option<bool>,
+}
+
+/// Cards with their printings, e.g. `/api/card-identities?name=dark magician&owned=true`
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "card-identities",
+    params(IdentityQuery),
+    responses(
+        (status = 200, description = "Cards by name, each with its printings oldest first", body = Vec<CardIdentityPrintings>),
+        (status = 400, description = "Invalid parameters", body = ApiError),
+    )
+)]
+async fn list_identities(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(query): ApiQuery<IdentityQuery>,
+) -> Result<Json<Vec<CardIdentityPrintings>>, ApiError> {
+    let identities = read_db(&state, move |db| {
+        db.get_identities(query.name.as_deref(), query.owned)
+    })
+    .await?;
+    Ok(Json(identities))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "card-identities",
+    params(("id" = i32, Path, description = "Card identity id")),
+    responses(
+        (status = 200, body = CardIdentityPrintings),
+        (status = 404, description = "Unknown card identity", body = ApiError),
+    )
+)]
+async fn get_identity(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+) -> Result<Json<CardIdentityPrintings>, ApiError> {
+    let identity = read_db(&state, move |db| db.get_identity(id)).await?;
+    Ok(Json(identity))
+}
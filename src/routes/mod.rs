@@ -1,3 +1,13 @@
 pub mod api;
+pub mod app;
 pub mod cards;
+pub mod cardtypes;
+pub mod collection;
+pub mod decks;
+pub mod error;
+pub mod identities;
+pub mod rarities;
+pub mod search;
 pub mod series;
+pub mod stats;
+pub mod wants;
@@ -0,0 +1,121 @@
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use std::sync::Arc;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::AppState;
+use crate::rarity::RarityUsage;
+use crate::routes::error::{ApiError, ApiJson, ApiPath, read_db, write_db};
+
+pub fn routes() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(list_rarities, create_rarity))
+        .routes(routes!(get_rarity, update_rarity, delete_rarity))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct RarityRequest {
+    name: String,
+}
+
+/// All rarities with the number of cards and owned copies using them
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "rarities",
+    responses(
+        (status = 200, body = Vec<RarityUsage>),
+    )
+)]
+async fn list_rarities(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<RarityUsage>>, ApiError> {
+    let rarities = read_db(&state, |db| db.get_rarities()).await?;
+    Ok(Json(rarities))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "rarities",
+    params(("id" = i32, Path, description = "Rarity id")),
+    responses(
+        (status = 200, body = RarityUsage),
+        (status = 404, description = "Unknown rarity", body = ApiError),
+    )
+)]
+async fn get_rarity(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+) -> Result<Json<RarityUsage>, ApiError> {
+    let rarity = read_db(&state, move |db| db.get_rarity(id)).await?;
+    Ok(Json(rarity))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "rarities",
+    request_body = RarityRequest,
+    responses(
+        (status = 201, body = RarityUsage),
+        (status = 400, description = "Empty name", body = ApiError),
+        (status = 409, description = "The rarity exists", body = ApiError),
+    )
+)]
+async fn create_rarity(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<RarityRequest>,
+) -> Result<Response, ApiError> {
+    let rarity = write_db(&state, move |db| db.create_rarity(&payload.name)).await?;
+    Ok((StatusCode::CREATED, Json(rarity)).into_response())
+}
+
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    tag = "rarities",
+    params(("id" = i32, Path, description = "Rarity id")),
+    request_body = RarityRequest,
+    responses(
+        (status = 200, body = RarityUsage),
+        (status = 400, description = "Empty name", body = ApiError),
+        (status = 404, description = "Unknown rarity", body = ApiError),
+        (status = 409, description = "A rarity with this name exists", body = ApiError),
+    )
+)]
+async fn update_rarity(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+    ApiJson(payload): ApiJson<RarityRequest>,
+) -> Result<Json<RarityUsage>, ApiError> {
+    let rarity = write_db(&state, move |db| db.rename_rarity(id, &payload.name)).await?;
+    Ok(Json(rarity))
+}
+
+/// Only rarities without cards can be deleted
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "rarities",
+    params(("id" = i32, Path, description = "Rarity id")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "Unknown rarity", body = ApiError),
+        (status = 409, description = "Cards use the rarity", body = ApiError),
+    )
+)]
+async fn delete_rarity(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+) -> Result<StatusCode, ApiError> {
+    write_db(&state, move |db| db.delete_rarity(id)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,67 @@
+use axum::{Json, extract::State};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::AppState;
+use crate::routes::cards::CardWithMeta;
+use crate::routes::error::{ApiError, ApiQuery, read_db};
+use crate::search::{Highlight, SearchHit};
+
+pub fn routes() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new().routes(routes!(search))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SearchQuery {
+    /// Words or a prefix to look for in card names and text
+    q: String,
+    /// Maximum number of results
+    limit: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct SearchResult {
+    #[serde(flatten)]
+    card: CardWithMeta,
+    rank: f64,
+    /// HTML-escaped card name with `<mark>` around the matched terms
+    name_highlight: String,
+    /// HTML-escaped fragment of the card text with `<mark>` around the matched terms
+    snippet: Option<String>,
+}
+
+impl From<SearchHit> for SearchResult {
+    fn from(hit: SearchHit) -> Self {
+        SearchResult {
+            card: CardWithMeta::from(hit.card),
+            rank: hit.rank,
+            name_highlight: hit.name_highlight,
+            snippet: hit.snippet,
+        }
+    }
+}
+
+/// Ranked full-text search, e.g. `/api/search?q=blue eyes&limit=20`
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Cards ordered by relevance", body = Vec<SearchResult>),
+        (status = 400, description = "Missing q", body = ApiError),
+    )
+)]
+async fn search(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(query): ApiQuery<SearchQuery>,
+) -> Result<Json<Vec<SearchResult>>, ApiError> {
+    let hits = read_db(&state, move |db| {
+        db.search_cards(&query.q, query.limit, Highlight::HTML)
+    })
+    .await?;
+    Ok(Json(hits.into_iter().map(SearchResult::from).collect()))
+}
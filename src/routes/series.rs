@@ -1,22 +1,324 @@
-use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing::get};
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
 
 use std::sync::Arc;
-use tokio::task;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
 
 use crate::AppState;
-use crate::series::Series;
+use crate::db::DatabaseConnection;
+use crate::dberror::DbError;
+use crate::routes::error::{ApiError, ApiJson, ApiPath, read_db, write_db};
+use crate::series::{Series, SeriesRegion, SeriesRegionSummary};
 
-pub fn routes() -> Router<Arc<AppState>> {
-    Router::new().route("/", get(list_series))
+pub fn routes() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(list_series, create_series))
+        .routes(routes!(get_series, update_series, delete_series))
+        .routes(routes!(list_series_regions, create_series_region))
+        .routes(routes!(
+            get_series_region,
+            update_series_region,
+            delete_series_region
+        ))
 }
 
-async fn list_series(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let db = state.db.clone(); // spawn_blocking closure must return the data // spawn_blocking closure returns the vector 
-    let series: Vec<Series> = task::spawn_blocking(move || {
-        let db = db.lock().unwrap(); // lock Mutex 
-        db.get_unique_series().unwrap() // call your method
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateSeriesRequest {
+    name: String,
+    /// YYYY-MM-DD
+    release_date: String,
+    #[serde(default)]
+    n_cards: i32,
+    prefix: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct UpdateSeriesRequest {
+    name: Option<String>,
+    release_date: Option<String>,
+    n_cards: Option<i32>,
+    prefix: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateRegionRequest {
+    /// Code in the card numbers, e.g. DE for LOB-DE001, empty for numbers without one
+    region: String,
+    /// Defaults to the language of the region code
+    #[serde(default)]
+    language: String,
+    /// YYYY-MM-DD
+    release_date: Option<String>,
+    #[serde(default)]
+    n_cards: i32,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct UpdateRegionRequest {
+    language: Option<String>,
+    release_date: Option<String>,
+    n_cards: Option<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "series",
+    responses(
+        (status = 200, body = Vec<Series>),
+    )
+)]
+async fn list_series(State(state): State<Arc<AppState>>) -> Result<Json<Vec<Series>>, ApiError> {
+    let series = read_db(&state, |db| db.get_unique_series()).await?;
+    Ok(Json(series))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "series",
+    params(("id" = i32, Path, description = "Series id")),
+    responses(
+        (status = 200, body = Series),
+        (status = 404, description = "Unknown series", body = ApiError),
+    )
+)]
+async fn get_series(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+) -> Result<Json<Series>, ApiError> {
+    let series = read_db(&state, move |db| db.get_series_by_id(id)).await?;
+    Ok(Json(series))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "series",
+    request_body = CreateSeriesRequest,
+    responses(
+        (status = 201, body = Series),
+        (status = 400, description = "Empty name, invalid date or negative number of cards", body = ApiError),
+        (status = 409, description = "A series with this name exists", body = ApiError),
+    )
+)]
+async fn create_series(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<CreateSeriesRequest>,
+) -> Result<Response, ApiError> {
+    let series = Series {
+        id: None,
+        name: payload.name,
+        release_date: payload.release_date,
+        n_cards: payload.n_cards,
+        prefix: payload.prefix,
+    };
+    let series = write_db(&state, move |db| db.create_series(&series)).await?;
+    Ok((StatusCode::CREATED, Json(series)).into_response())
+}
+
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    tag = "series",
+    params(("id" = i32, Path, description = "Series id")),
+    request_body = UpdateSeriesRequest,
+    responses(
+        (status = 200, body = Series),
+        (status = 400, description = "Empty name, invalid date or negative number of cards", body = ApiError),
+        (status = 404, description = "Unknown series", body = ApiError),
+        (status = 409, description = "A series with this name exists", body = ApiError),
+    )
+)]
+async fn update_series(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+    ApiJson(payload): ApiJson<UpdateSeriesRequest>,
+) -> Result<Json<Series>, ApiError> {
+    let series = write_db(&state, move |db| {
+        // fields missing from the request keep their current value
+        let current = db.get_series_by_id(id)?;
+        db.update_series(
+            id,
+            &Series {
+                id: Some(id),
+                name: payload.name.unwrap_or(current.name),
+                release_date: payload.release_date.unwrap_or(current.release_date),
+                n_cards: payload.n_cards.unwrap_or(current.n_cards),
+                prefix: payload.prefix.or(current.prefix),
+            },
+        )
+    })
+    .await?;
+    Ok(Json(series))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "series",
+    params(("id" = i32, Path, description = "Series id")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "Unknown series", body = ApiError),
+        (status = 409, description = "The series has cards", body = ApiError),
+    )
+)]
+async fn delete_series(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+) -> Result<StatusCode, ApiError> {
+    write_db(&state, move |db| db.delete_series(id)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// A region of the series in the path, regions of other series are unknown here
+fn region_of_series(
+    db: &DatabaseConnection,
+    id: i32,
+    region_id: i32,
+) -> Result<SeriesRegion, DbError> {
+    let region = db.get_series_region(region_id)?;
+    if region.series_id != id {
+        return Err(DbError::UnknownRegion(region_id.to_string()));
+    }
+    Ok(region)
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/regions",
+    tag = "series",
+    params(("id" = i32, Path, description = "Series id")),
+    responses(
+        (status = 200, description = "Regional printings, oldest first", body = Vec<SeriesRegionSummary>),
+        (status = 404, description = "Unknown series", body = ApiError),
+    )
+)]
+async fn list_series_regions(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+) -> Result<Json<Vec<SeriesRegionSummary>>, ApiError> {
+    let regions = read_db(&state, move |db| db.get_series_regions(id)).await?;
+    Ok(Json(regions))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/regions",
+    tag = "series",
+    params(("id" = i32, Path, description = "Series id")),
+    request_body = CreateRegionRequest,
+    responses(
+        (status = 201, body = SeriesRegion),
+        (status = 400, description = "Invalid region code, date or negative number of cards", body = ApiError),
+        (status = 404, description = "Unknown series", body = ApiError),
+        (status = 409, description = "The series has this region", body = ApiError),
+    )
+)]
+async fn create_series_region(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i32>,
+    ApiJson(payload): ApiJson<CreateRegionRequest>,
+) -> Result<Response, ApiError> {
+    let region = SeriesRegion {
+        id: None,
+        series_id: id,
+        region: payload.region,
+        language: payload.language,
+        release_date: payload.release_date,
+        n_cards: payload.n_cards,
+    };
+    let region = write_db(&state, move |db| db.create_series_region(&region)).await?;
+    Ok((StatusCode::CREATED, Json(region)).into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/regions/{region_id}",
+    tag = "series",
+    params(
+        ("id" = i32, Path, description = "Series id"),
+        ("region_id" = i32, Path, description = "Region id"),
+    ),
+    responses(
+        (status = 200, body = SeriesRegion),
+        (status = 404, description = "Unknown series or region", body = ApiError),
+    )
+)]
+async fn get_series_region(
+    State(state): State<Arc<AppState>>,
+    ApiPath((id, region_id)): ApiPath<(i32, i32)>,
+) -> Result<Json<SeriesRegion>, ApiError> {
+    let region = read_db(&state, move |db| region_of_series(db, id, region_id)).await?;
+    Ok(Json(region))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/{id}/regions/{region_id}",
+    tag = "series",
+    params(
+        ("id" = i32, Path, description = "Series id"),
+        ("region_id" = i32, Path, description = "Region id"),
+    ),
+    request_body = UpdateRegionRequest,
+    responses(
+        (status = 200, body = SeriesRegion),
+        (status = 400, description = "Invalid date or negative number of cards", body = ApiError),
+        (status = 404, description = "Unknown series or region", body = ApiError),
+    )
+)]
+async fn update_series_region(
+    State(state): State<Arc<AppState>>,
+    ApiPath((id, region_id)): ApiPath<(i32, i32)>,
+    ApiJson(payload): ApiJson<UpdateRegionRequest>,
+) -> Result<Json<SeriesRegion>, ApiError> {
+    let region = write_db(&state, move |db| {
+        // fields missing from the request keep their current value
+        let current = region_of_series(db, id, region_id)?;
+        db.update_series_region(
+            region_id,
+            &SeriesRegion {
+                language: payload.language.unwrap_or(current.language),
+                release_date: payload.release_date.or(current.release_date),
+                n_cards: payload.n_cards.unwrap_or(current.n_cards),
+                ..current
+            },
+        )
+    })
+    .await?;
+    Ok(Json(region))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}/regions/{region_id}",
+    tag = "series",
+    params(
+        ("id" = i32, Path, description = "Series id"),
+        ("region_id" = i32, Path, description = "Region id"),
+    ),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "Unknown series or region", body = ApiError),
+        (status = 409, description = "The region has cards", body = ApiError),
+    )
+)]
+async fn delete_series_region(
+    State(state): State<Arc<AppState>>,
+    ApiPath((id, region_id)): ApiPath<(i32, i32)>,
+) -> Result<StatusCode, ApiError> {
+    write_db(&state, move |db| {
+        region_of_series(db, id, region_id)?;
+        db.delete_series_region(region_id)
     })
-    .await
-    .unwrap(); // unwrap the JoinHandle 
-    (StatusCode::OK, Json(series))
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
 }
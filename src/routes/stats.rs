@@ -0,0 +1,44 @@
+use axum::{Json, extract::State};
+
+use std::sync::Arc;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::AppState;
+use crate::routes::error::{ApiError, read_db};
+use crate::stats::{CollectionValue, CompletionStats};
+
+pub fn routes() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(collection_value))
+        .routes(routes!(series_completion))
+}
+
+#[utoipa::path(
+    get,
+    path = "/value",
+    tag = "stats",
+    responses(
+        (status = 200, description = "Market value, cost basis and profit per series", body = CollectionValue),
+    )
+)]
+async fn collection_value(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CollectionValue>, ApiError> {
+    let value = read_db(&state, |db| db.get_collection_value()).await?;
+    Ok(Json(value))
+}
+
+#[utoipa::path(
+    get,
+    path = "/series",
+    tag = "stats",
+    responses(
+        (status = 200, description = "Completion per series", body = CompletionStats),
+    )
+)]
+async fn series_completion(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CompletionStats>, ApiError> {
+    let stats = read_db(&state, |db| db.get_completion_stats()).await?;
+    Ok(Json(stats))
+}
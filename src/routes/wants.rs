@@ -0,0 +1,156 @@
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::AppState;
+use crate::routes::error::{ApiError, ApiJson, ApiPath, read_db, write_db};
+use crate::want::{NewWant, Want};
+
+pub fn routes() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(list_wants, create_want))
+        .routes(routes!(missing_wants))
+        .routes(routes!(get_want, update_want, delete_want))
+}
+
+#[derive(Debug, serde::Serialize, ToSchema)]
+struct MissingWant {
+    #[serde(flatten)]
+    want: Want,
+    missing: i32,
+}
+
+#[derive(Debug, serde::Deserialize, ToSchema)]
+struct UpdateWantRequest {
+    quantity: Option<i32>,
+    max_price: Option<f64>,
+    priority: Option<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "wants",
+    responses(
+        (status = 200, body = Vec<Want>),
+    )
+)]
+async fn list_wants(State(state): State<Arc<AppState>>) -> Result<Json<Vec<Want>>, ApiError> {
+    let wants = read_db(&state, |db| db.get_wants()).await?;
+    Ok(Json(wants))
+}
+
+#[utoipa::path(
+    get,
+    path = "/missing",
+    tag = "wants",
+    responses(
+        (status = 200, description = "Wants with fewer copies in the collection than wanted", body = Vec<MissingWant>),
+    )
+)]
+async fn missing_wants(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<MissingWant>>, ApiError> {
+    let wants = read_db(&state, |db| db.get_missing_wants(None)).await?;
+    Ok(Json(
+        wants
+            .into_iter()
+            .map(|want| MissingWant {
+                missing: want.missing(),
+                want,
+            })
+            .collect(),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{number}",
+    tag = "wants",
+    params(("number" = String, Path, description = "Card number")),
+    responses(
+        (status = 200, body = Want),
+        (status = 404, description = "Unknown card or want", body = ApiError),
+    )
+)]
+async fn get_want(
+    State(state): State<Arc<AppState>>,
+    ApiPath(number): ApiPath<String>,
+) -> Result<Json<Want>, ApiError> {
+    let want = read_db(&state, move |db| db.get_want(&number)).await?;
+    Ok(Json(want))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "wants",
+    request_body = NewWant,
+    responses(
+        (status = 201, description = "Added or updated want", body = Want),
+        (status = 400, description = "Quantity below 1", body = ApiError),
+        (status = 404, description = "Unknown card", body = ApiError),
+    )
+)]
+async fn create_want(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<NewWant>,
+) -> Result<Response, ApiError> {
+    let want = write_db(&state, move |db| db.upsert_want(&payload)).await?;
+    Ok((StatusCode::CREATED, Json(want)).into_response())
+}
+
+#[utoipa::path(
+    put,
+    path = "/{number}",
+    tag = "wants",
+    params(("number" = String, Path, description = "Card number")),
+    request_body = UpdateWantRequest,
+    responses(
+        (status = 200, body = Want),
+        (status = 400, description = "Quantity below 1", body = ApiError),
+        (status = 404, description = "Unknown card or want", body = ApiError),
+    )
+)]
+async fn update_want(
+    State(state): State<Arc<AppState>>,
+    ApiPath(number): ApiPath<String>,
+    ApiJson(payload): ApiJson<UpdateWantRequest>,
+) -> Result<Json<Want>, ApiError> {
+    let want = write_db(&state, move |db| {
+        // fields missing from the request keep their current value
+        let current = db.get_want(&number)?;
+        db.upsert_want(&NewWant {
+            number,
+            quantity: payload.quantity.unwrap_or(current.quantity),
+            max_price: payload.max_price.or(current.max_price),
+            priority: payload.priority.unwrap_or(current.priority),
+        })
+    })
+    .await?;
+    Ok(Json(want))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{number}",
+    tag = "wants",
+    params(("number" = String, Path, description = "Card number")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "Unknown card or want", body = ApiError),
+    )
+)]
+async fn delete_want(
+    State(state): State<Arc<AppState>>,
+    ApiPath(number): ApiPath<String>,
+) -> Result<StatusCode, ApiError> {
+    write_db(&state, move |db| db.delete_want(&number)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,92 @@
+// Full-text search over cards, backed by the `cards_fts` FTS5 table (see migration V7).
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::Card;
+
+/// Markers around matched terms in `SearchHit::name_highlight` and `SearchHit::snippet`
+#[derive(Debug, Clone, Copy)]
+pub struct Highlight<'a> {
+    pub open: &'a str,
+    pub close: &'a str,
+    /// Escape `<`, `>`, `&` and quotes of the card data, so the result is safe to render as HTML
+    pub html: bool,
+}
+
+// Markers SQLite puts around matched terms, replaced by those of a `Highlight`
+// after the card data between them has been escaped
+pub(crate) const MATCH_OPEN: &str = "\u{2}";
+pub(crate) const MATCH_CLOSE: &str = "\u{3}";
+
+impl Highlight<'_> {
+    /// HTML `<mark>` tags, used by the API
+    pub const HTML: Highlight<'static> = Highlight {
+        open: "<mark>",
+        close: "</mark>",
+        html: true,
+    };
+
+    /// Bold text on ANSI terminals, used by the CLI
+    pub const TERMINAL: Highlight<'static> = Highlight {
+        open: "\x1b[1m",
+        close: "\x1b[0m",
+        html: false,
+    };
+
+    /// No markers, the plain name and text
+    pub const PLAIN: Highlight<'static> = Highlight {
+        open: "",
+        close: "",
+        html: false,
+    };
+
+    /// Replace the SQLite match markers in `marked` with these markers
+    pub(crate) fn apply(&self, marked: &str) -> String {
+        let text = if self.html {
+            escape_html(marked)
+        } else {
+            marked.to_string()
+        };
+        text.replace(MATCH_OPEN, self.open)
+            .replace(MATCH_CLOSE, self.close)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A card matching a search, best matches first
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchHit {
+    pub card: Card,
+    /// bm25 rank, lower is better
+    pub rank: f64,
+    /// Card name with the matched terms highlighted, HTML-escaped with `Highlight::HTML`
+    pub name_highlight: String,
+    /// Matching fragment of the card text, if the text matched, escaped like `name_highlight`
+    pub snippet: Option<String>,
+}
+
+/// Turn user input into an FTS5 query: every word must match as a prefix, in any column.
+/// Punctuation separates words, so `LOB-EN00` finds `LOB-EN001` and `blue eyes` finds `Blue-Eyes`.
+/// Returns `None` when the input has no words.
+pub fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| format!("\"{}\"*", t))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Series {
     pub id: Option<i32>, // optional until inserted
     pub name: String,
@@ -16,3 +17,28 @@ impl std::ops::Deref for Series {
         &self.id
     }
 }
+
+/// One regional printing of a series, e.g. the German print of LOB numbered LOB-DE001 and up.
+/// `region` is the code in its card numbers, empty for numbers without one like LOB-001.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct SeriesRegion {
+    pub id: Option<i32>, // optional until inserted
+    pub series_id: i32,
+    pub region: String,
+    /// Language of the cards, e.g. EN, DE or JP
+    pub language: String,
+    /// YYYY-MM-DD, `None` when unknown
+    pub release_date: Option<String>,
+    pub n_cards: i32,
+}
+
+/// A regional printing with the cards defined and owned in it
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SeriesRegionSummary {
+    #[serde(flatten)]
+    pub region: SeriesRegion,
+    pub cards: i32,
+    pub owned_cards: i32,
+    /// Rarities of the cards in this region
+    pub rarities: Vec<String>,
+}
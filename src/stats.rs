@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Value of the copies in one series.
+/// Copies without a known market price count as zero towards `market_value`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct SeriesValue {
+    pub series_id: Option<i32>, // None for the collection-wide total
+    pub series: String,
+    pub owned_copies: i32,
+    pub unpriced_copies: i32,
+    pub sold_copies: i32,
+    pub market_value: f64,
+    pub cost_basis: f64,
+    pub unrealized_profit: f64,
+    pub realized_profit: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CollectionValue {
+    pub series: Vec<SeriesValue>,
+    pub total: SeriesValue,
+}
+
+impl CollectionValue {
+    pub fn from_series(series: Vec<SeriesValue>) -> Self {
+        let mut total = SeriesValue {
+            series: "Total".into(),
+            ..Default::default()
+        };
+        for s in &series {
+            total.owned_copies += s.owned_copies;
+            total.unpriced_copies += s.unpriced_copies;
+            total.sold_copies += s.sold_copies;
+            total.market_value += s.market_value;
+            total.cost_basis += s.cost_basis;
+            total.unrealized_profit += s.unrealized_profit;
+            total.realized_profit += s.realized_profit;
+        }
+        CollectionValue { series, total }
+    }
+}
+
+/// Completion of one rarity within a series
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct RarityCompletion {
+    pub rarity: String,
+    pub cards: i32,
+    pub owned_cards: i32,
+    pub completion: f64,
+}
+
+/// Completion of one series. `completion` is measured against `n_cards` when the series has
+/// a known card count, otherwise against the cards defined in the database.
+/// Cards are counted per slot (collection number), so the printings of a slot in several
+/// regions count once and a slot is owned when any of them is.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct SeriesCompletion {
+    pub series_id: Option<i32>, // None for the collection-wide total
+    pub series: String,
+    pub prefix: String,
+    pub n_cards: i32,
+    pub cards_defined: i32,
+    pub owned_cards: i32,
+    pub completion: f64,
+    pub total_copies: i32,
+    /// Copies beyond the first of each slot, whatever their region
+    pub duplicates: i32,
+    pub rarities: Vec<RarityCompletion>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CompletionStats {
+    pub series: Vec<SeriesCompletion>,
+    pub total: SeriesCompletion,
+    /// Series of which every card is owned
+    pub completed_series: i32,
+}
+
+/// Percentage of `part` in `whole`, 0 for an empty whole
+pub fn percentage(part: i32, whole: i32) -> f64 {
+    if whole <= 0 {
+        0.0
+    } else {
+        100.0 * part as f64 / whole as f64
+    }
+}
+
+impl SeriesCompletion {
+    /// Number of cards needed for a complete series
+    pub fn target(&self) -> i32 {
+        if self.n_cards > 0 {
+            self.n_cards
+        } else {
+            self.cards_defined
+        }
+    }
+}
+
+impl CompletionStats {
+    pub fn from_series(series: Vec<SeriesCompletion>) -> Self {
+        let mut total = SeriesCompletion {
+            series: "Total".into(),
+            ..Default::default()
+        };
+        let mut completed_series = 0;
+        let mut target = 0;
+        for s in &series {
+            target += s.target();
+            total.n_cards += s.n_cards;
+            total.cards_defined += s.cards_defined;
+            total.owned_cards += s.owned_cards;
+            total.total_copies += s.total_copies;
+            total.duplicates += s.duplicates;
+            if s.completion >= 100.0 {
+                completed_series += 1;
+            }
+            for r in &s.rarities {
+                match total.rarities.iter_mut().find(|t| t.rarity == r.rarity) {
+                    Some(t) => {
+                        t.cards += r.cards;
+                        t.owned_cards += r.owned_cards;
+                    }
+                    None => total.rarities.push(r.clone()),
+                }
+            }
+        }
+        total.completion = percentage(total.owned_cards, target);
+        for r in &mut total.rarities {
+            r.completion = percentage(r.owned_cards, r.cards);
+        }
+        CompletionStats {
+            series,
+            total,
+            completed_series,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Entry on the want list: `quantity` copies of a card number
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Want {
+    pub number: String,
+    pub name: String,
+    pub series: String,
+    pub rarity: String,
+    pub quantity: i32,
+    pub max_price: Option<f64>,
+    pub priority: i32,
+    pub in_collection: i32,
+}
+
+impl Want {
+    /// Copies still needed to fulfil this want
+    pub fn missing(&self) -> i32 {
+        (self.quantity - self.in_collection).max(0)
+    }
+}
+
+/// Request to add or update a want
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct NewWant {
+    pub number: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+    #[serde(default)]
+    pub max_price: Option<f64>,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+fn default_quantity() -> i32 {
+    1
+}
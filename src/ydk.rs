@@ -0,0 +1,76 @@
+// Reading and writing of YGOPro/EDOPro `.ydk` deck files.
+//
+// A `.ydk` file lists one passcode per line under a `#main`, `#extra` or `!side` header.
+// Other lines starting with `#` are comments.
+
+use crate::deck::DeckSection;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Ydk {
+    pub main: Vec<i64>,
+    pub extra: Vec<i64>,
+    pub side: Vec<i64>,
+}
+
+impl Ydk {
+    /// Parse the contents of a `.ydk` file. Returns the line number of the first invalid line on error.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut ydk = Ydk::default();
+        let mut section = DeckSection::Main;
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            match line {
+                "" => continue,
+                "#main" => section = DeckSection::Main,
+                "#extra" => section = DeckSection::Extra,
+                "!side" => section = DeckSection::Side,
+                _ if line.starts_with('#') => continue, // comments like "#created by ..."
+                _ => {
+                    let passcode = line.parse::<i64>().map_err(|_| {
+                        format!("line {}: expected a passcode, found '{}'", i + 1, line)
+                    })?;
+                    ydk.section_mut(&section).push(passcode);
+                }
+            }
+        }
+        Ok(ydk)
+    }
+
+    pub fn section(&self, section: &DeckSection) -> &Vec<i64> {
+        match section {
+            DeckSection::Main => &self.main,
+            DeckSection::Extra => &self.extra,
+            DeckSection::Side => &self.side,
+        }
+    }
+
+    pub fn section_mut(&mut self, section: &DeckSection) -> &mut Vec<i64> {
+        match section {
+            DeckSection::Main => &mut self.main,
+            DeckSection::Extra => &mut self.extra,
+            DeckSection::Side => &mut self.side,
+        }
+    }
+
+    /// Render as `.ydk` file contents
+    pub fn to_ydk_string(&self) -> String {
+        let mut out = String::from("#created by card-collection-manager\n#main\n");
+        let lines = |codes: &Vec<i64>| codes.iter().map(|c| format!("{}\n", c)).collect::<String>();
+        out += &lines(&self.main);
+        out += "#extra\n";
+        out += &lines(&self.extra);
+        out += "!side\n";
+        out += &lines(&self.side);
+        out
+    }
+}
+
+/// Outcome of translating between a `.ydk` file and a deck
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct YdkReport {
+    /// Number of cards translated
+    pub resolved: i32,
+    /// Passcodes (import) or card names (export) that could not be translated, with their section
+    pub unresolved: Vec<(DeckSection, String)>,
+}
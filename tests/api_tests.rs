@@ -0,0 +1,440 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::Router;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use rusqlite::Connection;
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+use card_collection_manager::AppState;
+use card_collection_manager::card::DatabaseCard;
+use card_collection_manager::db::{DatabaseConnection, setup};
+use card_collection_manager::identity::{Attribute, CardStats};
+use card_collection_manager::pool::DatabasePool;
+use card_collection_manager::routes::api;
+use card_collection_manager::series::Series;
+
+// Database with one series "Test Series" (id 1) holding TS-001, the first card collected
+fn test_db(path: &str) -> DatabaseConnection {
+    let db = setup(path).unwrap();
+    let series_id = db
+        .insert_series(&Series {
+            id: None,
+            name: "Test Series".into(),
+            release_date: "2025-09-05".into(),
+            n_cards: 10,
+            prefix: Some("TS".into()),
+        })
+        .unwrap();
+    db.insert_card(&DatabaseCard {
+        name: "Test Card".into(),
+        series_id,
+        number: "TS-001".into(),
+        collection_number: 1,
+        in_collection: 1,
+        rarity_id: 1,
+        card_type_id: 1,
+    })
+    .unwrap();
+    db
+}
+
+fn app(db: DatabaseConnection) -> Router {
+    let state = Arc::new(AppState {
+        db: Arc::new(DatabasePool::new(db, 2).unwrap()),
+    });
+    Router::new().nest("/api", api::routes()).with_state(state)
+}
+
+// Send a request and return the status with the JSON body (Null when empty)
+async fn send(app: &Router, method: &str, uri: &str, body: Option<&str>) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, body)
+}
+
+// Assert an error response and return its body
+async fn expect_error(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<&str>,
+    status: StatusCode,
+    code: &str,
+) -> Value {
+    let (actual, body) = send(app, method, uri, body).await;
+    assert_eq!(actual, status, "{} {}: {}", method, uri, body);
+    assert_eq!(body["code"], code, "{} {}: {}", method, uri, body);
+    assert!(body["message"].as_str().is_some_and(|m| !m.is_empty()));
+    assert!(body.get("details").is_some());
+    body
+}
+
+#[tokio::test]
+async fn test_unknown_resources_are_not_found() {
+    let app = app(test_db(":memory:"));
+    for (uri, code) in [
+        ("/api/cards/TS-999", "unknown_card"),
+        ("/api/series/99", "unknown_series"),
+        ("/api/rarities/99", "unknown_rarity"),
+        ("/api/card-types/99", "unknown_card_type"),
+        ("/api/decks/none", "unknown_deck"),
+        ("/api/card-identities/99", "unknown_card"),
+        ("/api/nothing", "unknown_route"),
+    ] {
+        expect_error(&app, "GET", uri, None, StatusCode::NOT_FOUND, code).await;
+    }
+
+    let (status, body) = send(&app, "GET", "/api/cards/TS-001", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["in_collection"], 1);
+}
+
+#[tokio::test]
+async fn test_conflicts() {
+    let app = app(test_db(":memory:"));
+    let series = json!({"name": "Test Series", "release_date": "2025-09-05"}).to_string();
+    expect_error(
+        &app,
+        "POST",
+        "/api/series",
+        Some(&series),
+        StatusCode::CONFLICT,
+        "already_exists",
+    )
+    .await;
+    expect_error(
+        &app,
+        "DELETE",
+        "/api/series/1",
+        None,
+        StatusCode::CONFLICT,
+        "in_use",
+    )
+    .await;
+    expect_error(
+        &app,
+        "DELETE",
+        "/api/cards/TS-001",
+        None,
+        StatusCode::CONFLICT,
+        "in_use",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_invalid_requests() {
+    let app = app(test_db(":memory:"));
+    let bad_request = StatusCode::BAD_REQUEST;
+
+    // rejected by the database layer
+    expect_error(
+        &app,
+        "GET",
+        "/api/cards?limit=0",
+        None,
+        bad_request,
+        "invalid_operation",
+    )
+    .await;
+    let collect = json!({"ids": ["TS-001"], "count": -1}).to_string();
+    expect_error(
+        &app,
+        "POST",
+        "/api/cards/collect",
+        Some(&collect),
+        bad_request,
+        "invalid_operation",
+    )
+    .await;
+
+    // rejected by the extractors
+    expect_error(
+        &app,
+        "GET",
+        "/api/series/abc",
+        None,
+        bad_request,
+        "invalid_path",
+    )
+    .await;
+    expect_error(
+        &app,
+        "GET",
+        "/api/cards?sort=price",
+        None,
+        bad_request,
+        "invalid_parameters",
+    )
+    .await;
+    expect_error(
+        &app,
+        "POST",
+        "/api/wants",
+        Some("{not json"),
+        bad_request,
+        "invalid_body",
+    )
+    .await;
+    expect_error(
+        &app,
+        "POST",
+        "/api/wants",
+        Some(r#"{"quantity": 1}"#),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "invalid_body",
+    )
+    .await;
+
+    // rejected by the handlers
+    expect_error(
+        &app,
+        "POST",
+        "/api/cards",
+        Some("{}"),
+        bad_request,
+        "bad_request",
+    )
+    .await;
+    let body = expect_error(
+        &app,
+        "POST",
+        "/api/cards",
+        Some(r#"{"query": "type:trap colour:red"}"#),
+        bad_request,
+        "invalid_query",
+    )
+    .await;
+    assert_eq!(body["details"]["position"], 10);
+}
+
+#[tokio::test]
+async fn test_series_regions_and_card_slots() {
+    let app = app(test_db(":memory:"));
+    let card = json!({"name": "Test Card", "series_id": 1, "rarity_id": 1, "card_type_id": 1});
+    let (status, body) = send(&app, "POST", "/api/cards/TS-DE001", Some(&card.to_string())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["region"], "DE");
+
+    let (status, body) = send(&app, "GET", "/api/series/1/regions", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    assert_eq!(body[1]["region"], "DE");
+    assert_eq!(body[1]["language"], "DE");
+    assert_eq!(body[0]["owned_cards"], 1);
+
+    let (status, body) = send(&app, "GET", "/api/cards/TS-001/slot", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body[0]["number"], "TS-001");
+    assert_eq!(body[1]["number"], "TS-DE001");
+
+    let region = json!({"region": "JP", "release_date": "2025-01-10"}).to_string();
+    let (status, body) = send(&app, "POST", "/api/series/1/regions", Some(&region)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["language"], "JP");
+    let uri = format!("/api/series/1/regions/{}", body["id"]);
+    let (status, body) = send(&app, "PATCH", &uri, Some(r#"{"n_cards": 12}"#)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["n_cards"], 12);
+    assert_eq!(body["release_date"], "2025-01-10");
+    expect_error(
+        &app,
+        "POST",
+        "/api/series/1/regions",
+        Some(&region),
+        StatusCode::CONFLICT,
+        "already_exists",
+    )
+    .await;
+    let (status, _) = send(&app, "DELETE", &uri, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    expect_error(
+        &app,
+        "GET",
+        &uri,
+        None,
+        StatusCode::NOT_FOUND,
+        "unknown_region",
+    )
+    .await;
+    // regions are only found under their own series
+    expect_error(
+        &app,
+        "GET",
+        "/api/series/2/regions/1",
+        None,
+        StatusCode::NOT_FOUND,
+        "unknown_region",
+    )
+    .await;
+    expect_error(
+        &app,
+        "DELETE",
+        "/api/series/1/regions/2",
+        None,
+        StatusCode::CONFLICT,
+        "in_use",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_card_identities_group_printings() {
+    let db = test_db(":memory:");
+    let stats = CardStats {
+        attribute: Some(Attribute::Dark),
+        level: Some(4),
+        atk: Some(1500),
+        ..Default::default()
+    };
+    db.set_card_stats("TS-001", &stats).unwrap();
+    let app = app(db);
+    let card = json!({"name": "test card", "series_id": 1, "rarity_id": 2, "card_type_id": 1});
+    let (status, body) = send(&app, "POST", "/api/cards/TS-002", Some(&card.to_string())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["name"], "Test Card");
+    // the new printing has the stats of its card, unknown stats are left out
+    assert_eq!(body["attribute"], "DARK");
+    assert_eq!(body["atk"], 1500);
+    assert!(body.get("def").is_none());
+    let identity_id = body["identity_id"].clone();
+
+    let (status, body) = send(
+        &app,
+        "GET",
+        "/api/card-identities?name=test&owned=true",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["id"], identity_id);
+    assert_eq!(body[0]["owned_copies"], 1);
+    assert_eq!(body[0]["level"], 4);
+    assert_eq!(body[0]["printings"][1]["number"], "TS-002");
+
+    let uri = format!("/api/card-identities/{}", identity_id);
+    let (status, body) = send(&app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["printings"].as_array().unwrap().len(), 2);
+
+    let (_, body) = send(&app, "GET", "/api/card-identities?owned=false", None).await;
+    assert!(body.as_array().unwrap().is_empty());
+    expect_error(
+        &app,
+        "GET",
+        "/api/card-identities?owned=maybe",
+        None,
+        StatusCode::BAD_REQUEST,
+        "invalid_parameters",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_unknown_references_in_a_new_card() {
+    let app = app(test_db(":memory:"));
+    let card = json!({"name": "New", "series_id": 99, "rarity_id": 1, "card_type_id": 1});
+    expect_error(
+        &app,
+        "POST",
+        "/api/cards/TS-002",
+        Some(&card.to_string()),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "unknown_series",
+    )
+    .await;
+
+    let card = json!({"name": "New", "series_id": 1, "rarity_id": 1, "card_type_id": 1});
+    let (status, body) = send(&app, "POST", "/api/cards/TS-002", Some(&card.to_string())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["number"], "TS-002");
+}
+
+#[tokio::test]
+async fn test_database_failures_are_internal_errors() {
+    let path: PathBuf = std::env::temp_dir().join(format!("ccm-api-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let path = path.to_str().unwrap();
+    let app = app(test_db(path));
+    Connection::open(path)
+        .unwrap()
+        .execute_batch("DROP TABLE wants")
+        .unwrap();
+
+    expect_error(
+        &app,
+        "GET",
+        "/api/wants",
+        None,
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "database_error",
+    )
+    .await;
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+    }
+}
+
+// The committed OpenAPI document must match the annotations on the handlers.
+// Run with UPDATE_OPENAPI=1 to rewrite tests/fixtures/openapi.json after changing the API.
+#[test]
+fn test_openapi_document_is_up_to_date() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/openapi.json");
+    let generated = api::openapi().to_pretty_json().unwrap() + "\n";
+    if std::env::var_os("UPDATE_OPENAPI").is_some() {
+        std::fs::write(path, &generated).unwrap();
+    }
+    let committed = std::fs::read_to_string(path).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "tests/fixtures/openapi.json is outdated, run the tests with UPDATE_OPENAPI=1"
+    );
+}
+
+#[tokio::test]
+async fn test_openapi_paths_are_served() {
+    let app = app(test_db(":memory:"));
+    let (status, served) = send(&app, "GET", "/api/openapi.json", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(served, serde_json::to_value(api::openapi()).unwrap());
+
+    let paths = served["paths"].as_object().unwrap();
+    assert!(paths.contains_key("/cards"));
+    for (path, operations) in paths {
+        // fill path parameters with a value that does not exist
+        let uri = path
+            .split('/')
+            .map(|s| if s.starts_with('{') { "0" } else { s })
+            .collect::<Vec<_>>()
+            .join("/");
+        for method in operations.as_object().unwrap().keys() {
+            if method == "parameters" {
+                continue;
+            }
+            let method = method.to_uppercase();
+            let (status, body) = send(&app, &method, &format!("/api{}", uri), None).await;
+            assert_ne!(
+                status,
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{} {}",
+                method,
+                path
+            );
+            assert_ne!(body["code"], "unknown_route", "{} {}", method, path);
+        }
+    }
+}
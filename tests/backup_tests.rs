@@ -0,0 +1,200 @@
+use card_collection_manager::backup::{BACKUP_FORMAT_VERSION, Backup, ConflictPolicy};
+use card_collection_manager::card::{Condition, CopyAttributes, DatabaseCard};
+use card_collection_manager::db::{DatabaseConnection, setup};
+use card_collection_manager::deck::{DeckCardRequest, DeckSection};
+use card_collection_manager::identity::{Attribute, CardStats};
+use card_collection_manager::jsoncards::PasscodeJson;
+use card_collection_manager::series::{Series, SeriesRegion};
+use card_collection_manager::want::NewWant;
+
+// Database touching every table: two cards (one with stats), copies (one sold), a price, a want,
+// a deck and a passcode
+fn populated_db() -> DatabaseConnection {
+    let db = setup(":memory:").unwrap();
+    let series_id = db
+        .insert_series(&Series {
+            id: None,
+            name: "Test Series".into(),
+            release_date: "September 5, 2025".into(),
+            n_cards: 2,
+            prefix: Some("TS".into()),
+        })
+        .unwrap();
+    for i in 1..=2 {
+        db.insert_card(&DatabaseCard {
+            name: format!("Test Card {}", i),
+            series_id,
+            number: format!("TS-00{}", i),
+            collection_number: i,
+            in_collection: 0,
+            rarity_id: i,
+            card_type_id: 1,
+        })
+        .unwrap();
+    }
+
+    let bought = CopyAttributes {
+        condition: Some(Condition::LightlyPlayed),
+        price: Some(2.5),
+        date: Some("2025-01-01".into()),
+        ..Default::default()
+    };
+    db.collect_card("TS-001", Some(3), &bought).unwrap();
+    let sold = CopyAttributes {
+        price: Some(4.0),
+        date: Some("2025-02-01".into()),
+        ..Default::default()
+    };
+    db.sell_card("TS-001", 1, &sold).unwrap();
+    db.insert_price("TS-001", &Condition::LightlyPlayed, 3.0, Some("2025-03-01"))
+        .unwrap();
+    db.upsert_want(&NewWant {
+        number: "TS-002".into(),
+        quantity: 2,
+        max_price: Some(1.0),
+        priority: 1,
+    })
+    .unwrap();
+    db.create_deck("Test Deck").unwrap();
+    db.add_deck_card(
+        "Test Deck",
+        &DeckCardRequest {
+            card_name: "Test Card 1".into(),
+            section: DeckSection::Main,
+            quantity: 2,
+        },
+    )
+    .unwrap();
+    db.load_passcodes(&[PasscodeJson {
+        passcode: 12345,
+        name: "Test Card 1".into(),
+    }])
+    .unwrap();
+    db.set_card_stats(
+        "TS-002",
+        &CardStats {
+            attribute: Some(Attribute::Water),
+            rank: Some(4),
+            monster_type: Some("Aqua".into()),
+            atk: Some(2100),
+            def: Some(1600),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    db
+}
+
+#[test]
+fn test_backup_roundtrip_into_empty_database() {
+    let source = populated_db();
+    // a region dated apart from its series
+    let region = source.get_series_region_by_code("Test Series", "").unwrap();
+    source
+        .update_series_region(
+            region.id.unwrap(),
+            &SeriesRegion {
+                release_date: Some("2025-10-01".into()),
+                ..region
+            },
+        )
+        .unwrap();
+    let json = serde_json::to_string(&source.create_backup().unwrap()).unwrap();
+    let backup: Backup = serde_json::from_str(&json).unwrap();
+    assert_eq!(backup.format_version, BACKUP_FORMAT_VERSION);
+
+    let target = setup(":memory:").unwrap();
+    let report = target
+        .restore_backup(&backup, &ConflictPolicy::Skip)
+        .unwrap();
+    assert_eq!(report.cards_inserted, 2);
+    assert_eq!(report.copies, 3);
+    assert_eq!(report.regions, 1);
+    assert_eq!(report.identities, 2);
+
+    let restored = target.create_backup().unwrap();
+    assert_eq!(
+        serde_json::to_value(&restored.cards).unwrap(),
+        serde_json::to_value(&backup.cards).unwrap()
+    );
+    assert_eq!(restored.series[0].release_date, "2025-09-05");
+    assert_eq!(
+        restored.regions[0].release_date.as_deref(),
+        Some("2025-10-01")
+    );
+    assert_eq!(restored.prices.len(), 1);
+    assert_eq!(restored.wants[0].quantity, 2);
+    assert_eq!(restored.decks[0].entries[0].quantity, 2);
+    assert_eq!(restored.passcodes.len(), 1);
+    assert_eq!(
+        serde_json::to_value(&restored.identities).unwrap(),
+        serde_json::to_value(&backup.identities).unwrap()
+    );
+
+    let card = &target.get_cards(Some("Test Card 1")).unwrap()[0];
+    assert_eq!(card.in_collection, 2);
+    assert_eq!(card.passcode, Some(12345));
+    assert_eq!(
+        target.get_collection_value().unwrap().total.realized_profit,
+        1.5
+    );
+}
+
+#[test]
+fn test_restore_conflict_policies() {
+    let backup = populated_db().create_backup().unwrap();
+
+    let db = populated_db();
+    let report = db.restore_backup(&backup, &ConflictPolicy::Skip).unwrap();
+    assert_eq!(report.cards_skipped, 2);
+    assert_eq!(report.copies, 0);
+    assert_eq!(report.prices, 0);
+    assert_eq!(
+        db.get_cards(Some("Test Card 1")).unwrap()[0].in_collection,
+        2
+    );
+
+    db.restore_backup(&backup, &ConflictPolicy::MergeQuantities)
+        .unwrap();
+    assert_eq!(
+        db.get_cards(Some("Test Card 1")).unwrap()[0].in_collection,
+        4
+    );
+    assert_eq!(db.get_want("TS-002").unwrap().quantity, 4);
+    assert_eq!(db.get_deck("Test Deck").unwrap().entries[0].quantity, 4);
+
+    let report = db
+        .restore_backup(&backup, &ConflictPolicy::Overwrite)
+        .unwrap();
+    assert_eq!(report.cards_overwritten, 2);
+    assert_eq!(
+        db.get_cards(Some("Test Card 1")).unwrap()[0].in_collection,
+        2
+    );
+    assert_eq!(db.get_all_copies("TS-001").unwrap().len(), 3);
+    assert_eq!(db.get_want("TS-002").unwrap().quantity, 2);
+    assert_eq!(db.get_deck("Test Deck").unwrap().entries[0].quantity, 2);
+}
+
+#[test]
+fn test_restore_refuses_newer_format() {
+    let mut backup = populated_db().create_backup().unwrap();
+    backup.format_version = BACKUP_FORMAT_VERSION + 1;
+
+    let db = setup(":memory:").unwrap();
+    assert!(db.restore_backup(&backup, &ConflictPolicy::Skip).is_err());
+    assert!(db.get_cards(None).unwrap().is_empty());
+}
+
+#[test]
+fn test_parse_conflict_policy() {
+    assert_eq!(
+        "merge-quantities".parse::<ConflictPolicy>().unwrap(),
+        ConflictPolicy::MergeQuantities
+    );
+    assert_eq!(
+        "Overwrite".parse::<ConflictPolicy>().unwrap(),
+        ConflictPolicy::Overwrite
+    );
+    assert!("replace".parse::<ConflictPolicy>().is_err());
+}
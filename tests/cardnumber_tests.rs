@@ -0,0 +1,138 @@
+use card_collection_manager::cardnumber::{
+    CardNumber, MAX_RANGE, expand_card_numbers, parse_card_numbers,
+};
+
+fn expand(spec: &str) -> Vec<String> {
+    expand_card_numbers(spec).unwrap()
+}
+
+#[test]
+fn test_parse_single_card_number() {
+    let n: CardNumber = "LOB-EN001".parse().unwrap();
+    assert_eq!(n.set_code, "LOB");
+    assert_eq!(n.region.as_deref(), Some("EN"));
+    assert_eq!(n.number, 1);
+    assert_eq!(n.width, 3);
+    assert_eq!(n.to_string(), "LOB-EN001");
+
+    let n: CardNumber = "LOB-001".parse().unwrap();
+    assert_eq!(n.region, None);
+    assert_eq!(n.to_string(), "LOB-001");
+
+    let n: CardNumber = "SDK-E001".parse().unwrap();
+    assert_eq!(n.region.as_deref(), Some("E"));
+    assert_eq!(n.to_string(), "SDK-E001");
+
+    let n: CardNumber = "RA01-EN1001".parse().unwrap();
+    assert_eq!(n.set_code, "RA01");
+    assert_eq!(n.number, 1001);
+    assert_eq!(n.width, 4);
+
+    let n: CardNumber = " LOB-EN001 ".parse().unwrap();
+    assert_eq!(n.to_string(), "LOB-EN001");
+}
+
+#[test]
+fn test_parse_rejects_invalid_card_numbers() {
+    for invalid in [
+        "",
+        "LOB",
+        "LOB-",
+        "LOB-EN",
+        "-EN001",
+        "LOB-EN00A",
+        "LOB-E1N001",
+        "LO B-001",
+        "LOB-99999999999",
+    ] {
+        assert!(
+            invalid.parse::<CardNumber>().is_err(),
+            "'{}' should not parse",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn test_single_item_specs() {
+    assert_eq!(expand("LOB-EN001"), vec!["LOB-EN001"]);
+    assert_eq!(expand("TS-001"), vec!["TS-001"]);
+}
+
+#[test]
+fn test_ranges_keep_region_and_width() {
+    assert_eq!(
+        expand("LOB-EN001..EN003"),
+        vec!["LOB-EN001", "LOB-EN002", "LOB-EN003"]
+    );
+    assert_eq!(
+        expand("LOB-E009..E011"),
+        vec!["LOB-E009", "LOB-E010", "LOB-E011"]
+    );
+    assert_eq!(expand("LOB-EN001..003").len(), 3);
+    assert_eq!(expand("LOB-EN001..LOB-EN003").len(), 3);
+    assert_eq!(expand("LOB-01..03"), vec!["LOB-01", "LOB-02", "LOB-03"]);
+    assert_eq!(
+        expand("RA01-EN0998..EN1000"),
+        vec!["RA01-EN0998", "RA01-EN0999", "RA01-EN1000"]
+    );
+    assert_eq!(expand("LOB-EN005..EN005"), vec!["LOB-EN005"]);
+}
+
+#[test]
+fn test_legacy_dash_ranges() {
+    assert_eq!(expand("TS-001-003"), vec!["TS-001", "TS-002", "TS-003"]);
+    assert_eq!(
+        expand("LOB-EN001-EN003"),
+        vec!["LOB-EN001", "LOB-EN002", "LOB-EN003"]
+    );
+    assert_eq!(expand("LOB-E001-E002"), vec!["LOB-E001", "LOB-E002"]);
+}
+
+#[test]
+fn test_lists_reuse_previous_set_and_region() {
+    assert_eq!(
+        expand("LOB-EN001..EN003,EN020"),
+        vec!["LOB-EN001", "LOB-EN002", "LOB-EN003", "LOB-EN020"]
+    );
+    assert_eq!(expand("LOB-EN001,020"), vec!["LOB-EN001", "LOB-EN020"]);
+    assert_eq!(expand("LOB-EN001,DE001"), vec!["LOB-EN001", "LOB-DE001"]);
+    assert_eq!(
+        expand("LOB-EN001, MRD-EN002..EN003 ,010"),
+        vec!["LOB-EN001", "MRD-EN002", "MRD-EN003", "MRD-EN010"]
+    );
+    assert_eq!(
+        expand("LOB-001,005..006"),
+        vec!["LOB-001", "LOB-005", "LOB-006"]
+    );
+}
+
+#[test]
+fn test_invalid_ranges() {
+    for (spec, message) in [
+        ("LOB-EN010..EN001", "ends before it starts"),
+        ("LOB-EN001..DE010", "within one set and region"),
+        ("LOB-EN001..MRD-EN010", "within one set and region"),
+        ("LOB-001..EN010", "within one set and region"),
+        ("EN001..EN010", "no set code"),
+        ("LOB-EN001,,EN003", "empty item"),
+        ("LOB-EN001,", "empty item"),
+        ("LOB-EN001..", "has no number"),
+        ("LOB-EN001..ENX", "has no number"),
+    ] {
+        let err = parse_card_numbers(spec).unwrap_err();
+        assert!(err.contains(message), "'{}' gave '{}'", spec, err);
+    }
+}
+
+#[test]
+fn test_range_size_is_limited() {
+    assert_eq!(
+        parse_card_numbers(&format!("LOB-{:05}..{:05}", 1, MAX_RANGE))
+            .unwrap()
+            .len(),
+        MAX_RANGE as usize
+    );
+    let err = parse_card_numbers(&format!("LOB-{:05}..{:05}", 0, MAX_RANGE)).unwrap_err();
+    assert!(err.contains("larger than"));
+}
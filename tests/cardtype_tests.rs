@@ -0,0 +1,177 @@
+use card_collection_manager::cardtype::{CardType, CardTypeDefinitions};
+use card_collection_manager::db::setup;
+use card_collection_manager::jsoncards::SeriesJson;
+
+// (main, sub) a category is stored as
+fn stored(category: &str) -> (String, String) {
+    let cardtype = CardTypeDefinitions::bundled()
+        .parse_card_type(category)
+        .unwrap();
+    (cardtype.main, cardtype.sub)
+}
+
+fn pair(main: &str, sub: &str) -> (String, String) {
+    (main.to_string(), sub.to_string())
+}
+
+#[test]
+fn test_parse_wiki_categories() {
+    let definitions = CardTypeDefinitions::bundled();
+    let taxon = definitions.parse("Synchro Tuner Effect Monster").unwrap();
+    assert_eq!(taxon.frame, "Synchro");
+    assert_eq!(taxon.abilities, vec!["Tuner", "Effect"]);
+    assert_eq!(
+        stored("Synchro Tuner Effect Monster"),
+        pair("Synchro Monster", "Tuner Effect")
+    );
+
+    assert_eq!(stored("Link Monster"), pair("Link Monster", "Normal"));
+    assert_eq!(
+        stored("Pendulum Effect Monster"),
+        pair("Monster", "Pendulum Effect")
+    );
+    assert_eq!(
+        stored("Xyz Pendulum Effect Monster"),
+        pair("Xyz Monster", "Pendulum Effect")
+    );
+    assert_eq!(stored("Fusion Monster"), pair("Fusion Monster", "Normal"));
+    assert_eq!(
+        stored("Effect Fusion Monster"),
+        pair("Fusion Monster", "Effect")
+    );
+    assert_eq!(
+        stored("Flip Effect Monster"),
+        pair("Monster", "Flip Effect")
+    );
+    // a ritual spell is a spell, not a ritual monster
+    assert_eq!(stored("Ritual Spell Card"), pair("Spell Card", "Ritual"));
+    assert_eq!(
+        stored("Quick-Play Spell Card"),
+        pair("Spell Card", "Quick-Play")
+    );
+    assert_eq!(stored("Trap Card"), pair("Trap Card", "Normal"));
+    // word order, case, slashes and aliases do not matter
+    assert_eq!(
+        stored("effect / tuner / synchro"),
+        stored("Synchro Tuner Effect Monster")
+    );
+    assert_eq!(
+        stored("Dual Effect Monster"),
+        pair("Monster", "Gemini Effect")
+    );
+
+    let e = definitions.parse("Counter Spell Card").unwrap_err();
+    assert!(e.contains("unknown word 'counter'"), "{}", e);
+    let e = definitions.parse("Dragon").unwrap_err();
+    assert!(e.contains("no card frame"), "{}", e);
+}
+
+#[test]
+fn test_seeded_card_types_are_in_the_taxonomy() {
+    let db = setup(":memory:").unwrap();
+    for usage in db.get_card_types().unwrap() {
+        assert!(usage.taxon.is_some(), "{}", usage.display);
+        assert_eq!(
+            CardTypeDefinitions::bundled()
+                .parse_card_type(&usage.display)
+                .unwrap(),
+            usage.cardtype
+        );
+    }
+}
+
+#[test]
+fn test_common_categories_are_seeded() {
+    let db = setup(":memory:").unwrap();
+    for category in [
+        "Normal Monster",
+        "Effect Monster",
+        "Flip Effect Monster",
+        "Union Effect Monster",
+        "Toon Effect Monster",
+        "Spirit Effect Monster",
+        "Gemini Effect Monster",
+        "Tuner Effect Monster",
+        "Fusion Monster",
+        "Effect Fusion Monster",
+        "Ritual Monster",
+        "Ritual Effect Monster",
+        "Normal Spell Card",
+        "Continuous Spell Card",
+        "Equip Spell Card",
+        "Field Spell Card",
+        "Quick-Play Spell Card",
+        "Ritual Spell Card",
+        "Normal Trap Card",
+        "Continuous Trap Card",
+        "Counter Trap Card",
+    ] {
+        assert!(db.get_card_type_id(category).is_ok(), "{}", category);
+    }
+}
+
+#[test]
+fn test_definitions_must_define_their_abilities() {
+    let toml = r#"
+        [[frames]]
+        name = "Monster"
+        main = "Monster"
+        words = ["monster"]
+        abilities = ["Effect"]
+        default = "Normal"
+
+        [[abilities]]
+        name = "Normal"
+    "#;
+    let e = CardTypeDefinitions::from_toml(toml).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "frame 'Monster' names undefined ability 'Effect'"
+    );
+}
+
+#[test]
+fn test_missing_card_types_can_be_created_before_an_import() {
+    let db = setup(":memory:").unwrap();
+    let series: SeriesJson = serde_json::from_value(serde_json::json!({
+        "name": "Hidden Arsenal",
+        "ncards": 3,
+        "release_date": "2009-12-01",
+        "prefix": "HA01",
+        "cards": [
+            {"card_number": "HA01-EN001", "name": "Gravity Warrior", "rarity": "Ultra Rare",
+             "category": "Synchro Effect Monster"},
+            {"card_number": "HA01-EN002", "name": "Rocket Warrior", "rarity": "Common",
+             "category": "Effect Monster"},
+            {"card_number": "HA01-EN003", "name": "Formula Synchron", "rarity": "Ultra Rare",
+             "category": "Synchro Tuner Effect Monster"}
+        ]
+    }))
+    .unwrap();
+    let categories = series.cards.iter().map(|c| c.category.as_str());
+    let missing = db.missing_card_types(categories.clone()).unwrap();
+    assert_eq!(
+        missing,
+        vec![
+            CardType {
+                main: "Synchro Monster".into(),
+                sub: "Effect".into()
+            },
+            CardType {
+                main: "Synchro Monster".into(),
+                sub: "Tuner Effect".into()
+            },
+        ]
+    );
+    assert!(db.add_series_json(series.clone()).is_err());
+
+    for cardtype in &missing {
+        db.create_card_type(cardtype).unwrap();
+    }
+    assert!(db.missing_card_types(categories).unwrap().is_empty());
+    assert_eq!(db.add_series_json(series).unwrap().1, 3);
+    assert_eq!(
+        db.get_card("HA01-EN003").unwrap().cardtype.display(),
+        "Tuner Effect Synchro Monster"
+    );
+}
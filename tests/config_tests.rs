@@ -0,0 +1,165 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::Router;
+use axum::body::Body;
+use axum::http::{Request, Response, StatusCode, header};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+use card_collection_manager::AppState;
+use card_collection_manager::config::ServerConfig;
+use card_collection_manager::pool::DatabasePool;
+use card_collection_manager::routes::app;
+
+fn server(config: &ServerConfig) -> Router {
+    let state = Arc::new(AppState {
+        db: Arc::new(DatabasePool::open(":memory:", 0).unwrap()),
+    });
+    app::router(config, state).unwrap()
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (Response<Body>, String) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let (parts, body) = response.into_parts();
+    let bytes = body.collect().await.unwrap().to_bytes();
+    (
+        Response::from_parts(parts, Body::empty()),
+        String::from_utf8_lossy(&bytes).into_owned(),
+    )
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::builder().uri(uri).body(Body::empty()).unwrap()
+}
+
+// Directory standing in for frontend/dist
+fn frontend_dist() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ccm-dist-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("assets")).unwrap();
+    std::fs::write(dir.join("index.html"), "<div id=\"root\"></div>").unwrap();
+    std::fs::write(dir.join("assets/app.js"), "console.log('app')").unwrap();
+    dir
+}
+
+#[test]
+fn test_config_file_overrides_defaults() {
+    let config = ServerConfig::from_toml(
+        r#"
+        port = 8080
+        cors_origins = ["http://example.com", "http://localhost:4173"]
+        static_dir = "frontend/dist"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.cors_origins.len(), 2);
+    assert_eq!(config.static_dir, Some(PathBuf::from("frontend/dist")));
+    // missing settings keep their default
+    assert_eq!(config.bind, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    assert_eq!(config.log_level, "info");
+    assert_eq!(config.addr().to_string(), "0.0.0.0:8080");
+
+    assert_eq!(
+        ServerConfig::from_toml("").unwrap(),
+        ServerConfig::default()
+    );
+}
+
+#[test]
+fn test_example_config_holds_the_defaults() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/server.example.toml");
+    let config = ServerConfig::load(&PathBuf::from(path)).unwrap();
+    assert_eq!(config, ServerConfig::default());
+}
+
+#[test]
+fn test_invalid_config_files_are_refused() {
+    for text in ["prot = 8080", "port = \"eighty\"", "bind = \"localhost\""] {
+        assert!(ServerConfig::from_toml(text).is_err(), "{}", text);
+    }
+
+    let err = ServerConfig::load(&PathBuf::from("does/not/exist.toml")).unwrap_err();
+    assert!(err.to_string().contains("does/not/exist.toml"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_static_frontend_next_to_the_api() {
+    let dist = frontend_dist();
+    let config = ServerConfig {
+        static_dir: Some(dist.clone()),
+        ..Default::default()
+    };
+    let app = server(&config);
+
+    let (response, body) = send(&app, get("/")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(body.contains("root"));
+    let (_, body) = send(&app, get("/assets/app.js")).await;
+    assert_eq!(body, "console.log('app')");
+    // routes of the frontend get the page itself
+    let (response, body) = send(&app, get("/decks/main")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(body.contains("root"));
+
+    let (response, body) = send(&app, get("/api/health")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(body.contains("ok"));
+    let (response, body) = send(&app, get("/api/nothing")).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(body.contains("unknown_route"));
+
+    let _ = std::fs::remove_dir_all(dist);
+}
+
+#[tokio::test]
+async fn test_without_static_dir_only_the_api_is_served() {
+    let app = server(&ServerConfig::default());
+    let (response, _) = send(&app, get("/")).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let state = Arc::new(AppState {
+        db: Arc::new(DatabasePool::open(":memory:", 0).unwrap()),
+    });
+    let config = ServerConfig {
+        static_dir: Some(PathBuf::from("does/not/exist")),
+        ..Default::default()
+    };
+    assert!(app::router(&config, state).is_err());
+}
+
+#[tokio::test]
+async fn test_cors_origins() {
+    let config = ServerConfig {
+        cors_origins: vec!["http://example.com".into()],
+        ..Default::default()
+    };
+    let app = server(&config);
+
+    for (origin, allowed) in [
+        ("http://example.com", Some("http://example.com")),
+        ("http://localhost:5173", None),
+    ] {
+        let request = Request::builder()
+            .uri("/api/health")
+            .header(header::ORIGIN, origin)
+            .body(Body::empty())
+            .unwrap();
+        let (response, _) = send(&app, request).await;
+        let header = response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN);
+        assert_eq!(header.map(|h| h.to_str().unwrap()), allowed, "{}", origin);
+    }
+
+    let config = ServerConfig {
+        cors_origins: vec!["*".into()],
+        ..Default::default()
+    };
+    let request = Request::builder()
+        .uri("/api/health")
+        .header(header::ORIGIN, "http://anywhere.org")
+        .body(Body::empty())
+        .unwrap();
+    let (response, _) = send(&server(&config), request).await;
+    assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+}
@@ -0,0 +1,154 @@
+use card_collection_manager::collection_csv::{CsvImportOptions, read_csv, write_csv};
+use card_collection_manager::db::setup;
+
+const COLLECTION_CSV: &str = include_str!("fixtures/collection.csv");
+
+#[test]
+fn test_import_reports_row_errors() {
+    let db = setup(":memory:").unwrap();
+    let rows = read_csv(COLLECTION_CSV.as_bytes()).unwrap();
+    let report = db
+        .import_csv_rows(rows, &CsvImportOptions::default())
+        .unwrap();
+
+    assert_eq!(report.inserted, 3);
+    assert_eq!(report.errors.len(), 2);
+    // unknown rarity
+    assert_eq!(report.errors[0].line, 4);
+    assert_eq!(report.errors[0].number.as_deref(), Some("LOB-053"));
+    // owned is not a number
+    assert_eq!(report.errors[1].line, 5);
+    assert_eq!(report.errors[1].number.as_deref(), Some("LOB-054"));
+
+    let cards = db.get_cards(None).unwrap();
+    assert_eq!(cards.len(), 3);
+    let blue_eyes = cards.iter().find(|c| c.number == "LOB-001").unwrap();
+    assert_eq!(blue_eyes.in_collection, 2);
+    assert_eq!(blue_eyes.cardtype.display(), "Normal Monster");
+    // unknown series are created
+    assert_eq!(db.get_unique_series().unwrap().len(), 2);
+}
+
+#[test]
+fn test_dry_run_changes_nothing() {
+    let db = setup(":memory:").unwrap();
+    let rows = read_csv(COLLECTION_CSV.as_bytes()).unwrap();
+    let options = CsvImportOptions {
+        dry_run: true,
+        upsert: false,
+        create_types: false,
+    };
+    let report = db.import_csv_rows(rows, &options).unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.inserted, 3);
+    assert!(db.get_cards(None).unwrap().is_empty());
+    assert!(db.get_unique_series().unwrap().is_empty());
+}
+
+#[test]
+fn test_upsert_by_number() {
+    let db = setup(":memory:").unwrap();
+    let rows = read_csv(COLLECTION_CSV.as_bytes()).unwrap();
+    db.import_csv_rows(rows, &CsvImportOptions::default())
+        .unwrap();
+
+    let update = "series,number,name,rarity,type,owned
+Legend of Blue Eyes White Dragon,LOB-001,Blue-Eyes White Dragon,Ultra Rare,Normal Monster,1
+Legend of Blue Eyes White Dragon,LOB-052,Dark Hole,Super Rare,Normal Spell Card,0
+Metal Raiders,MRD-001,Feral Imp,Rare,Normal Monster,3
+";
+    // without upsert existing numbers are errors
+    let report = db
+        .import_csv_rows(
+            read_csv(update.as_bytes()).unwrap(),
+            &CsvImportOptions::default(),
+        )
+        .unwrap();
+    assert_eq!(report.errors.len(), 3);
+
+    let options = CsvImportOptions {
+        dry_run: false,
+        upsert: true,
+        create_types: false,
+    };
+    let report = db
+        .import_csv_rows(read_csv(update.as_bytes()).unwrap(), &options)
+        .unwrap();
+    assert_eq!(report.updated, 2);
+    assert_eq!(report.unchanged, 1);
+    assert!(report.errors.is_empty());
+
+    let cards = db.get_cards(None).unwrap();
+    let feral_imp = cards.iter().find(|c| c.number == "MRD-001").unwrap();
+    assert_eq!(feral_imp.rarity.name, "Rare");
+    assert_eq!(feral_imp.in_collection, 3);
+    let blue_eyes = cards.iter().find(|c| c.number == "LOB-001").unwrap();
+    assert_eq!(blue_eyes.in_collection, 1);
+}
+
+#[test]
+fn test_export_round_trip() {
+    let db = setup(":memory:").unwrap();
+    let rows = read_csv(COLLECTION_CSV.as_bytes()).unwrap();
+    db.import_csv_rows(rows, &CsvImportOptions::default())
+        .unwrap();
+
+    let mut exported = Vec::new();
+    write_csv(&mut exported, &db.get_cards(None).unwrap()).unwrap();
+    let text = String::from_utf8(exported.clone()).unwrap();
+    assert!(text.starts_with("series,number,name,rarity,type,owned\n"));
+
+    let other = setup(":memory:").unwrap();
+    let report = other
+        .import_csv_rows(
+            read_csv(exported.as_slice()).unwrap(),
+            &CsvImportOptions::default(),
+        )
+        .unwrap();
+    assert_eq!(report.inserted, 3);
+    assert!(report.errors.is_empty());
+
+    let mut again = Vec::new();
+    write_csv(&mut again, &other.get_cards(None).unwrap()).unwrap();
+    assert_eq!(again, exported);
+}
+
+#[test]
+fn test_create_types_on_request() {
+    let db = setup(":memory:").unwrap();
+    let csv = "series,number,name,rarity,type,owned
+Hidden Arsenal,HA01-EN001,Gravity Warrior,Ultra Rare,Synchro Effect Monster,1
+Hidden Arsenal,HA01-EN002,Rocket Warrior,Common,Effect Monster,1
+";
+    // without the option the row is refused
+    let report = db
+        .import_csv_rows(
+            read_csv(csv.as_bytes()).unwrap(),
+            &CsvImportOptions::default(),
+        )
+        .unwrap();
+    assert!(report.created_card_types.is_empty());
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].number.as_deref(), Some("HA01-EN001"));
+
+    // a dry run lists the types it would create
+    let mut options = CsvImportOptions {
+        dry_run: true,
+        upsert: true,
+        create_types: true,
+    };
+    let report = db
+        .import_csv_rows(read_csv(csv.as_bytes()).unwrap(), &options)
+        .unwrap();
+    assert_eq!(report.created_card_types, vec!["Effect Synchro Monster"]);
+    assert!(report.errors.is_empty());
+    assert!(db.get_card_type_id("Synchro Effect Monster").is_err());
+
+    options.dry_run = false;
+    let report = db
+        .import_csv_rows(read_csv(csv.as_bytes()).unwrap(), &options)
+        .unwrap();
+    assert_eq!(report.inserted, 1);
+    assert_eq!(report.unchanged, 1);
+    assert!(db.get_card_type_id("Synchro Effect Monster").is_ok());
+}
@@ -1,7 +1,15 @@
-use card_collection_manager::card::DatabaseCard;
-use card_collection_manager::series::Series;
+use card_collection_manager::card::{
+    BatchMode, CardListOptions, CardSort, Condition, CopyAttributes, DatabaseCard, Edition,
+    SortOrder,
+};
+use card_collection_manager::cardtype::CardType;
+use card_collection_manager::deck::{DeckCardRequest, DeckSection};
+use card_collection_manager::identity::{Attribute, CardStats};
+use card_collection_manager::jsoncards::SeriesJson;
+use card_collection_manager::series::{Series, SeriesRegion};
+use card_collection_manager::want::NewWant;
 
-use card_collection_manager::db::setup;
+use card_collection_manager::db::{get_series_and_number, setup};
 
 #[test]
 fn test_insert_and_get_series() {
@@ -53,3 +61,874 @@ fn test_insert_and_get_card() {
     assert_eq!(cards.len(), 1);
     assert_eq!(cards[0].name, "Test Card");
 }
+
+fn setup_with_card() -> card_collection_manager::db::DatabaseConnection {
+    let db = setup(":memory:").unwrap();
+    let series = Series {
+        id: None,
+        name: "Test Series".into(),
+        release_date: "2025-09-05".into(),
+        n_cards: 10,
+        prefix: Some("TS".into()),
+    };
+    let series_id = db.insert_series(&series).unwrap();
+    for n in 1..=3 {
+        let card = DatabaseCard {
+            name: format!("Test Card {}", n),
+            series_id,
+            number: format!("TS-00{}", n),
+            collection_number: n,
+            in_collection: 0,
+            rarity_id: 1,
+            card_type_id: 1,
+        };
+        db.insert_card(&card).unwrap();
+    }
+    db
+}
+
+#[test]
+fn test_collect_records_copy_attributes() {
+    let db = setup_with_card();
+
+    let first_edition = CopyAttributes {
+        condition: Some(Condition::LightlyPlayed),
+        edition: Some(Edition::First),
+        language: Some("DE".into()),
+        notes: Some("signed".into()),
+        ..Default::default()
+    };
+    assert_eq!(db.collect_card("TS-001", None, &first_edition).unwrap(), 1);
+    assert_eq!(
+        db.collect_card("TS-001", Some(2), &CopyAttributes::default())
+            .unwrap(),
+        3
+    );
+
+    let copies = db.get_copies("TS-001").unwrap();
+    assert_eq!(copies.len(), 3);
+    assert_eq!(copies[0].condition, Condition::LightlyPlayed);
+    assert_eq!(copies[0].edition, Edition::First);
+    assert_eq!(copies[0].language, "DE");
+    assert_eq!(copies[0].notes.as_deref(), Some("signed"));
+    assert_eq!(copies[1].condition, Condition::NearMint);
+    assert_eq!(copies[1].edition, Edition::Unlimited);
+    assert_eq!(copies[1].language, "EN");
+
+    let cards = db.get_cards(Some("Test Card 1")).unwrap();
+    assert_eq!(cards[0].in_collection, 3);
+    assert_eq!(cards[0].copies.len(), 3);
+}
+
+#[test]
+fn test_sell_targets_matching_copies() {
+    let db = setup_with_card();
+    let lp = CopyAttributes {
+        condition: Some(Condition::LightlyPlayed),
+        ..Default::default()
+    };
+    db.collect_card("TS-001", Some(2), &CopyAttributes::default())
+        .unwrap();
+    db.collect_card("TS-001", None, &lp).unwrap();
+
+    assert_eq!(db.sell_card("TS-001", 1, &lp).unwrap(), 2);
+    assert!(
+        db.get_copies("TS-001")
+            .unwrap()
+            .iter()
+            .all(|c| c.condition == Condition::NearMint)
+    );
+
+    // no LP copy left, nothing may be removed
+    assert!(db.sell_card("TS-001", 1, &lp).is_err());
+    assert_eq!(db.get_copies("TS-001").unwrap().len(), 2);
+
+    assert_eq!(
+        db.sell_card("TS-001", 2, &CopyAttributes::default())
+            .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_collect_range_adds_copies() {
+    let db = setup_with_card();
+    db.collect_card("TS-001-003", None, &CopyAttributes::default())
+        .unwrap();
+    let cards = db.get_cards(None).unwrap();
+    assert!(cards.iter().all(|c| c.in_collection == 1));
+}
+
+#[test]
+fn test_collection_value() {
+    let db = setup_with_card();
+    let bought = |price: f64| CopyAttributes {
+        price: Some(price),
+        date: Some("2025-01-01".into()),
+        ..Default::default()
+    };
+    db.collect_card("TS-001", Some(2), &bought(1.5)).unwrap();
+    db.collect_card("TS-002", None, &bought(4.0)).unwrap();
+
+    db.insert_price("TS-001", &Condition::NearMint, 2.0, Some("2025-01-01"))
+        .unwrap();
+    db.insert_price("TS-001", &Condition::NearMint, 3.0, Some("2025-02-01"))
+        .unwrap();
+    db.insert_price("TS-001", &Condition::LightlyPlayed, 100.0, None)
+        .unwrap();
+    assert!(
+        db.insert_price("XX-999", &Condition::NearMint, 1.0, None)
+            .is_err()
+    );
+    assert_eq!(db.get_prices("TS-001").unwrap().len(), 3);
+
+    let sold = CopyAttributes {
+        price: Some(5.0),
+        date: Some("2025-03-01".into()),
+        ..Default::default()
+    };
+    assert_eq!(db.sell_card("TS-001", 1, &sold).unwrap(), 1);
+
+    let value = db.get_collection_value().unwrap();
+    assert_eq!(value.series.len(), 1);
+    let total = value.total;
+    assert_eq!(total.owned_copies, 2);
+    assert_eq!(total.sold_copies, 1);
+    assert_eq!(total.unpriced_copies, 1); // TS-002 has no market price
+    assert_eq!(total.market_value, 3.0); // latest NM price of TS-001
+    assert_eq!(total.cost_basis, 5.5);
+    assert_eq!(total.unrealized_profit, -2.5);
+    assert_eq!(total.realized_profit, 3.5);
+}
+
+#[test]
+fn test_invalid_purchase_date_is_rejected() {
+    let db = setup_with_card();
+    let copy = CopyAttributes {
+        date: Some("01/02/2025".into()),
+        ..Default::default()
+    };
+    assert!(db.collect_card("TS-001", None, &copy).is_err());
+}
+
+#[test]
+fn test_wants_and_missing_report() {
+    let db = setup_with_card();
+    let want = |number: &str, quantity: i32| NewWant {
+        number: number.into(),
+        quantity,
+        max_price: Some(2.5),
+        priority: 1,
+    };
+
+    db.upsert_want(&want("TS-001", 3)).unwrap();
+    db.upsert_want(&want("TS-002", 1)).unwrap();
+    assert!(db.upsert_want(&want("XX-001", 1)).is_err());
+    assert!(db.upsert_want(&want("TS-003", 0)).is_err());
+
+    db.collect_card("TS-001", None, &CopyAttributes::default())
+        .unwrap();
+    db.collect_card("TS-002", None, &CopyAttributes::default())
+        .unwrap();
+
+    // updating replaces the existing want
+    let updated = db.upsert_want(&want("TS-001", 4)).unwrap();
+    assert_eq!(updated.quantity, 4);
+    assert_eq!(db.get_wants().unwrap().len(), 2);
+
+    let missing = db.get_missing_wants(None).unwrap();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].number, "TS-001");
+    assert_eq!(missing[0].missing(), 3);
+    assert!(
+        db.get_missing_wants(Some("Other Series"))
+            .unwrap()
+            .is_empty()
+    );
+
+    db.delete_want("TS-001").unwrap();
+    assert!(db.delete_want("TS-001").is_err());
+    assert!(db.get_missing_wants(None).unwrap().is_empty());
+}
+
+#[test]
+fn test_deck_check_against_collection() {
+    let db = setup_with_card();
+    // second printing of the same card name
+    db.insert_card(&DatabaseCard {
+        name: "Test Card 1".into(),
+        series_id: 1,
+        number: "TS-004".into(),
+        collection_number: 4,
+        in_collection: 1,
+        rarity_id: 2,
+        card_type_id: 1,
+    })
+    .unwrap();
+    db.collect_card("TS-001", None, &CopyAttributes::default())
+        .unwrap();
+
+    let entry = |name: &str, section: DeckSection, quantity: i32| DeckCardRequest {
+        card_name: name.into(),
+        section,
+        quantity,
+    };
+
+    db.create_deck("Other").unwrap();
+    db.add_deck_card("Other", &entry("Test Card 1", DeckSection::Main, 1))
+        .unwrap();
+
+    db.create_deck("Mine").unwrap();
+    assert!(db.create_deck("Mine").is_err());
+    db.add_deck_card("mine", &entry("test card 1", DeckSection::Main, 2))
+        .unwrap();
+    db.add_deck_card("Mine", &entry("Test Card 1", DeckSection::Side, 1))
+        .unwrap();
+    db.add_deck_card("Mine", &entry("Test Card 2", DeckSection::Extra, 1))
+        .unwrap();
+
+    let deck = db.get_deck("Mine").unwrap();
+    assert_eq!(deck.entries.len(), 3);
+    assert_eq!(deck.entries[0].card_name, "Test Card 1"); // spelling from the cards table
+    assert_eq!(deck.entries[1].section, DeckSection::Extra);
+
+    let check = db.check_deck("Mine").unwrap();
+    assert!(!check.is_complete());
+    // two owned copies over two printings, one of them used by "Other"
+    let main = &check.entries[0];
+    assert_eq!(main.owned, 2);
+    assert_eq!(main.printings, vec!["TS-001", "TS-004"]);
+    assert_eq!(main.missing, 0);
+    assert_eq!(main.committed, 1);
+    assert_eq!(main.other_decks[0].deck, "Other");
+    let extra = &check.entries[1];
+    assert_eq!(extra.missing, 1);
+    let side = &check.entries[2];
+    assert_eq!(side.missing, 1);
+
+    db.remove_deck_card("Mine", &entry("Test Card 2", DeckSection::Extra, 1))
+        .unwrap();
+    assert_eq!(db.get_deck("Mine").unwrap().entries.len(), 2);
+    assert!(
+        db.remove_deck_card("Mine", &entry("Test Card 2", DeckSection::Extra, 1))
+            .is_err()
+    );
+
+    db.delete_deck("Other").unwrap();
+    assert!(db.check_deck("Other").is_err());
+    assert_eq!(db.check_deck("Mine").unwrap().entries[0].committed, 0);
+}
+
+#[test]
+fn test_collect_range_is_all_or_nothing() {
+    let db = setup_with_card();
+    // TS-004 does not exist, so none of the range may be collected
+    assert!(
+        db.collect_card("TS-001-004", None, &CopyAttributes::default())
+            .is_err()
+    );
+    assert!(
+        db.get_cards(None)
+            .unwrap()
+            .iter()
+            .all(|c| c.in_collection == 0)
+    );
+
+    let ids = vec!["TS-001".to_string(), "TS-009".to_string()];
+    assert!(
+        db.collect_cards(
+            &ids,
+            Some(2),
+            &CopyAttributes::default(),
+            BatchMode::AllOrNothing
+        )
+        .is_err()
+    );
+    assert!(db.get_copies("TS-001").unwrap().is_empty());
+}
+
+#[test]
+fn test_best_effort_reports_every_card() {
+    let db = setup_with_card();
+    let ids = vec!["TS-001-002".to_string(), "TS-009".to_string()];
+    let results = db
+        .collect_cards(
+            &ids,
+            Some(2),
+            &CopyAttributes::default(),
+            BatchMode::BestEffort,
+        )
+        .unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].number, "TS-001");
+    assert_eq!(results[0].in_collection, Some(2));
+    assert_eq!(results[1].in_collection, Some(2));
+    assert!(!results[2].is_ok());
+    assert!(results[2].error.as_ref().unwrap().contains("TS-009"));
+
+    // TS-003 has no copies to sell, the others still are sold
+    let ids = vec!["TS-001-003".to_string()];
+    let results = db
+        .sell_cards(&ids, 1, &CopyAttributes::default(), BatchMode::BestEffort)
+        .unwrap();
+    assert_eq!(
+        results.iter().map(|r| r.in_collection).collect::<Vec<_>>(),
+        vec![Some(1), Some(1), None]
+    );
+
+    assert!(
+        db.sell_cards(&ids, 1, &CopyAttributes::default(), BatchMode::AllOrNothing)
+            .is_err()
+    );
+    assert_eq!(db.get_copies("TS-001").unwrap().len(), 1);
+}
+
+#[test]
+fn test_collect_card_number_spec() {
+    let db = setup_with_card();
+    let ids = vec!["TS-001..002,003".to_string()];
+    let results = db
+        .collect_cards(
+            &ids,
+            None,
+            &CopyAttributes::default(),
+            BatchMode::AllOrNothing,
+        )
+        .unwrap();
+    assert_eq!(results.len(), 3);
+    assert!(
+        db.get_cards(None)
+            .unwrap()
+            .iter()
+            .all(|c| c.in_collection == 1)
+    );
+
+    // a malformed spec is reported, not looked up as a card number
+    let ids = vec!["TS-003..001".to_string(), "TS-001".to_string()];
+    let results = db
+        .collect_cards(
+            &ids,
+            None,
+            &CopyAttributes::default(),
+            BatchMode::BestEffort,
+        )
+        .unwrap();
+    assert!(
+        results[0]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("ends before it starts")
+    );
+    assert_eq!(results[1].in_collection, Some(2));
+}
+
+#[test]
+fn test_rarity_and_card_type_usage() {
+    let db = setup_with_card();
+    db.collect_card("TS-001", Some(2), &CopyAttributes::default())
+        .unwrap();
+
+    let rarities = db.get_rarities().unwrap();
+    assert_eq!(rarities.len(), 7);
+    assert_eq!(rarities[0].rarity.name, "Common");
+    assert_eq!(rarities[0].cards, 3);
+    assert_eq!(rarities[0].owned_copies, 2);
+    assert!(
+        rarities[1..]
+            .iter()
+            .all(|r| r.cards == 0 && r.owned_copies == 0)
+    );
+
+    let card_types = db.get_card_types().unwrap();
+    let used: Vec<_> = card_types.iter().filter(|t| t.cards > 0).collect();
+    assert_eq!(used.len(), 1);
+    assert_eq!(used[0].id, 1);
+    assert_eq!(used[0].display, "Normal Spell Card");
+    assert_eq!(used[0].cards, 3);
+    assert_eq!(used[0].owned_copies, 2);
+}
+
+#[test]
+fn test_completion_stats() {
+    let db = setup_with_card();
+    db.collect_card("TS-001", Some(3), &CopyAttributes::default())
+        .unwrap();
+    db.collect_card("TS-002", None, &CopyAttributes::default())
+        .unwrap();
+    db.sell_card("TS-002", 1, &CopyAttributes::default())
+        .unwrap();
+    db.collect_card("TS-003", None, &CopyAttributes::default())
+        .unwrap();
+
+    let stats = db.get_completion_stats().unwrap();
+    assert_eq!(stats.series.len(), 1);
+    let series = &stats.series[0];
+    assert_eq!(series.prefix, "TS");
+    assert_eq!(series.n_cards, 10);
+    assert_eq!(series.cards_defined, 3);
+    assert_eq!(series.owned_cards, 2); // the copy of TS-002 was sold
+    assert_eq!(series.total_copies, 4);
+    assert_eq!(series.duplicates, 2);
+    assert_eq!(series.completion, 20.0);
+    assert_eq!(series.rarities.len(), 1);
+    assert_eq!(series.rarities[0].rarity, "Common");
+    assert_eq!(series.rarities[0].cards, 3);
+    assert_eq!(series.rarities[0].owned_cards, 2);
+    assert_eq!(stats.total.owned_cards, 2);
+    assert_eq!(stats.completed_series, 0);
+
+    // a German printing of slot 1 and one of a slot only printed in German
+    for (number, collection_number) in [("TS-DE001", 1), ("TS-DE004", 4)] {
+        db.insert_card(&DatabaseCard {
+            name: format!("Test Card {}", collection_number),
+            series_id: 1,
+            number: number.into(),
+            collection_number,
+            in_collection: 0,
+            rarity_id: 1,
+            card_type_id: 1,
+        })
+        .unwrap();
+        db.collect_card(number, None, &CopyAttributes::default())
+            .unwrap();
+    }
+    let stats = db.get_completion_stats().unwrap();
+    let series = &stats.series[0];
+    assert_eq!(series.cards_defined, 4);
+    assert_eq!(series.owned_cards, 3);
+    assert_eq!(series.total_copies, 6);
+    // the German copy of slot 1 is a duplicate of the three copies of TS-001
+    assert_eq!(series.duplicates, 3);
+    assert_eq!(series.completion, 30.0);
+    assert_eq!(series.rarities[0].cards, 4);
+    assert_eq!(series.rarities[0].owned_cards, 3);
+}
+
+#[test]
+fn test_series_rarity_and_card_type_crud() {
+    let db = setup_with_card();
+
+    let new_series = Series {
+        id: None,
+        name: "Second Series".into(),
+        release_date: "2025-10-01".into(),
+        n_cards: 5,
+        prefix: Some("SS".into()),
+    };
+    let created = db.create_series(&new_series).unwrap();
+    assert_eq!(created.release_date, "2025-10-01");
+    assert!(db.create_series(&new_series).is_err()); // duplicate name
+    let bad_date = Series {
+        name: "Third Series".into(),
+        release_date: "October 1st".into(),
+        ..new_series.clone()
+    };
+    assert!(db.create_series(&bad_date).is_err());
+
+    let id = created.id.unwrap();
+    let renamed = Series {
+        n_cards: 6,
+        ..created.clone()
+    };
+    assert_eq!(db.update_series(id, &renamed).unwrap().n_cards, 6);
+    db.delete_series(id).unwrap();
+    assert!(db.get_series_by_id(id).is_err());
+    // the test series still has cards
+    let err = db.delete_series(1).unwrap_err();
+    assert!(err.to_string().contains("has 3 cards"));
+
+    let rarity = db.create_rarity("Ghost Rare").unwrap();
+    assert_eq!(rarity.cards, 0);
+    assert!(db.create_rarity("Ghost Rare").is_err());
+    assert!(db.rename_rarity(rarity.rarity.id, "Common").is_err());
+    let rarity = db.rename_rarity(rarity.rarity.id, "Ghost").unwrap();
+    assert_eq!(rarity.rarity.name, "Ghost");
+    db.delete_rarity(rarity.rarity.id).unwrap();
+    assert!(db.delete_rarity(1).is_err()); // used by the test cards
+
+    let xyz = CardType {
+        main: "Xyz Monster".into(),
+        sub: "Effect".into(),
+    };
+    let cardtype = db.create_card_type(&xyz).unwrap();
+    assert_eq!(cardtype.display, "Effect Xyz Monster");
+    assert!(db.create_card_type(&xyz).is_err());
+    let cardtype = db
+        .update_card_type(
+            cardtype.id,
+            &CardType {
+                main: "Monster".into(),
+                sub: "Ritual".into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(cardtype.display, "Ritual Monster");
+    db.delete_card_type(cardtype.id).unwrap();
+    assert!(db.delete_card_type(1).is_err());
+}
+
+#[test]
+fn test_series_regions_and_slots() {
+    let db = setup_with_card();
+    for (number, rarity_id) in [("TS-DE001", 2), ("TS-EN001", 1), ("TS-DE002", 1)] {
+        let (_, collection_number) = get_series_and_number(number);
+        db.insert_card(&DatabaseCard {
+            name: "Test Card".into(),
+            series_id: 1,
+            number: number.into(),
+            collection_number,
+            in_collection: 0,
+            rarity_id,
+            card_type_id: 1,
+        })
+        .unwrap();
+    }
+    db.collect_card("TS-DE001", Some(1), &CopyAttributes::default())
+        .unwrap();
+
+    // cards add the region of their number, dated and sized like the series
+    let regions = db.get_series_regions(1).unwrap();
+    let codes: Vec<&str> = regions.iter().map(|r| r.region.region.as_str()).collect();
+    assert_eq!(codes, vec!["", "DE", "EN"]);
+    let de = &regions[1];
+    assert_eq!(de.region.language, "DE");
+    assert_eq!(de.region.release_date.as_deref(), Some("2025-09-05"));
+    assert_eq!((de.cards, de.owned_cards, de.region.n_cards), (2, 1, 10));
+    assert_eq!(de.rarities.len(), 2);
+
+    // the same slot in every region, collected separately
+    let slot: Vec<(String, String, i32)> = db
+        .get_slot_cards("TS-EN001")
+        .unwrap()
+        .into_iter()
+        .map(|c| (c.number, c.region, c.in_collection))
+        .collect();
+    assert_eq!(
+        slot,
+        vec![
+            ("TS-001".into(), "".into(), 0),
+            ("TS-DE001".into(), "DE".into(), 1),
+            ("TS-EN001".into(), "EN".into(), 0),
+        ]
+    );
+    assert!(db.get_slot_cards("TS-999").is_err());
+
+    // a region without cards yet, its language from the region code
+    let fr = db
+        .create_series_region(&SeriesRegion {
+            id: None,
+            series_id: 1,
+            region: "f".into(),
+            language: "".into(),
+            release_date: Some("2025-11-01".into()),
+            n_cards: 8,
+        })
+        .unwrap();
+    assert_eq!((fr.region.as_str(), fr.language.as_str()), ("F", "FR"));
+    assert!(db.create_series_region(&fr).is_err()); // duplicate
+    let bad = SeriesRegion {
+        region: "F1".into(),
+        ..fr.clone()
+    };
+    assert!(db.create_series_region(&bad).is_err());
+
+    let id = fr.id.unwrap();
+    let updated = SeriesRegion {
+        n_cards: 9,
+        ..fr.clone()
+    };
+    assert_eq!(db.update_series_region(id, &updated).unwrap().n_cards, 9);
+    let moved = SeriesRegion {
+        region: "IT".into(),
+        ..fr.clone()
+    };
+    assert!(db.update_series_region(id, &moved).is_err());
+    let looked_up = db.get_series_region_by_code("test series", "f").unwrap();
+    assert_eq!(looked_up.id, Some(id));
+
+    db.delete_series_region(id).unwrap();
+    assert!(db.get_series_region(id).is_err());
+    let err = db.delete_series_region(de.region.id.unwrap()).unwrap_err();
+    assert!(err.to_string().contains("has 2 cards"), "{}", err);
+}
+
+#[test]
+fn test_printings_share_their_card_identity() {
+    let db = setup_with_card();
+    // a second printing of Test Card 1 in another rarity, one copy owned
+    db.insert_card(&DatabaseCard {
+        name: "test card 1".into(),
+        series_id: 1,
+        number: "TS-004".into(),
+        collection_number: 4,
+        in_collection: 1,
+        rarity_id: 2,
+        card_type_id: 1,
+    })
+    .unwrap();
+    assert_eq!(db.get_card("TS-004").unwrap().name, "Test Card 1");
+
+    let identities = db.get_identities(Some("card 1"), None).unwrap();
+    assert_eq!(identities.len(), 1);
+    let card = &identities[0];
+    assert_eq!(card.owned_copies, 1);
+    let printings: Vec<(&str, i32)> = card
+        .printings
+        .iter()
+        .map(|p| (p.number.as_str(), p.in_collection))
+        .collect();
+    assert_eq!(printings, vec![("TS-001", 0), ("TS-004", 1)]);
+    assert_eq!(
+        db.get_identity(card.identity.id).unwrap().printings.len(),
+        2
+    );
+    assert!(db.get_identity(99).is_err());
+
+    // "do I own any Test Card 2?"
+    assert!(
+        db.get_identities(Some("card 2"), Some(true))
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(db.get_identities(None, Some(false)).unwrap().len(), 2);
+
+    // passcode and text belong to the card, not the printing
+    db.set_card_passcode("TS-001", 12345).unwrap();
+    db.set_card_text("TS-004", "Shared text").unwrap();
+    let other = db.get_card("TS-004").unwrap();
+    assert_eq!(other.passcode, Some(12345));
+    assert_eq!(
+        db.get_card("TS-001").unwrap().text.as_deref(),
+        Some("Shared text")
+    );
+
+    // renaming a printing makes it a printing of another card
+    let mut renamed = db.get_database_card("TS-004").unwrap();
+    renamed.name = "Test Card 2".into();
+    db.update_card("TS-004", &renamed).unwrap();
+    let moved = db.get_card("TS-004").unwrap();
+    assert_eq!(
+        moved.identity_id,
+        db.get_card("TS-002").unwrap().identity_id
+    );
+    assert_eq!(moved.passcode, None);
+    assert_eq!(
+        db.get_identities(Some("card 1"), None).unwrap()[0]
+            .printings
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_renames_fix_and_clean_up_identities() {
+    let db = setup_with_card();
+    db.set_card_passcode("TS-001", 12345).unwrap();
+
+    // a case-only rename fixes the name of the card
+    let mut card = db.get_database_card("TS-001").unwrap();
+    card.name = "TEST CARD 1".into();
+    db.update_card("TS-001", &card).unwrap();
+    let renamed = db.get_card("TS-001").unwrap();
+    assert_eq!(renamed.name, "TEST CARD 1");
+    assert_eq!(renamed.passcode, Some(12345));
+
+    // the only printing renames its card too
+    card.name = "Other Card".into();
+    db.update_card("TS-001", &card).unwrap();
+    assert_eq!(db.get_card("TS-001").unwrap().passcode, Some(12345));
+    assert!(db.get_identities(Some("card 1"), None).unwrap().is_empty());
+
+    // a rename to a known card leaves no card without printings behind
+    card.name = "Test Card 2".into();
+    db.update_card("TS-001", &card).unwrap();
+    assert!(db.get_identities(Some("other"), None).unwrap().is_empty());
+
+    // neither does a failed update or insert
+    let mut taken = db.get_database_card("TS-003").unwrap();
+    taken.name = "Fresh Name".into();
+    taken.number = "TS-002".into();
+    assert!(db.update_card("TS-003", &taken).is_err());
+    taken.number = "TS-003".into();
+    db.insert_card(&taken).unwrap();
+    assert!(db.get_identities(Some("fresh"), None).unwrap().is_empty());
+    assert_eq!(db.get_card("TS-003").unwrap().name, "Test Card 3");
+
+    // nor does deleting the last printing
+    db.delete_card("TS-003").unwrap();
+    assert!(db.get_identities(Some("card 3"), None).unwrap().is_empty());
+    assert_eq!(db.get_identities(None, None).unwrap().len(), 1);
+}
+
+#[test]
+fn test_card_crud() {
+    let db = setup_with_card();
+    let mut card = DatabaseCard {
+        name: "New Card".into(),
+        series_id: 1,
+        number: "TS-004".into(),
+        collection_number: 4,
+        in_collection: 0,
+        rarity_id: 2,
+        card_type_id: 5,
+    };
+    let created = db.create_card(&card).unwrap();
+    assert_eq!(created.rarity.name, "Rare");
+    assert!(db.create_card(&card).is_err()); // number exists
+    let unknown_series = DatabaseCard {
+        number: "TS-005".into(),
+        series_id: 99,
+        ..card.clone()
+    };
+    assert!(db.create_card(&unknown_series).is_err());
+
+    card.number = "TS-003".into();
+    let err = db.update_card("TS-004", &card).unwrap_err();
+    assert!(err.to_string().contains("already exists"));
+    card.number = "TS-005".into();
+    db.update_card("TS-004", &card).unwrap();
+    assert_eq!(db.get_card("TS-005").unwrap().name, "New Card");
+    assert!(db.get_card("TS-004").is_err());
+
+    db.collect_card("TS-005", None, &CopyAttributes::default())
+        .unwrap();
+    db.sell_card("TS-005", 1, &CopyAttributes::default())
+        .unwrap();
+    // sold copies are history and keep the card
+    let err = db.delete_card("TS-005").unwrap_err();
+    assert!(err.to_string().contains("1 copies"));
+    db.delete_card("TS-002").unwrap();
+    assert!(db.get_card("TS-002").is_err());
+    assert!(db.delete_card("TS-002").is_err());
+
+    // a failing step rolls back the earlier writes of a transaction
+    card.number = "TS-006".into();
+    let err = db
+        .transaction(|db| {
+            db.create_card(&card)?;
+            db.set_card_text("TS-404", "Lost")
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("TS-404"));
+    assert!(db.get_card("TS-006").is_err());
+}
+
+#[test]
+fn test_list_cards_filters_sorts_and_pages() {
+    let db = setup_with_card();
+    db.collect_card("TS-002", Some(2), &CopyAttributes::default())
+        .unwrap();
+    let rare = DatabaseCard {
+        name: "Rare Card".into(),
+        series_id: 1,
+        number: "TS-004".into(),
+        collection_number: 4,
+        in_collection: 0,
+        rarity_id: 2,
+        card_type_id: 11,
+    };
+    db.insert_card(&rare).unwrap();
+
+    let page = db
+        .list_cards(&CardListOptions {
+            limit: Some(2),
+            offset: 1,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(page.total, 4);
+    let numbers: Vec<_> = page.cards.iter().map(|c| c.number.as_str()).collect();
+    assert_eq!(numbers, ["TS-002", "TS-003"]);
+    assert_eq!(page.cards[0].copies.len(), 2);
+
+    let page = db
+        .list_cards(&CardListOptions {
+            sort: CardSort::Owned,
+            order: SortOrder::Desc,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(page.cards[0].number, "TS-002");
+
+    let filtered = |options: CardListOptions| {
+        db.list_cards(&options)
+            .unwrap()
+            .cards
+            .into_iter()
+            .map(|c| c.number)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        filtered(CardListOptions {
+            rarity: Some("rare, super rare".into()),
+            ..Default::default()
+        }),
+        ["TS-004"]
+    );
+    assert_eq!(
+        filtered(CardListOptions {
+            cardtype: Some("trap card".into()),
+            ..Default::default()
+        }),
+        ["TS-004"]
+    );
+    assert_eq!(
+        filtered(CardListOptions {
+            owned: Some(false),
+            series_id: Some(1),
+            name: Some("test".into()),
+            ..Default::default()
+        }),
+        ["TS-001", "TS-003"]
+    );
+    assert!(
+        db.list_cards(&CardListOptions {
+            limit: Some(0),
+            ..Default::default()
+        })
+        .is_err()
+    );
+}
+
+#[test]
+fn test_json_import_sets_card_stats() {
+    let db = setup(":memory:").unwrap();
+    let series: SeriesJson = serde_json::from_value(serde_json::json!({
+        "name": "Legend of Blue Eyes White Dragon",
+        "ncards": 2,
+        "release_date": "March 8, 2002",
+        "prefix": "LOB",
+        "cards": [
+            {"card_number": "LOB-005", "name": "Dark Magician", "rarity": "Ultra Rare",
+             "category": "Normal Monster", "attribute": "DARK", "level": 7,
+             "monster_type": "Spellcaster", "atk": 2500, "def": 2100},
+            {"card_number": "LOB-100", "name": "Trap Hole", "rarity": "Common",
+             "category": "Normal Trap Card"}
+        ]
+    }))
+    .unwrap();
+    db.add_series_json(series).unwrap();
+
+    let magician = db.get_card("LOB-005").unwrap().stats;
+    assert_eq!(magician.attribute, Some(Attribute::Dark));
+    assert_eq!(magician.stars().as_deref(), Some("Level 7"));
+    assert_eq!(magician.monster_type.as_deref(), Some("Spellcaster"));
+    assert_eq!((magician.atk, magician.def), (Some(2500), Some(2100)));
+    assert!(db.get_card("LOB-100").unwrap().stats.is_empty());
+
+    // stats left empty keep their value, impossible ones are refused
+    let stats = CardStats {
+        atk: Some(2800),
+        ..Default::default()
+    };
+    db.set_card_stats("LOB-005", &stats).unwrap();
+    let magician = db.get_card("LOB-005").unwrap().stats;
+    assert_eq!((magician.level, magician.atk), (Some(7), Some(2800)));
+    let stats = CardStats {
+        link_rating: Some(9),
+        ..Default::default()
+    };
+    assert!(db.set_card_stats("LOB-005", &stats).is_err());
+    assert!(db.set_card_stats("LOB-999", &CardStats::default()).is_err());
+}
@@ -0,0 +1,210 @@
+use card_collection_manager::db::setup;
+use card_collection_manager::fandom::{SetPage, filter_rarity};
+
+const LOB: &str = include_str!("fixtures/fandom_lob.html");
+const SINGLE_REGION: &str = include_str!("fixtures/fandom_single_region.html");
+
+#[test]
+fn test_parse_set_page() {
+    let page = SetPage::parse(LOB).unwrap();
+    assert_eq!(page.name, "Legend of Blue Eyes White Dragon");
+
+    // footnote markers are dropped, other infobox sections ignored
+    let dates: Vec<(&str, &str)> = page
+        .release_dates
+        .iter()
+        .map(|d| (d.region.as_str(), d.date.as_str()))
+        .collect();
+    assert_eq!(
+        dates,
+        vec![
+            ("North American", "March 8, 2002"),
+            ("European", "October 17, 2002"),
+            ("French", "October 24, 2002"),
+        ]
+    );
+
+    // the gallery tabber has no card list
+    let regions: Vec<(&str, bool, usize)> = page
+        .card_lists
+        .iter()
+        .map(|l| (l.region.as_str(), l.current, l.cards.len()))
+        .collect();
+    assert_eq!(
+        regions,
+        vec![
+            ("English (na)", true, 8),
+            ("English (eu)", false, 2),
+            ("French", false, 1),
+        ]
+    );
+}
+
+#[test]
+fn test_series_json_of_the_current_tab() {
+    let series = SetPage::parse(LOB).unwrap().to_series_json(None).unwrap();
+    assert_eq!(series.name, "Legend of Blue Eyes White Dragon");
+    assert_eq!(series.release_date, "March 8, 2002");
+    assert_eq!(series.ncards, 8);
+    assert_eq!(series.prefix.as_deref(), Some("LOB"));
+
+    let cards: Vec<(&str, &str, &str, &str)> = series
+        .cards
+        .iter()
+        .map(|c| {
+            (
+                c.card_number.as_str(),
+                c.name.as_str(),
+                c.rarity.as_str(),
+                c.category.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        cards,
+        vec![
+            (
+                "LOB-EN001",
+                "Blue-Eyes White Dragon",
+                "Ultra Rare",
+                "Normal Monster"
+            ),
+            ("LOB-EN002", "Hitotsu-Me Giant", "Common", "Normal Monster"),
+            (
+                "LOB-EN003",
+                "Flame Swordsman",
+                "Super Rare",
+                "Fusion Monster"
+            ),
+            ("LOB-EN004", "Skull Servant", "Common", "Normal Monster"),
+            ("LOB-EN005", "Dark Magician", "Ultra Rare", "Normal Monster"),
+            (
+                "LOB-EN006",
+                "Gaia The Fierce Knight",
+                "Ultra Rare",
+                "Normal Monster"
+            ),
+            ("LOB-EN119", "Sogen", "Common", "Field Spell Card"),
+            ("LOB-EN124", "Trap Hole", "Super Rare", "Normal Trap Card"),
+        ]
+    );
+}
+
+#[test]
+fn test_series_json_per_region() {
+    let page = SetPage::parse(LOB).unwrap();
+
+    let eu = page.to_series_json(Some("english (EU)")).unwrap();
+    assert_eq!(eu.release_date, "October 17, 2002");
+    assert_eq!(eu.cards[0].card_number, "LOB-E001");
+    assert_eq!(eu.prefix.as_deref(), Some("LOB"));
+
+    // the English name column, not the French one
+    let fr = page.to_series_json(Some("French")).unwrap();
+    assert_eq!(fr.release_date, "October 24, 2002");
+    assert_eq!(fr.cards[0].name, "Blue-Eyes White Dragon");
+    assert_eq!(fr.cards[0].rarity, "Ultra Rare");
+
+    let err = page.to_series_json(Some("German")).unwrap_err();
+    assert!(
+        err.contains("English (na), English (eu), French"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_page_without_tabs() {
+    let series = SetPage::parse(SINGLE_REGION)
+        .unwrap()
+        .to_series_json(None)
+        .unwrap();
+    assert_eq!(series.name, "Starter Deck: Kaiba");
+    assert_eq!(series.prefix.as_deref(), Some("SDK"));
+    assert_eq!(series.ncards, 2);
+    // header row in the body is skipped, the English name is taken over the Japanese one
+    assert_eq!(series.cards[0].name, "Blue-Eyes White Dragon");
+    assert_eq!(series.cards[1].category, "Normal Monster");
+}
+
+#[test]
+fn test_filter_rarity() {
+    assert_eq!(filter_rarity("Common\nShort Print"), "Common");
+    assert_eq!(filter_rarity("Common\nSuper Short Print"), "Common");
+    assert_eq!(filter_rarity("Ultra Rare\nSuper Rare"), "Ultra Rare");
+    assert_eq!(filter_rarity(" Secret Rare "), "Secret Rare");
+    assert_eq!(filter_rarity(""), "");
+}
+
+#[test]
+fn test_pages_that_are_no_set_page() {
+    let err = SetPage::parse("<html><body><p>Not found</p></body></html>").unwrap_err();
+    assert!(err.contains("set name"), "{}", err);
+
+    let no_list = SINGLE_REGION.replace("card-list", "other-list");
+    let err = SetPage::parse(&no_list).unwrap_err();
+    assert!(err.contains("card list"), "{}", err);
+}
+
+#[test]
+fn test_import_set_page() {
+    let db = setup(":memory:").unwrap();
+    let series = SetPage::parse(LOB).unwrap().to_series_json(None).unwrap();
+    let (series_id, inserted) = db.add_series_json(series).unwrap();
+    assert_eq!(inserted, 8);
+
+    let stored = db.get_series_by_id(series_id).unwrap();
+    assert_eq!(stored.name, "Legend of Blue Eyes White Dragon");
+    assert_eq!(stored.n_cards, 8);
+    assert_eq!(stored.prefix.as_deref(), Some("LOB"));
+
+    let sogen = db.get_card("LOB-EN119").unwrap();
+    assert_eq!(sogen.rarity.name, "Common");
+    assert_eq!(sogen.cardtype.main, "Spell Card");
+    assert_eq!(sogen.cardtype.sub, "Field");
+    assert_eq!(sogen.collection_number, 119);
+}
+
+#[test]
+fn test_import_every_region_into_one_series() {
+    let db = setup(":memory:").unwrap();
+    let page = SetPage::parse(LOB).unwrap();
+    for list in &page.card_lists {
+        let series = page.to_series_json(Some(&list.region)).unwrap();
+        db.add_series_json(series).unwrap();
+    }
+    let series_id = db
+        .get_series_id_by_name("Legend of Blue Eyes White Dragon")
+        .unwrap();
+
+    // each tab is a region with the release date of its infobox entry
+    let regions: Vec<(String, String, Option<String>, i32)> = db
+        .get_series_regions(series_id)
+        .unwrap()
+        .into_iter()
+        .map(|r| {
+            (
+                r.region.region,
+                r.region.language,
+                r.region.release_date,
+                r.cards,
+            )
+        })
+        .collect();
+    assert_eq!(
+        regions,
+        vec![
+            ("EN".into(), "EN".into(), Some("2002-03-08".into()), 8),
+            ("E".into(), "EN".into(), Some("2002-10-17".into()), 2),
+            ("F".into(), "FR".into(), Some("2002-10-24".into()), 1),
+        ]
+    );
+
+    let slot: Vec<String> = db
+        .get_slot_cards("LOB-EN001")
+        .unwrap()
+        .into_iter()
+        .map(|c| c.number)
+        .collect();
+    assert_eq!(slot, vec!["LOB-E001", "LOB-EN001", "LOB-F001"]);
+}
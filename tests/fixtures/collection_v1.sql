@@ -0,0 +1,8 @@
+-- Small collection in the version 1 schema, loaded before upgrading fixtures
+INSERT INTO rarity (name) VALUES ('Common'), ('Ultra Rare');
+INSERT INTO card_type (maintype, subtype) VALUES ('Monster', 'Normal'), ('Spell Card', 'Normal');
+INSERT INTO series (name, release_date, prefix, n_cards)
+    VALUES ('Legend of Blue Eyes White Dragon', '2002-03-08', 'LOB', 126);
+INSERT INTO cards (name, series_id, collection_number, number, in_collection, rarity_id, card_type_id)
+    VALUES ('Blue-Eyes White Dragon', 1, 1, 'LOB-001', 2, 2, 1),
+           ('Dark Hole', 1, 52, 'LOB-052', 0, 1, 2);
@@ -0,0 +1,34 @@
+-- Database as created by releases before schema versioning (user_version 0)
+CREATE TABLE rarity (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE card_type (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    maintype TEXT NOT NULL,
+    subtype TEXT,
+    UNIQUE (maintype, subtype)
+);
+
+CREATE TABLE series (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    release_date DATE NOT NULL,
+    prefix TEXT,
+    n_cards INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE cards (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    series_id INTEGER NOT NULL,
+    collection_number INTEGER NOT NULL,
+    number TEXT NOT NULL UNIQUE,
+    in_collection INTEGER NOT NULL DEFAULT 0,
+    rarity_id INTEGER NOT NULL,
+    card_type_id INTEGER NOT NULL,
+    FOREIGN KEY (rarity_id) REFERENCES rarity(id)
+    FOREIGN KEY (series_id) REFERENCES series(id)
+    FOREIGN KEY (card_type_id) REFERENCES card_type(id)
+);
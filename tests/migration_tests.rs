@@ -0,0 +1,283 @@
+use std::path::{Path, PathBuf};
+
+use card_collection_manager::card::DatabaseCard;
+use card_collection_manager::db::{DatabaseConnection, setup};
+use card_collection_manager::deck::{DeckCardRequest, DeckSection};
+use card_collection_manager::migrations::SCHEMA_VERSION;
+use card_collection_manager::search::Highlight;
+use card_collection_manager::series::{Series, SeriesRegion};
+use card_collection_manager::want::NewWant;
+use rusqlite::Connection;
+
+const LEGACY_V0: &str = include_str!("fixtures/legacy_v0.sql");
+const COLLECTION_V1: &str = include_str!("fixtures/collection_v1.sql");
+
+// Fresh database file in the temp dir, unique per test
+fn temp_db(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("ccm-migration-{}-{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+// Build a database at `version` holding the collection fixture
+fn fixture_at_version(path: &Path, version: i32) {
+    let path = path.to_str().unwrap();
+    DatabaseConnection::new(path)
+        .unwrap()
+        .migrate_to(1)
+        .unwrap();
+    Connection::open(path)
+        .unwrap()
+        .execute_batch(COLLECTION_V1)
+        .unwrap();
+    DatabaseConnection::new(path)
+        .unwrap()
+        .migrate_to(version)
+        .unwrap();
+}
+
+fn assert_fixture_collection(db: &DatabaseConnection) {
+    assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+
+    let cards = db.get_cards(None).unwrap();
+    assert_eq!(cards.len(), 2);
+    let blue_eyes = cards.iter().find(|c| c.number == "LOB-001").unwrap();
+    assert_eq!(blue_eyes.name, "Blue-Eyes White Dragon");
+    assert_eq!(blue_eyes.in_collection, 2);
+    assert_eq!(blue_eyes.copies.len(), 2);
+    assert_eq!(blue_eyes.rarity.name, "Ultra Rare");
+    assert_eq!(blue_eyes.series.prefix.as_deref(), Some("LOB"));
+
+    // existing cards are in the region of their number, dated like the series
+    assert_eq!(blue_eyes.region, "");
+    let regions = db.get_series_regions(blue_eyes.series.id.unwrap()).unwrap();
+    assert_eq!(regions.len(), 1);
+    assert_eq!(regions[0].region.language, "EN");
+    assert_eq!(
+        regions[0].region.release_date.as_deref(),
+        Some("2002-03-08")
+    );
+    assert_eq!((regions[0].cards, regions[0].owned_cards), (2, 1));
+
+    // existing cards are added to the search index
+    let hits = db.search_cards("blue eyes", None, Highlight::HTML).unwrap();
+    assert_eq!(hits[0].card.number, "LOB-001");
+}
+
+#[test]
+fn test_new_database_is_at_latest_version() {
+    let db = setup(":memory:").unwrap();
+    assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+}
+
+#[test]
+fn test_upgrade_legacy_unversioned_database() {
+    let path = temp_db("legacy");
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch(LEGACY_V0).unwrap();
+    conn.execute_batch(COLLECTION_V1).unwrap();
+    drop(conn);
+
+    let db = setup(path.to_str().unwrap()).unwrap();
+    assert_fixture_collection(&db);
+    drop(db);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_upgrade_from_every_version() {
+    for version in 1..=SCHEMA_VERSION {
+        let path = temp_db(&format!("v{}", version));
+        fixture_at_version(&path, version);
+
+        let db = DatabaseConnection::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(db.schema_version().unwrap(), version);
+        drop(db);
+
+        let db = setup(path.to_str().unwrap()).unwrap();
+        assert_fixture_collection(&db);
+        drop(db);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn test_migrate_is_idempotent() {
+    let db = setup(":memory:").unwrap();
+    db.migrate().unwrap();
+    db.migrate().unwrap();
+    assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+}
+
+#[test]
+fn test_refuses_newer_database() {
+    let path = temp_db("newer");
+    let conn = Connection::open(&path).unwrap();
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+        .unwrap();
+    drop(conn);
+
+    let err = setup(path.to_str().unwrap()).err().unwrap();
+    assert!(err.to_string().contains("newer than the supported version"));
+
+    // the database must be left untouched
+    let conn = Connection::open(&path).unwrap();
+    let version: i32 = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .unwrap();
+    assert_eq!(version, SCHEMA_VERSION + 1);
+    drop(conn);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_printings_are_grouped_by_name() {
+    let path = temp_db("identities");
+    fixture_at_version(&path, 8);
+    // a second printing of Blue-Eyes, spelled differently and with passcode and text
+    Connection::open(&path)
+        .unwrap()
+        .execute_batch(
+            "INSERT INTO cards (name, series_id, collection_number, number, rarity_id,
+                card_type_id, passcode, text)
+             VALUES ('blue-eyes white dragon', 1, 1, 'LOB-E001', 2, 1, 89631139,
+                'This legendary dragon is a powerful engine of destruction.')",
+        )
+        .unwrap();
+
+    let db = setup(path.to_str().unwrap()).unwrap();
+    let identities = db.get_identities(None, None).unwrap();
+    assert_eq!(identities.len(), 2);
+    let blue_eyes = &identities[0];
+    assert_eq!(blue_eyes.identity.name, "Blue-Eyes White Dragon");
+    assert_eq!(blue_eyes.identity.passcode, Some(89631139));
+    assert_eq!(blue_eyes.owned_copies, 2);
+    let numbers: Vec<&str> = blue_eyes
+        .printings
+        .iter()
+        .map(|p| p.number.as_str())
+        .collect();
+    assert_eq!(numbers, vec!["LOB-001", "LOB-E001"]);
+
+    // both printings share name, passcode and text, also in the search index
+    let card = db.get_card("LOB-001").unwrap();
+    assert_eq!(card.identity_id, blue_eyes.identity.id);
+    assert_eq!(card.passcode, Some(89631139));
+    let hits = db
+        .search_cards("engine destruction", None, Highlight::HTML)
+        .unwrap();
+    assert_eq!(hits.len(), 2);
+    drop(db);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_deletes_cascade_through_foreign_keys() {
+    let path = temp_db("cascade");
+    let db = setup(path.to_str().unwrap()).unwrap();
+    let series_id = db
+        .insert_series(&Series {
+            id: None,
+            name: "Cascade".into(),
+            release_date: "2025-09-05".into(),
+            n_cards: 1,
+            prefix: Some("CA".into()),
+        })
+        .unwrap();
+    db.create_series_region(&SeriesRegion {
+        id: None,
+        series_id,
+        region: "EN".into(),
+        language: String::new(),
+        release_date: None,
+        n_cards: 1,
+    })
+    .unwrap();
+    db.insert_card(&DatabaseCard {
+        name: "Cascade Card".into(),
+        series_id,
+        number: "CA-EN001".into(),
+        collection_number: 1,
+        in_collection: 0,
+        rarity_id: 1,
+        card_type_id: 1,
+    })
+    .unwrap();
+    db.upsert_want(&NewWant {
+        number: "CA-EN001".into(),
+        quantity: 1,
+        max_price: None,
+        priority: 0,
+    })
+    .unwrap();
+    db.create_deck("Cascade").unwrap();
+    db.add_deck_card(
+        "Cascade",
+        &DeckCardRequest {
+            card_name: "Cascade Card".into(),
+            section: DeckSection::Main,
+            quantity: 1,
+        },
+    )
+    .unwrap();
+
+    db.delete_card("CA-EN001").unwrap();
+    db.delete_deck("Cascade").unwrap();
+    db.delete_series(series_id).unwrap();
+
+    let conn = Connection::open(&path).unwrap();
+    for table in ["wants", "deck_cards", "series_regions"] {
+        let rows: i32 = conn
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(rows, 0, "{} left behind", table);
+    }
+    drop(db);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_flip_and_union_become_effect_types() {
+    let path = temp_db("effect-subtypes");
+    fixture_at_version(&path, 10);
+    // the old seeds, with a union effect type created by hand next to them
+    Connection::open(&path)
+        .unwrap()
+        .execute_batch(
+            "INSERT INTO card_type (maintype, subtype)
+             VALUES ('Monster', 'Flip'), ('Monster', 'Union'), ('Monster', 'Union Effect');
+             INSERT INTO card_identities (name) VALUES ('Man-Eater Bug'), ('Dragon Piper');
+             INSERT INTO cards (series_id, collection_number, number, rarity_id, card_type_id,
+                identity_id)
+             SELECT 1, 3, 'LOB-003', 1, t.id, i.id FROM card_type t, card_identities i
+             WHERE t.subtype = 'Flip' AND i.name = 'Man-Eater Bug';
+             INSERT INTO cards (series_id, collection_number, number, rarity_id, card_type_id,
+                identity_id)
+             SELECT 1, 4, 'LOB-004', 1, t.id, i.id FROM card_type t, card_identities i
+             WHERE t.subtype = 'Union' AND i.name = 'Dragon Piper';",
+        )
+        .unwrap();
+
+    let db = setup(path.to_str().unwrap()).unwrap();
+    assert_eq!(
+        db.get_card("LOB-003").unwrap().cardtype.display(),
+        "Flip Effect Monster"
+    );
+    assert_eq!(
+        db.get_card("LOB-004").unwrap().cardtype.display(),
+        "Union Effect Monster"
+    );
+    let subtypes: Vec<String> = db
+        .get_card_types()
+        .unwrap()
+        .into_iter()
+        .filter(|usage| usage.cardtype.main == "Monster")
+        .map(|usage| usage.cardtype.sub)
+        .collect();
+    assert!(!subtypes.contains(&"Flip".to_string()));
+    assert!(!subtypes.contains(&"Union".to_string()));
+    assert_eq!(subtypes.iter().filter(|s| *s == "Union Effect").count(), 1);
+    drop(db);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use card_collection_manager::pool::DatabasePool;
+use card_collection_manager::series::Series;
+
+// Fresh database file in the temp dir, unique per test
+fn temp_db(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ccm-pool-{}-{}.db", name, std::process::id()));
+    remove_db(&path);
+    path
+}
+
+// Remove the database with its write-ahead log
+fn remove_db(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
+
+fn series(name: &str) -> Series {
+    Series {
+        id: None,
+        name: name.into(),
+        release_date: "2025-09-05".into(),
+        n_cards: 10,
+        prefix: None,
+    }
+}
+
+#[test]
+fn test_readers_see_committed_writes() {
+    let path = temp_db("commit");
+    let pool = DatabasePool::open(path.to_str().unwrap(), 2).unwrap();
+    assert_eq!(pool.readers(), 2);
+
+    pool.write().insert_series(&series("Written")).unwrap();
+    let names: Vec<String> = pool
+        .read()
+        .get_unique_series()
+        .unwrap()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(names, vec!["Written"]);
+
+    // read connections refuse changes
+    let err = pool.read().insert_series(&series("Refused")).unwrap_err();
+    assert!(err.to_string().contains("readonly"), "{}", err);
+
+    drop(pool);
+    remove_db(&path);
+}
+
+#[test]
+fn test_reads_do_not_wait_for_each_other_or_the_writer() {
+    let path = temp_db("parallel");
+    let pool = DatabasePool::open(path.to_str().unwrap(), 3).unwrap();
+
+    // all readers are taken at the same time while the writer is busy
+    let writer = pool.write();
+    writer.insert_series(&series("Before")).unwrap();
+    let barrier = Barrier::new(3);
+    thread::scope(|s| {
+        for _ in 0..3 {
+            s.spawn(|| {
+                let reader = pool.read();
+                barrier.wait();
+                assert_eq!(reader.get_unique_series().unwrap().len(), 1);
+            });
+        }
+    });
+    drop(writer);
+
+    drop(pool);
+    remove_db(&path);
+}
+
+#[test]
+fn test_in_memory_databases_read_through_the_writer() {
+    let pool = DatabasePool::open(":memory:", 4).unwrap();
+    assert_eq!(pool.readers(), 0);
+
+    pool.write().insert_series(&series("Memory")).unwrap();
+    assert_eq!(pool.read().get_unique_series().unwrap().len(), 1);
+}
+
+#[test]
+fn test_connections_survive_a_panic() {
+    let path = temp_db("panic");
+    let pool = Arc::new(DatabasePool::open(path.to_str().unwrap(), 1).unwrap());
+
+    for take in [DatabasePool::read, DatabasePool::write] {
+        let shared = pool.clone();
+        let result = thread::spawn(move || {
+            let _conn = take(&shared);
+            panic!("request failed");
+        })
+        .join();
+        assert!(result.is_err());
+    }
+
+    pool.write().insert_series(&series("After")).unwrap();
+    assert_eq!(pool.read().get_unique_series().unwrap().len(), 1);
+
+    drop(pool);
+    remove_db(&path);
+}
@@ -0,0 +1,232 @@
+use card_collection_manager::card::{CopyAttributes, DatabaseCard};
+use card_collection_manager::db::{DatabaseConnection, setup};
+use card_collection_manager::identity::{Attribute, CardStats};
+use card_collection_manager::query::{Field, Op, Query, Term, TermKind};
+use card_collection_manager::series::Series;
+
+fn field(field: Field, op: Op, value: &str) -> Term {
+    Term {
+        negated: false,
+        kind: TermKind::Field {
+            field,
+            op,
+            value: value.into(),
+        },
+    }
+}
+
+fn error_at(query: &str) -> (usize, String) {
+    let e = Query::parse(query).unwrap_err();
+    (e.position, e.message)
+}
+
+#[test]
+fn test_parse_terms() {
+    let query =
+        Query::parse(r#"rarity:"ultra rare" type:trap series:LOB owned>0 name:dragon"#).unwrap();
+    assert_eq!(
+        query.terms,
+        vec![
+            field(Field::Rarity, Op::Match, "ultra rare"),
+            field(Field::Type, Op::Match, "trap"),
+            field(Field::Series, Op::Match, "LOB"),
+            field(Field::Owned, Op::Gt, "0"),
+            field(Field::Name, Op::Match, "dragon"),
+        ]
+    );
+    assert!(!query.is_plain_text());
+}
+
+#[test]
+fn test_parse_operators_negation_and_text() {
+    let query = Query::parse(r#"  -type:spell owned>=2 owned<=3 year<2005 year!=2003 number=LOB-001 "blue eyes" magician "#)
+        .unwrap();
+    let mut negated = field(Field::Type, Op::Match, "spell");
+    negated.negated = true;
+    assert_eq!(
+        query.terms,
+        vec![
+            negated,
+            field(Field::Owned, Op::Ge, "2"),
+            field(Field::Owned, Op::Le, "3"),
+            field(Field::Year, Op::Lt, "2005"),
+            field(Field::Year, Op::Ne, "2003"),
+            field(Field::Number, Op::Eq, "LOB-001"),
+            Term {
+                negated: false,
+                kind: TermKind::Text("blue eyes".into())
+            },
+            Term {
+                negated: false,
+                kind: TermKind::Text("magician".into())
+            },
+        ]
+    );
+    assert_eq!(query.text(), "blue eyes magician");
+
+    assert!(Query::parse("dark magician").unwrap().is_plain_text());
+    assert!(!Query::parse("-dark").unwrap().is_plain_text());
+    assert!(Query::parse("   ").unwrap().terms.is_empty());
+    assert_eq!(
+        Query::parse(r#"name:"say \"hi\"""#).unwrap().terms,
+        vec![field(Field::Name, Op::Match, "say \"hi\"")]
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let (position, message) = error_at("type:trap colour:red");
+    assert_eq!(position, 10);
+    assert!(message.contains("unknown field 'colour'"));
+    assert!(message.contains("rarity"));
+
+    let (position, message) = error_at(r#"name:"dark magician"#);
+    assert_eq!(position, 5);
+    assert!(message.contains("unterminated"));
+
+    let (position, message) = error_at("owned>many");
+    assert_eq!(position, 6);
+    assert!(message.contains("expects a number"));
+
+    let (position, message) = error_at("name>dragon");
+    assert_eq!(position, 4);
+    assert!(message.contains("can only be compared"));
+
+    let (position, message) = error_at("rarity: rare");
+    assert_eq!(position, 7);
+    assert!(message.contains("missing value"));
+
+    let (_, message) = error_at("-");
+    assert!(message.contains("expected a search term"));
+
+    let (position, message) = error_at(r#"name:"dark"magician"#);
+    assert_eq!(position, 11);
+    assert!(message.contains("whitespace"));
+
+    let e = Query::parse("owned>x").unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "at position 7: 'owned' expects a number, found 'x'"
+    );
+}
+
+fn setup_with_cards() -> DatabaseConnection {
+    let db = setup(":memory:").unwrap();
+    let lob = db
+        .insert_series(&Series {
+            id: None,
+            name: "Legend of Blue Eyes White Dragon".into(),
+            release_date: "March 8, 2002".into(),
+            n_cards: 3,
+            prefix: Some("LOB".into()),
+        })
+        .unwrap();
+    let mrd = db
+        .insert_series(&Series {
+            id: None,
+            name: "Metal Raiders".into(),
+            release_date: "June 26, 2002".into(),
+            n_cards: 1,
+            prefix: Some("MRD".into()),
+        })
+        .unwrap();
+    // (name, series, number, rarity id, card type id)
+    let cards = [
+        ("Blue-Eyes White Dragon", lob, "LOB-001", 4, 5), // Ultra Rare, Normal Monster
+        ("Trap Hole", lob, "LOB-002", 1, 11),             // Common, Normal Trap Card
+        ("Dragon Capture Jar", lob, "LOB-003", 4, 11),    // Ultra Rare, Normal Trap Card
+        ("Mirror Force", mrd, "MRD-001", 4, 11),          // Ultra Rare, Normal Trap Card
+    ];
+    for (n, (name, series_id, number, rarity_id, card_type_id)) in cards.into_iter().enumerate() {
+        db.insert_card(&DatabaseCard {
+            name: name.into(),
+            series_id,
+            number: number.into(),
+            collection_number: n as i32 + 1,
+            in_collection: 0,
+            rarity_id,
+            card_type_id,
+        })
+        .unwrap();
+    }
+    db.collect_card("LOB-003", Some(2), &CopyAttributes::default())
+        .unwrap();
+    db.collect_card("MRD-001", None, &CopyAttributes::default())
+        .unwrap();
+    db
+}
+
+fn find(db: &DatabaseConnection, query: &str) -> Vec<String> {
+    db.find_cards(&Query::parse(query).unwrap())
+        .unwrap()
+        .into_iter()
+        .map(|c| c.number)
+        .collect()
+}
+
+#[test]
+fn test_find_cards_with_query() {
+    let db = setup_with_cards();
+    assert_eq!(
+        find(
+            &db,
+            r#"rarity:"ultra rare" type:trap series:LOB owned>0 name:dragon"#
+        ),
+        vec!["LOB-003"]
+    );
+    assert_eq!(find(&db, "").len(), 4);
+    assert_eq!(find(&db, "rarity:common"), vec!["LOB-002"]);
+    assert!(find(&db, "rarity:rare").is_empty());
+    assert_eq!(find(&db, "type:trap").len(), 3);
+    assert_eq!(find(&db, r#"type="normal monster""#), vec!["LOB-001"]);
+    assert_eq!(find(&db, "-type:trap"), vec!["LOB-001"]);
+    assert_eq!(find(&db, "series:metal"), vec!["MRD-001"]);
+    assert_eq!(find(&db, "series:mrd"), vec!["MRD-001"]);
+    assert_eq!(find(&db, "owned>=1"), vec!["LOB-003", "MRD-001"]);
+    assert_eq!(find(&db, "owned=0 type:trap"), vec!["LOB-002"]);
+    assert_eq!(find(&db, "number=lob-001"), vec!["LOB-001"]);
+    assert_eq!(find(&db, "name!=\"trap hole\" series:LOB").len(), 2);
+    assert_eq!(find(&db, "year:2002").len(), 4);
+    assert!(find(&db, "year>2002").is_empty());
+    // words without a field also match the series name and number
+    assert_eq!(find(&db, "dragon type:trap"), vec!["LOB-002", "LOB-003"]);
+    assert_eq!(find(&db, "capt type:trap"), vec!["LOB-003"]);
+    assert_eq!(find(&db, "-dragon"), vec!["MRD-001"]);
+}
+
+#[test]
+fn test_query_values_are_parameters() {
+    let db = setup_with_cards();
+    assert!(find(&db, r#"name:"'; DROP TABLE cards; --""#).is_empty());
+    assert_eq!(find(&db, "").len(), 4);
+    // LIKE wildcards in a value are matched literally
+    assert!(find(&db, "name:%").is_empty());
+    assert!(find(&db, "number:_001").is_empty());
+    assert_eq!(find(&db, "number:-001"), vec!["LOB-001", "MRD-001"]);
+}
+
+#[test]
+fn test_find_cards_by_stats() {
+    let db = setup_with_cards();
+    let stats = CardStats {
+        attribute: Some(Attribute::Light),
+        level: Some(8),
+        monster_type: Some("Dragon".into()),
+        atk: Some(3000),
+        def: Some(2500),
+        ..Default::default()
+    };
+    db.set_card_stats("LOB-001", &stats).unwrap();
+
+    assert_eq!(find(&db, "attribute:light"), vec!["LOB-001"]);
+    assert_eq!(find(&db, "race:dragon atk>=3000 level=8"), vec!["LOB-001"]);
+    assert!(find(&db, "race:drag").is_empty());
+    assert!(find(&db, "def>2500").is_empty());
+    assert!(find(&db, "rank:8").is_empty());
+    // spells and traps have no attribute
+    assert_eq!(find(&db, "-attribute:dark").len(), 4);
+    assert_eq!(
+        Query::parse("atk:?").unwrap_err().message,
+        "'atk' expects a number, found '?'"
+    );
+}
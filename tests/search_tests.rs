@@ -0,0 +1,134 @@
+use card_collection_manager::card::DatabaseCard;
+use card_collection_manager::db::{DatabaseConnection, setup};
+use card_collection_manager::search::{Highlight, fts_query};
+use card_collection_manager::series::Series;
+
+const MARK: Highlight = Highlight {
+    open: "[",
+    close: "]",
+    html: false,
+};
+
+fn setup_with_cards() -> DatabaseConnection {
+    let db = setup(":memory:").unwrap();
+    let series_id = db
+        .insert_series(&Series {
+            id: None,
+            name: "Legend of Blue Eyes".into(),
+            release_date: "March 8, 2002".into(),
+            n_cards: 3,
+            prefix: Some("LOB".into()),
+        })
+        .unwrap();
+    for (n, name) in ["Blue-Eyes White Dragon", "Dark Magician", "Trap Hole"]
+        .iter()
+        .enumerate()
+    {
+        db.insert_card(&DatabaseCard {
+            name: name.to_string(),
+            series_id,
+            number: format!("LOB-EN00{}", n + 1),
+            collection_number: n as i32 + 1,
+            in_collection: 0,
+            rarity_id: 1,
+            card_type_id: 1,
+        })
+        .unwrap();
+    }
+    db.set_card_text(
+        "LOB-EN002",
+        "The ultimate wizard in terms of attack and defense.",
+    )
+    .unwrap();
+    db.set_card_text(
+        "LOB-EN003",
+        "When your opponent Normal or Flip Summons a monster with 1000 or more ATK: Target that monster; destroy that target. Not a dragon.",
+    )
+    .unwrap();
+    db
+}
+
+fn numbers(db: &DatabaseConnection, query: &str) -> Vec<String> {
+    db.search_cards(query, None, MARK)
+        .unwrap()
+        .into_iter()
+        .map(|hit| hit.card.number)
+        .collect()
+}
+
+#[test]
+fn test_fts_query() {
+    assert_eq!(
+        fts_query("blue-eyes LOB").as_deref(),
+        Some("\"blue\"* \"eyes\"* \"LOB\"*")
+    );
+    assert_eq!(fts_query(" \"* - "), None);
+}
+
+#[test]
+fn test_search_by_prefix_number_series_and_text() {
+    let db = setup_with_cards();
+    assert_eq!(numbers(&db, "magi"), vec!["LOB-EN002"]);
+    assert_eq!(numbers(&db, "LOB-EN003"), vec!["LOB-EN003"]);
+    assert_eq!(numbers(&db, "legend").len(), 3);
+    assert_eq!(numbers(&db, "wizard"), vec!["LOB-EN002"]);
+    assert_eq!(numbers(&db, "trap hole"), vec!["LOB-EN003"]);
+    assert!(numbers(&db, "exodia").is_empty());
+    assert!(numbers(&db, "").is_empty());
+}
+
+#[test]
+fn test_search_ranks_names_first_and_highlights() {
+    let db = setup_with_cards();
+    // "dragon" is in the name of LOB-EN001 but only in the text of LOB-EN003
+    let hits = db.search_cards("dragon", None, MARK).unwrap();
+    let found: Vec<_> = hits.iter().map(|h| h.card.number.as_str()).collect();
+    assert_eq!(found, vec!["LOB-EN001", "LOB-EN003"]);
+    assert!(hits[0].rank < hits[1].rank);
+    assert_eq!(hits[0].name_highlight, "Blue-Eyes White [Dragon]");
+    assert_eq!(hits[0].snippet, None);
+    assert!(hits[1].snippet.as_ref().unwrap().contains("[dragon]"));
+
+    assert_eq!(db.search_cards("dragon", Some(1), MARK).unwrap().len(), 1);
+}
+
+#[test]
+fn test_html_highlights_escape_card_data() {
+    let db = setup_with_cards();
+    db.set_card_text("LOB-EN002", "Attack & <b>defense</b> of the wizard")
+        .unwrap();
+    let hits = db.search_cards("wizard", None, Highlight::HTML).unwrap();
+    assert_eq!(
+        hits[0].snippet.as_deref(),
+        Some("Attack &amp; &lt;b&gt;defense&lt;/b&gt; of the <mark>wizard</mark>")
+    );
+    assert_eq!(hits[0].name_highlight, "Dark Magician");
+}
+
+#[test]
+fn test_search_index_follows_changes() {
+    let db = setup_with_cards();
+    let mut card = DatabaseCard {
+        name: "Dark Magician Girl".into(),
+        series_id: 1,
+        number: "LOB-EN002".into(),
+        collection_number: 2,
+        in_collection: 0,
+        rarity_id: 1,
+        card_type_id: 1,
+    };
+    db.update_card("LOB-EN002", &card).unwrap();
+    assert_eq!(numbers(&db, "girl"), vec!["LOB-EN002"]);
+
+    card.number = "LOB-EN099".into();
+    db.update_card("LOB-EN002", &card).unwrap();
+    assert_eq!(numbers(&db, "LOB-EN099"), vec!["LOB-EN099"]);
+    assert!(numbers(&db, "LOB-EN002").is_empty());
+
+    // collecting copies updates cards without touching the index
+    db.collect_card("LOB-EN099", Some(2), &Default::default())
+        .unwrap();
+    let hits = db.search_cards("girl", None, MARK).unwrap();
+    assert_eq!(hits[0].card.in_collection, 2);
+    assert_eq!(hits[0].card.copies.len(), 2);
+}
@@ -0,0 +1,99 @@
+use card_collection_manager::card::DatabaseCard;
+use card_collection_manager::db::setup;
+use card_collection_manager::deck::DeckSection;
+use card_collection_manager::jsoncards::{PasscodeJson, PasscodesJson};
+use card_collection_manager::series::Series;
+use card_collection_manager::ydk::Ydk;
+
+const DECK_YDK: &str = include_str!("fixtures/deck.ydk");
+
+#[test]
+fn test_parse_ydk() {
+    let ydk = Ydk::parse(DECK_YDK).unwrap();
+    assert_eq!(ydk.main, vec![89631139, 89631139, 46986414, 12345678]);
+    assert_eq!(ydk.extra, vec![23995346]);
+    assert_eq!(ydk.side, vec![53129443]);
+
+    // writing and reading again gives the same deck
+    assert_eq!(Ydk::parse(&ydk.to_ydk_string()).unwrap(), ydk);
+}
+
+#[test]
+fn test_parse_invalid_ydk() {
+    let err = Ydk::parse("#main\n89631139\nBlue-Eyes\n").unwrap_err();
+    assert!(err.contains("line 3"));
+}
+
+#[test]
+fn test_passcodes_json_formats() {
+    let list: PasscodesJson =
+        serde_json::from_str(r#"[{"passcode": 46986414, "name": "Dark Magician"}]"#).unwrap();
+    assert_eq!(list.into_entries()[0].passcode, 46986414);
+
+    let ygoprodeck: PasscodesJson = serde_json::from_str(
+        r#"{"data": [{"id": 89631139, "name": "Blue-Eyes White Dragon", "type": "Normal Monster"}]}"#,
+    )
+    .unwrap();
+    assert_eq!(ygoprodeck.into_entries()[0].name, "Blue-Eyes White Dragon");
+}
+
+#[test]
+fn test_import_and_export_ydk() {
+    let db = setup(":memory:").unwrap();
+    let series_id = db
+        .insert_series(&Series {
+            id: None,
+            name: "Legend of Blue Eyes White Dragon".into(),
+            release_date: "March 8, 2002".into(),
+            n_cards: 126,
+            prefix: Some("LOB".into()),
+        })
+        .unwrap();
+    db.insert_card(&DatabaseCard {
+        name: "Blue-Eyes White Dragon".into(),
+        series_id,
+        number: "LOB-001".into(),
+        collection_number: 1,
+        in_collection: 2,
+        rarity_id: 4,
+        card_type_id: 5,
+    })
+    .unwrap();
+
+    let passcode = |passcode: i64, name: &str| PasscodeJson {
+        passcode,
+        name: name.into(),
+    };
+    db.load_passcodes(&[
+        passcode(89631139, "Blue-Eyes White Dragon"),
+        passcode(46986414, "Dark Magician"),
+        passcode(23995346, "Blue-Eyes Ultimate Dragon"),
+        passcode(53129443, "Dark Hole"),
+    ])
+    .unwrap();
+    // the card picked up its passcode from the mapping
+    assert_eq!(db.get_cards(None).unwrap()[0].passcode, Some(89631139));
+
+    let report = db
+        .import_ydk("Kaiba", &Ydk::parse(DECK_YDK).unwrap())
+        .unwrap();
+    assert_eq!(report.resolved, 5);
+    assert_eq!(
+        report.unresolved,
+        vec![(DeckSection::Main, "12345678".to_string())]
+    );
+    assert!(db.import_ydk("Kaiba", &Ydk::default()).is_err());
+
+    let deck = db.get_deck("Kaiba").unwrap();
+    assert_eq!(deck.entries[0].card_name, "Blue-Eyes White Dragon");
+    assert_eq!(deck.entries[0].quantity, 2);
+
+    let (ydk, report) = db.export_ydk("Kaiba").unwrap();
+    assert!(report.unresolved.is_empty());
+    assert_eq!(ydk.main, vec![89631139, 89631139, 46986414]);
+    assert_eq!(ydk.extra, vec![23995346]);
+    assert_eq!(ydk.side, vec![53129443]);
+
+    let (collection, _) = db.export_collection_ydk().unwrap();
+    assert_eq!(collection.main, vec![89631139, 89631139]);
+}